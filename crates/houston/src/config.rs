@@ -19,6 +19,9 @@ pub struct Config {
     /// override_api_key is used for overriding the API key returned
     /// when loading a profile
     pub override_api_key: Option<String>,
+
+    /// api_key_file is the file override_api_key was read from, if any
+    pub api_key_file: Option<Utf8PathBuf>,
 }
 
 impl Config {
@@ -59,6 +62,23 @@ impl Config {
         Ok(Config {
             home,
             override_api_key,
+            api_key_file: None,
+        })
+    }
+
+    /// Overrides the API key returned when loading a profile with the
+    /// contents of `path`, ignoring any surrounding whitespace. This takes
+    /// precedence over an `override_api_key` passed to [`Config::new`].
+    pub fn with_api_key_file(self, path: &Utf8Path) -> Result<Config, HoustonProblem> {
+        let contents = Fs::read_file(path)?;
+        let api_key = contents.trim();
+        if api_key.is_empty() {
+            return Err(HoustonProblem::EmptyApiKeyFile(path.to_string()));
+        }
+        Ok(Config {
+            override_api_key: Some(api_key.to_string()),
+            api_key_file: Some(path.to_path_buf()),
+            ..self
         })
     }
 
@@ -102,9 +122,59 @@ struct Elv2Toml {
 #[cfg(test)]
 mod tests {
     use super::Config;
-    use assert_fs::TempDir;
+    use crate::{CredentialOrigin, HoustonProblem, Profile};
+    use assert_fs::{prelude::*, TempDir};
     use camino::Utf8PathBuf;
     use std::convert::TryFrom;
+
+    fn config_with_api_key_file(contents: Option<&str>) -> Result<Config, HoustonProblem> {
+        let tmp_home = TempDir::new().unwrap();
+        let tmp_path = Utf8PathBuf::try_from(tmp_home.path().to_path_buf()).unwrap();
+        let key_file = tmp_home.child("api-key");
+        if let Some(contents) = contents {
+            key_file.write_str(contents).unwrap();
+        }
+        let key_path = Utf8PathBuf::try_from(key_file.path().to_path_buf()).unwrap();
+        Config::new(Some(&tmp_path), Some("user:env:key".to_string()))?.with_api_key_file(&key_path)
+    }
+
+    #[test]
+    fn it_reads_the_api_key_from_a_file() {
+        let config = config_with_api_key_file(Some("user:file:key")).unwrap();
+        let credential = Profile::get_credential("default", &config).unwrap();
+
+        assert_eq!(credential.api_key, "user:file:key");
+        assert!(matches!(
+            credential.origin,
+            CredentialOrigin::ApiKeyFile(path) if path.ends_with("api-key")
+        ));
+    }
+
+    #[test]
+    fn it_trims_whitespace_around_the_api_key_in_a_file() {
+        let config = config_with_api_key_file(Some("  user:file:key\r\n\n")).unwrap();
+
+        assert_eq!(config.override_api_key.as_deref(), Some("user:file:key"));
+    }
+
+    #[test]
+    fn it_errors_on_a_missing_api_key_file() {
+        let error = config_with_api_key_file(None).unwrap_err();
+
+        assert!(
+            matches!(error, HoustonProblem::RoverStdError(_)),
+            "{:?}",
+            error
+        );
+        assert!(error.to_string().contains("api-key"), "{}", error);
+    }
+
+    #[test]
+    fn it_errors_on_an_empty_api_key_file() {
+        let error = config_with_api_key_file(Some(" \n")).unwrap_err();
+
+        assert!(matches!(error, HoustonProblem::EmptyApiKeyFile(_)));
+    }
     #[test]
     fn it_can_clear_global_config() {
         let tmp_home = TempDir::new().unwrap();
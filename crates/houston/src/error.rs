@@ -38,6 +38,10 @@ pub enum HoustonProblem {
     #[error("The API key associated with profile \"{0}\" is corrupt.")]
     CorruptedProfile(String),
 
+    /// EmptyApiKeyFile occurs when a file that should contain an API key only contains whitespace.
+    #[error("The API key file \"{0}\" is empty.")]
+    EmptyApiKeyFile(String),
+
     /// PathNotUtf8 occurs when Houston encounters a file path that is not valid UTF-8
     #[error(transparent)]
     PathNotUtf8(#[from] camino::FromPathBufError),
@@ -50,6 +54,15 @@ pub enum HoustonProblem {
     #[error(transparent)]
     TomlDeserialization(#[from] toml::de::Error),
 
+    /// InvalidSettings occurs when `rover.toml` can't be deserialized.
+    #[error("The settings in \"{path}\" are invalid: {source}")]
+    InvalidSettings {
+        /// The path of the settings file
+        path: String,
+        /// Why it couldn't be deserialized
+        source: toml::de::Error,
+    },
+
     /// io::Error occurs when any given std::io::Error arises.
     #[error(transparent)]
     IoError(#[from] io::Error),
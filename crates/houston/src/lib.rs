@@ -5,9 +5,11 @@
 mod config;
 mod error;
 mod profile;
+mod settings;
 
 pub use config::Config;
 pub use error::HoustonProblem;
+pub use settings::{RoutingUrlValidation, Settings};
 
 pub use profile::mask_key;
 /// Utilites for saving, loading, and deleting configuration profiles.
@@ -43,6 +43,9 @@ pub enum CredentialOrigin {
 
     /// The credential is from a profile
     ConfigFile(String),
+
+    /// The credential was read from a file containing only the API key
+    ApiKeyFile(PathBuf),
 }
 
 impl Profile {
@@ -73,7 +76,10 @@ impl Profile {
         let credential = match &config.override_api_key {
             Some(api_key) => Credential {
                 api_key: api_key.to_string(),
-                origin: CredentialOrigin::EnvVar,
+                origin: match &config.api_key_file {
+                    Some(path) => CredentialOrigin::ApiKeyFile(path.clone()),
+                    None => CredentialOrigin::EnvVar,
+                },
             },
             None => {
                 let opts = LoadOpts { sensitive: true };
@@ -129,6 +135,9 @@ impl Profile {
     pub fn delete(name: &str, config: &Config) -> Result<(), HoustonProblem> {
         let dir = Profile::dir(name, config);
         tracing::debug!(dir = ?dir);
+        if !dir.exists() {
+            return Err(HoustonProblem::ProfileNotFound(name.to_string()));
+        }
         Fs::remove_dir_all(dir)?;
         Ok(())
     }
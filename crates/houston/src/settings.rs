@@ -0,0 +1,106 @@
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+
+use crate::{Config, HoustonProblem};
+use rover_std::{Fs, RoverStdError};
+
+/// Settings that apply to every profile, read from
+/// `$APOLLO_CONFIG_HOME/rover.toml`. Anything left out keeps its default.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    /// How strictly `rover subgraph publish` checks routing urls when none
+    /// of its flags say otherwise
+    #[serde(default)]
+    pub routing_url_validation: RoutingUrlValidation,
+}
+
+/// How strictly routing urls are checked before they're published
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoutingUrlValidation {
+    /// Routing urls that are only reachable locally fail the publish,
+    /// like with `--reject-local-routing-url`
+    Strict,
+    /// Problems with routing urls are warned about, or confirmed in a terminal
+    #[default]
+    Warn,
+    /// Routing urls aren't checked, like with `--allow-invalid-routing-url`
+    Off,
+}
+
+impl Config {
+    /// Reads the settings in `rover.toml`, or the defaults if there isn't one
+    pub fn settings(&self) -> Result<Settings, HoustonProblem> {
+        let path = self.get_settings_toml_path();
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+        match Fs::read_file(&path) {
+            Ok(contents) => {
+                toml::from_str(&contents).map_err(|source| HoustonProblem::InvalidSettings {
+                    path: path.to_string(),
+                    source,
+                })
+            }
+            Err(RoverStdError::EmptyFile { .. }) => Ok(Settings::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn get_settings_toml_path(&self) -> Utf8PathBuf {
+        self.home.join("rover.toml")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RoutingUrlValidation, Settings};
+    use crate::{Config, HoustonProblem};
+    use assert_fs::{prelude::*, TempDir};
+    use camino::Utf8PathBuf;
+    use std::convert::TryFrom;
+
+    fn settings_from(contents: Option<&str>) -> Result<Settings, HoustonProblem> {
+        let tmp_home = TempDir::new().unwrap();
+        if let Some(contents) = contents {
+            tmp_home.child("rover.toml").write_str(contents).unwrap();
+        }
+        let tmp_path = Utf8PathBuf::try_from(tmp_home.path().to_path_buf()).unwrap();
+        Config::new(Some(&tmp_path), None)?.settings()
+    }
+
+    #[test]
+    fn it_reads_each_routing_url_validation_mode() {
+        for (value, expected) in [
+            ("strict", RoutingUrlValidation::Strict),
+            ("warn", RoutingUrlValidation::Warn),
+            ("off", RoutingUrlValidation::Off),
+        ] {
+            let settings =
+                settings_from(Some(&format!("routing_url_validation = \"{}\"\n", value))).unwrap();
+            assert_eq!(settings.routing_url_validation, expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn it_defaults_without_a_settings_file() {
+        assert_eq!(settings_from(None).unwrap(), Settings::default());
+        assert_eq!(settings_from(Some("")).unwrap(), Settings::default());
+        assert_eq!(
+            Settings::default().routing_url_validation,
+            RoutingUrlValidation::Warn
+        );
+    }
+
+    #[test]
+    fn it_errors_on_an_unknown_routing_url_validation_mode() {
+        let error = settings_from(Some("routing_url_validation = \"lenient\"\n")).unwrap_err();
+
+        assert!(
+            matches!(error, HoustonProblem::InvalidSettings { .. }),
+            "{:?}",
+            error
+        );
+        assert!(error.to_string().contains("rover.toml"), "{}", error);
+    }
+}
@@ -1,23 +1,38 @@
+use crate::blocking::Deadline;
 use crate::error::{EndpointKind, RoverClientError};
 
+use backoff::Error as BackoffError;
+use flate2::{write::GzEncoder, Compression};
 use graphql_client::{Error as GraphQLError, GraphQLQuery, Response as GraphQLResponse};
 use reqwest::{
     blocking::{Client as ReqwestClient, Response},
-    header::{HeaderMap, HeaderValue},
-    StatusCode,
+    header::{HeaderMap, HeaderValue, CONTENT_ENCODING, RETRY_AFTER},
+    Client as AsyncReqwestClient, Response as AsyncResponse, StatusCode,
 };
+use rover_std::redact;
 
 pub(crate) const JSON_CONTENT_TYPE: &str = "application/json";
 
 const MAX_ELAPSED_TIME: Option<Duration> =
     Some(Duration::from_secs(if cfg!(test) { 2 } else { 10 }));
 
+/// the error code Apollo Studio responds with when it doesn't accept an API key
+const UNAUTHENTICATED_CODE: &str = "UNAUTHENTICATED";
+
+/// the longest a `Retry-After` header can make Rover wait before retrying
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+use std::io::Write;
 use std::time::Duration;
 
 /// Represents a generic GraphQL client for making http requests.
 pub struct GraphQLClient {
     graphql_endpoint: String,
     client: ReqwestClient,
+    async_client: Option<AsyncReqwestClient>,
+    deadline: Option<Deadline>,
+    compression_threshold: Option<usize>,
+    trace_http: bool,
 }
 
 impl GraphQLClient {
@@ -27,9 +42,43 @@ impl GraphQLClient {
         GraphQLClient {
             graphql_endpoint: graphql_endpoint.to_string(),
             client,
+            async_client: None,
+            deadline: None,
+            compression_threshold: None,
+            trace_http: false,
+        }
+    }
+
+    /// Sends the requests made with [`GraphQLClient::post_no_retry_async`]
+    /// with `async_client`, which should be configured like the blocking one.
+    pub fn with_async_client(self, async_client: AsyncReqwestClient) -> GraphQLClient {
+        GraphQLClient {
+            async_client: Some(async_client),
+            ..self
+        }
+    }
+
+    /// Makes every request, including retries, give up once `deadline` passes.
+    pub fn with_deadline(self, deadline: Option<Deadline>) -> GraphQLClient {
+        GraphQLClient { deadline, ..self }
+    }
+
+    /// Gzip compresses the body of every request larger than `threshold` bytes,
+    /// such as publishes of large schemas. Requests are sent uncompressed
+    /// instead if the endpoint doesn't accept compressed ones.
+    pub fn with_compression_threshold(self, threshold: Option<usize>) -> GraphQLClient {
+        GraphQLClient {
+            compression_threshold: threshold,
+            ..self
         }
     }
 
+    /// Logs the headers and body of every request and response at info level,
+    /// with anything that looks like an API key redacted.
+    pub fn with_trace_http(self, trace_http: bool) -> GraphQLClient {
+        GraphQLClient { trace_http, ..self }
+    }
+
     /// Client method for making a GraphQL request.
     ///
     /// Takes one argument, `variables`. Returns an optional response.
@@ -46,7 +95,7 @@ impl GraphQLClient {
         let request_body = self.get_request_body::<Q>(variables)?;
         header_map.append("Content-Type", HeaderValue::from_str(JSON_CONTENT_TYPE)?);
         let response = self.execute(request_body, header_map, true, endpoint_kind);
-        GraphQLClient::handle_response::<Q>(response?, endpoint_kind)
+        self.receive::<Q>(response?, endpoint_kind)
     }
 
     /// Client method for making a GraphQL request.
@@ -65,7 +114,53 @@ impl GraphQLClient {
         let request_body = self.get_request_body::<Q>(variables)?;
         header_map.append("Content-Type", HeaderValue::from_str(JSON_CONTENT_TYPE)?);
         let response = self.execute(request_body, header_map, false, endpoint_kind);
-        GraphQLClient::handle_response::<Q>(response?, endpoint_kind)
+        self.receive::<Q>(response?, endpoint_kind)
+    }
+
+    /// Client method for making a GraphQL request without blocking, so that
+    /// several requests can be in flight at once.
+    ///
+    /// Takes one argument, `variables`. Returns an optional response.
+    /// Does not automatically retry requests. Fails unless an async client
+    /// was given with [`GraphQLClient::with_async_client`].
+    pub async fn post_no_retry_async<Q>(
+        &self,
+        variables: Q::Variables,
+        header_map: &mut HeaderMap,
+        endpoint_kind: EndpointKind,
+    ) -> Result<Q::ResponseData, RoverClientError>
+    where
+        Q: GraphQLQuery,
+    {
+        let client = self
+            .async_client
+            .as_ref()
+            .ok_or_else(|| RoverClientError::AdhocError {
+                msg: "There is no async client to send this request with.".to_string(),
+            })?;
+        let request_body = self.get_request_body::<Q>(variables)?;
+        header_map.append("Content-Type", HeaderValue::from_str(JSON_CONTENT_TYPE)?);
+        self.trace_request(&request_body, header_map);
+
+        if let Some(compressed_body) = self.compress(&request_body) {
+            let mut compressed_header_map = header_map.clone();
+            compressed_header_map.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            let result = self
+                .send_async(
+                    client,
+                    compressed_body,
+                    &compressed_header_map,
+                    endpoint_kind,
+                )
+                .await;
+            if !self.rejected_compression(&result) {
+                return self.receive_async::<Q>(result?, endpoint_kind).await;
+            }
+        }
+        let response = self
+            .send_async(client, request_body.into_bytes(), header_map, endpoint_kind)
+            .await?;
+        self.receive_async::<Q>(response, endpoint_kind).await
     }
 
     fn get_request_body<Q: GraphQLQuery>(
@@ -83,102 +178,270 @@ impl GraphQLClient {
         should_retry: bool,
         endpoint_kind: EndpointKind,
     ) -> Result<Response, RoverClientError> {
-        use backoff::{retry, Error as BackoffError, ExponentialBackoff};
+        self.trace_request(&request_body, header_map);
+
+        if let Some(compressed_body) = self.compress(&request_body) {
+            let mut compressed_header_map = header_map.clone();
+            compressed_header_map.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            let result = self.send(
+                compressed_body,
+                &compressed_header_map,
+                should_retry,
+                endpoint_kind,
+            );
+            if !self.rejected_compression(&result) {
+                return result;
+            }
+        }
+        self.send(
+            request_body.into_bytes(),
+            header_map,
+            should_retry,
+            endpoint_kind,
+        )
+    }
 
+    fn trace_request(&self, request_body: &str, header_map: &HeaderMap) {
         tracing::trace!(request_headers = ?header_map);
-        tracing::debug!("Request Body: {}", request_body);
+        tracing::trace!("Request Body: {}", request_body);
+        if self.trace_http {
+            tracing::info!(
+                "request to {}: {} {}",
+                self.graphql_endpoint,
+                redact(&format!("{:?}", header_map)),
+                redact(request_body)
+            );
+        }
+    }
+
+    /// whether a compressed request failed because the endpoint doesn't accept
+    /// compressed requests, like older ones that reject bodies they can't decode,
+    /// so it has to be sent again uncompressed
+    fn rejected_compression<T>(&self, result: &Result<T, RoverClientError>) -> bool {
+        let rejected = matches!(
+            result,
+            Err(RoverClientError::SendRequest { source, .. })
+                if source.status() == Some(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+        );
+        if rejected {
+            tracing::info!(
+                "{} does not accept gzip compressed requests, sending it uncompressed",
+                self.graphql_endpoint
+            );
+        }
+        rejected
+    }
+
+    /// gzip compresses `request_body` if it's over the compression threshold
+    fn compress(&self, request_body: &str) -> Option<Vec<u8>> {
+        let threshold = self.compression_threshold?;
+        if request_body.len() <= threshold {
+            return None;
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        match encoder
+            .write_all(request_body.as_bytes())
+            .and_then(|_| encoder.finish())
+        {
+            Ok(compressed_body) => {
+                tracing::debug!(
+                    "compressed the request body from {} to {} bytes",
+                    request_body.len(),
+                    compressed_body.len()
+                );
+                Some(compressed_body)
+            }
+            Err(e) => {
+                tracing::debug!("could not compress the request body: {}", e);
+                None
+            }
+        }
+    }
+
+    fn send(
+        &self,
+        request_body: Vec<u8>,
+        header_map: &HeaderMap,
+        should_retry: bool,
+        endpoint_kind: EndpointKind,
+    ) -> Result<Response, RoverClientError> {
+        use backoff::{retry, ExponentialBackoff};
+
+        let remaining = match &self.deadline {
+            Some(deadline) => {
+                deadline.start();
+                Some(
+                    deadline
+                        .remaining()
+                        .ok_or(RoverClientError::OperationTimedOut {
+                            timeout: deadline.timeout(),
+                        })?,
+                )
+            }
+            None => None,
+        };
         let graphql_operation = || {
-            let response = self
+            let mut request = self
                 .client
                 .post(&self.graphql_endpoint)
                 .headers(header_map.clone())
-                .body(request_body.clone())
-                .send();
-
-            match response {
-                Err(client_error) => {
-                    if client_error.is_timeout() || client_error.is_connect() {
-                        Err(BackoffError::transient(client_error))
-                    } else if client_error.is_body()
-                        || client_error.is_decode()
-                        || client_error.is_builder()
-                        || client_error.is_redirect()
-                    {
-                        Err(BackoffError::Permanent(client_error))
-                    } else if client_error.is_request() {
-                        if let Some(hyper_error) =
-                            get_source_error_type::<hyper::Error>(&client_error)
-                        {
-                            if hyper_error.is_incomplete_message() {
-                                Err(BackoffError::transient(client_error))
-                            } else {
-                                Err(BackoffError::Permanent(client_error))
-                            }
-                        } else {
-                            Err(BackoffError::Permanent(client_error))
-                        }
-                    } else {
-                        Err(BackoffError::Permanent(client_error))
-                    }
-                }
-                Ok(success) => {
-                    if let Err(status_error) = success.error_for_status_ref() {
-                        if let Some(response_status) = status_error.status() {
-                            if response_status.is_server_error()
-                                || response_status.is_client_error()
-                                || response_status.is_redirection()
-                            {
-                                if matches!(response_status, StatusCode::BAD_REQUEST) {
-                                    if let Ok(text) = success.text() {
-                                        tracing::debug!("{}", text);
-                                    }
-                                    Err(BackoffError::Permanent(status_error))
-                                } else {
-                                    Err(BackoffError::transient(status_error))
-                                }
-                            } else {
-                                Err(BackoffError::Permanent(status_error))
+                .body(request_body.clone());
+            if let Some(deadline) = &self.deadline {
+                // a retry can start just as the deadline passes, which times out right away
+                request = request.timeout(deadline.remaining().unwrap_or(Duration::ZERO));
+            }
+            match request.send() {
+                Err(client_error) => Err(classify_send_error(client_error)),
+                Ok(success) => match success.error_for_status_ref() {
+                    Err(status_error) => {
+                        let error = classify_status_error(status_error, success.headers());
+                        if let BackoffError::Permanent(_) = error {
+                            if let Ok(text) = success.text() {
+                                tracing::debug!("{}", text);
                             }
-                        } else {
-                            Err(BackoffError::Permanent(status_error))
                         }
-                    } else {
-                        Ok(success)
+                        Err(error)
                     }
-                }
+                    Ok(_) => Ok(success),
+                },
             }
         };
 
         if should_retry {
             let backoff_strategy = ExponentialBackoff {
-                max_elapsed_time: MAX_ELAPSED_TIME,
+                max_elapsed_time: match (MAX_ELAPSED_TIME, remaining) {
+                    (Some(max_elapsed_time), Some(remaining)) => {
+                        Some(max_elapsed_time.min(remaining))
+                    }
+                    (max_elapsed_time, _) => max_elapsed_time,
+                },
                 ..Default::default()
             };
 
-            retry(backoff_strategy, graphql_operation).map_err(|e| match e {
-                BackoffError::Permanent(reqwest_error)
-                | BackoffError::Transient {
-                    err: reqwest_error,
-                    retry_after: _,
-                } => RoverClientError::SendRequest {
-                    source: reqwest_error,
-                    endpoint_kind,
-                },
-            })
+            retry(backoff_strategy, graphql_operation)
+                .map_err(|e| self.map_send_error(e, endpoint_kind))
         } else {
-            graphql_operation().map_err(|e| match e {
-                BackoffError::Permanent(reqwest_error)
-                | BackoffError::Transient {
-                    err: reqwest_error,
-                    retry_after: _,
-                } => RoverClientError::SendRequest {
-                    source: reqwest_error,
-                    endpoint_kind,
-                },
-            })
+            graphql_operation().map_err(|e| self.map_send_error(e, endpoint_kind))
         }
     }
 
+    /// Like [`GraphQLClient::send`] without retries, for the async client.
+    async fn send_async(
+        &self,
+        client: &AsyncReqwestClient,
+        request_body: Vec<u8>,
+        header_map: &HeaderMap,
+        endpoint_kind: EndpointKind,
+    ) -> Result<AsyncResponse, RoverClientError> {
+        let mut request = client
+            .post(&self.graphql_endpoint)
+            .headers(header_map.clone())
+            .body(request_body);
+        if let Some(deadline) = &self.deadline {
+            deadline.start();
+            let remaining = deadline
+                .remaining()
+                .ok_or(RoverClientError::OperationTimedOut {
+                    timeout: deadline.timeout(),
+                })?;
+            request = request.timeout(remaining);
+        }
+        let error = match request.send().await {
+            Err(client_error) => classify_send_error(client_error),
+            Ok(success) => match success.error_for_status_ref() {
+                Err(status_error) => {
+                    let error = classify_status_error(status_error, success.headers());
+                    if let BackoffError::Permanent(_) = error {
+                        if let Ok(text) = success.text().await {
+                            tracing::debug!("{}", text);
+                        }
+                    }
+                    error
+                }
+                Ok(_) => return Ok(success),
+            },
+        };
+        Err(self.map_send_error(error, endpoint_kind))
+    }
+
+    fn map_send_error(
+        &self,
+        error: backoff::Error<reqwest::Error>,
+        endpoint_kind: EndpointKind,
+    ) -> RoverClientError {
+        match (&self.deadline, error) {
+            (
+                Some(deadline),
+                backoff::Error::Permanent(err) | backoff::Error::Transient { err, .. },
+            ) if err.is_timeout() && deadline.remaining().is_none() => {
+                RoverClientError::OperationTimedOut {
+                    timeout: deadline.timeout(),
+                }
+            }
+            (_, error) => to_rover_client_error(error, endpoint_kind),
+        }
+    }
+
+    /// Handles `response` like [`GraphQLClient::handle_response`], logging its
+    /// status, headers and body first if HTTP tracing is on.
+    fn receive<Q: GraphQLQuery>(
+        &self,
+        response: Response,
+        endpoint_kind: EndpointKind,
+    ) -> Result<Q::ResponseData, RoverClientError> {
+        if !self.trace_http {
+            return GraphQLClient::handle_response::<Q>(response, endpoint_kind);
+        }
+        let response_status = response.status();
+        let response_headers = redact(&format!("{:?}", response.headers()));
+        let response_body = response
+            .text()
+            .map_err(|source| RoverClientError::SendRequest {
+                source,
+                endpoint_kind,
+            })
+            .and_then(|text| {
+                self.trace_response(response_status, &response_headers, &text);
+                Ok(serde_json::from_str(&text)?)
+            });
+        GraphQLClient::handle_response_body::<Q>(response_status, response_body, endpoint_kind)
+    }
+
+    /// Like [`GraphQLClient::receive`], for a response to an async request.
+    async fn receive_async<Q: GraphQLQuery>(
+        &self,
+        response: AsyncResponse,
+        endpoint_kind: EndpointKind,
+    ) -> Result<Q::ResponseData, RoverClientError> {
+        let response_status = response.status();
+        tracing::debug!(response_status = ?response_status, response_headers = ?response.headers());
+        let response_headers = redact(&format!("{:?}", response.headers()));
+        let response_body = response
+            .text()
+            .await
+            .map_err(|source| RoverClientError::SendRequest {
+                source,
+                endpoint_kind,
+            })
+            .and_then(|text| {
+                if self.trace_http {
+                    self.trace_response(response_status, &response_headers, &text);
+                }
+                Ok(serde_json::from_str(&text)?)
+            });
+        GraphQLClient::handle_response_body::<Q>(response_status, response_body, endpoint_kind)
+    }
+
+    fn trace_response(&self, response_status: StatusCode, response_headers: &str, text: &str) {
+        tracing::info!(
+            "response from {}: {} {} {}",
+            self.graphql_endpoint,
+            response_status,
+            response_headers,
+            redact(text)
+        );
+    }
+
     /// To be used internally or by other implementations of a GraphQL client.
     ///
     /// This fn tries to parse the JSON response from a GraphQL server. It will
@@ -193,10 +456,25 @@ impl GraphQLClient {
     ) -> Result<Q::ResponseData, RoverClientError> {
         let response_status = response.status();
         tracing::debug!(response_status = ?response_status, response_headers = ?response.headers());
-        match response.json::<GraphQLResponse<Q::ResponseData>>() {
+        let response_body = response
+            .json::<GraphQLResponse<Q::ResponseData>>()
+            .map_err(|source| RoverClientError::SendRequest {
+                source,
+                endpoint_kind,
+            });
+        GraphQLClient::handle_response_body::<Q>(response_status, response_body, endpoint_kind)
+    }
+
+    /// The part of [`GraphQLClient::handle_response`] after the body is parsed.
+    fn handle_response_body<Q: GraphQLQuery>(
+        response_status: StatusCode,
+        response_body: Result<GraphQLResponse<Q::ResponseData>, RoverClientError>,
+        endpoint_kind: EndpointKind,
+    ) -> Result<Q::ResponseData, RoverClientError> {
+        match response_body {
             Ok(response_body) => {
                 if let Some(response_body_errors) = response_body.errors {
-                    handle_graphql_body_errors(response_body_errors)?;
+                    handle_graphql_body_errors(response_body_errors, endpoint_kind)?;
                 }
                 match response_status {
                     StatusCode::OK => {
@@ -213,10 +491,7 @@ impl GraphQLClient {
             }
             Err(e) => {
                 if response_status.is_success() {
-                    Err(RoverClientError::SendRequest {
-                        source: e,
-                        endpoint_kind,
-                    })
+                    Err(e)
                 } else {
                     Err(RoverClientError::ClientError {
                         msg: response_status.to_string(),
@@ -227,13 +502,116 @@ impl GraphQLClient {
     }
 }
 
-fn handle_graphql_body_errors(errors: Vec<GraphQLError>) -> Result<(), RoverClientError> {
+/// whether a request that couldn't be sent at all is worth retrying
+fn classify_send_error(client_error: reqwest::Error) -> BackoffError<reqwest::Error> {
+    if client_error.is_timeout() || client_error.is_connect() {
+        BackoffError::transient(client_error)
+    } else if client_error.is_body()
+        || client_error.is_decode()
+        || client_error.is_builder()
+        || client_error.is_redirect()
+    {
+        BackoffError::Permanent(client_error)
+    } else if client_error.is_request() {
+        match get_source_error_type::<hyper::Error>(&client_error) {
+            Some(hyper_error) if hyper_error.is_incomplete_message() => {
+                BackoffError::transient(client_error)
+            }
+            _ => BackoffError::Permanent(client_error),
+        }
+    } else {
+        BackoffError::Permanent(client_error)
+    }
+}
+
+/// whether a request that was answered with an error status is worth retrying,
+/// after as long as the response's `Retry-After` header asks if it was rate limited
+fn classify_status_error(
+    status_error: reqwest::Error,
+    headers: &HeaderMap,
+) -> BackoffError<reqwest::Error> {
+    match status_error.status() {
+        Some(StatusCode::TOO_MANY_REQUESTS) => match retry_after(headers) {
+            Some(delay) => BackoffError::retry_after(status_error, delay),
+            None => BackoffError::transient(status_error),
+        },
+        Some(
+            StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED | StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ) => BackoffError::Permanent(status_error),
+        Some(status)
+            if status.is_server_error() || status.is_client_error() || status.is_redirection() =>
+        {
+            BackoffError::transient(status_error)
+        }
+        _ => BackoffError::Permanent(status_error),
+    }
+}
+
+fn to_rover_client_error(
+    error: backoff::Error<reqwest::Error>,
+    endpoint_kind: EndpointKind,
+) -> RoverClientError {
+    match error {
+        backoff::Error::Transient { err, retry_after }
+            if err.status() == Some(StatusCode::TOO_MANY_REQUESTS) =>
+        {
+            RoverClientError::RateLimited {
+                retry_after,
+                endpoint_kind,
+            }
+        }
+        backoff::Error::Permanent(err)
+            if endpoint_kind == EndpointKind::ApolloStudio
+                && err.status() == Some(StatusCode::UNAUTHORIZED) =>
+        {
+            RoverClientError::AuthenticationFailed {
+                msg: StatusCode::UNAUTHORIZED.to_string(),
+            }
+        }
+        backoff::Error::Permanent(err) | backoff::Error::Transient { err, .. } => {
+            RoverClientError::SendRequest {
+                source: err,
+                endpoint_kind,
+            }
+        }
+    }
+}
+
+/// how long a `Retry-After` header asks to wait, up to [`MAX_RETRY_AFTER`].
+/// only the number of seconds form is supported, not HTTP dates.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
+}
+
+fn handle_graphql_body_errors(
+    errors: Vec<GraphQLError>,
+    endpoint_kind: EndpointKind,
+) -> Result<(), RoverClientError> {
     if errors.is_empty() {
         Ok(())
     } else {
         tracing::debug!("GraphQL response errors: {:?}", errors);
+        let unauthenticated = errors.iter().find(|error| {
+            error
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.get("code"))
+                .and_then(|code| code.as_str())
+                == Some(UNAUTHENTICATED_CODE)
+        });
         if errors[0].message == "406: Not Acceptable" {
             Err(RoverClientError::MalformedKey)
+        } else if let (Some(error), EndpointKind::ApolloStudio) = (unauthenticated, endpoint_kind) {
+            Err(RoverClientError::AuthenticationFailed {
+                msg: error.message.clone(),
+            })
         } else {
             Err(RoverClientError::GraphQl {
                 msg: errors
@@ -270,7 +648,7 @@ mod tests {
     #[test]
     fn it_is_ok_on_empty_errors() {
         let errors = vec![];
-        assert!(handle_graphql_body_errors(errors).is_ok());
+        assert!(handle_graphql_body_errors(errors, EndpointKind::ApolloStudio).is_ok());
     }
 
     #[test]
@@ -282,7 +660,9 @@ mod tests {
             path: None,
         }];
         let expected_error = RoverClientError::MalformedKey.to_string();
-        let actual_error = handle_graphql_body_errors(errors).unwrap_err().to_string();
+        let actual_error = handle_graphql_body_errors(errors, EndpointKind::ApolloStudio)
+            .unwrap_err()
+            .to_string();
         assert_eq!(actual_error, expected_error);
     }
 
@@ -306,10 +686,132 @@ mod tests {
             msg: format!("{}\n{}", errors[0].message, errors[1].message),
         }
         .to_string();
-        let actual_error = handle_graphql_body_errors(errors).unwrap_err().to_string();
+        let actual_error = handle_graphql_body_errors(errors, EndpointKind::ApolloStudio)
+            .unwrap_err()
+            .to_string();
         assert_eq!(actual_error, expected_error);
     }
 
+    #[test]
+    fn it_returns_authentication_failed_for_unauthenticated_errors() {
+        let unauthenticated = || {
+            vec![GraphQLError {
+                message: "This API key has expired".to_string(),
+                locations: None,
+                extensions: Some(
+                    [("code".to_string(), serde_json::json!("UNAUTHENTICATED"))]
+                        .into_iter()
+                        .collect(),
+                ),
+                path: None,
+            }]
+        };
+        assert!(matches!(
+            handle_graphql_body_errors(unauthenticated(), EndpointKind::ApolloStudio),
+            Err(RoverClientError::AuthenticationFailed { msg }) if msg == "This API key has expired"
+        ));
+        // a subgraph rejecting its own credentials has nothing to do with the API key
+        assert!(matches!(
+            handle_graphql_body_errors(unauthenticated(), EndpointKind::Customer),
+            Err(RoverClientError::GraphQl { .. })
+        ));
+    }
+
+    const LARGE_BODY: &str = r#"{"query":"mutation SubgraphPublishMutation { publish }","variables":{"schema":"type Query { hello: String } type Query { hello: String } type Query { hello: String }"}}"#;
+
+    fn content_encoding(req: &httpmock::prelude::HttpMockRequest) -> Option<&str> {
+        req.headers.as_ref()?.iter().find_map(|(name, value)| {
+            name.eq_ignore_ascii_case("content-encoding")
+                .then_some(value.as_str())
+        })
+    }
+
+    fn is_gzipped_large_body(req: &httpmock::prelude::HttpMockRequest) -> bool {
+        use std::io::Read;
+
+        let Some(body) = &req.body else {
+            return false;
+        };
+        let mut decompressed = String::new();
+        flate2::read::GzDecoder::new(body.as_slice())
+            .read_to_string(&mut decompressed)
+            .is_ok()
+            && content_encoding(req) == Some("gzip")
+            && decompressed == LARGE_BODY
+    }
+
+    fn is_uncompressed_large_body(req: &httpmock::prelude::HttpMockRequest) -> bool {
+        content_encoding(req).is_none() && req.body.as_deref() == Some(LARGE_BODY.as_bytes())
+    }
+
+    #[test]
+    fn it_compresses_request_bodies_over_the_threshold() {
+        let server = MockServer::start();
+        let compressed = server.mock(|when, then| {
+            when.method(POST).matches(is_gzipped_large_body);
+            then.status(200).body("{}");
+        });
+
+        let graphql_client = GraphQLClient::new(&server.url("/graphql"), ReqwestClient::new())
+            .with_compression_threshold(Some(LARGE_BODY.len() - 1));
+        let response = graphql_client.execute(
+            LARGE_BODY.to_string(),
+            &HeaderMap::new(),
+            false,
+            EndpointKind::ApolloStudio,
+        );
+
+        assert!(response.is_ok());
+        compressed.assert();
+    }
+
+    #[test]
+    fn it_does_not_compress_request_bodies_under_the_threshold() {
+        let server = MockServer::start();
+        let uncompressed = server.mock(|when, then| {
+            when.method(POST).matches(is_uncompressed_large_body);
+            then.status(200).body("{}");
+        });
+
+        let graphql_client = GraphQLClient::new(&server.url("/graphql"), ReqwestClient::new())
+            .with_compression_threshold(Some(LARGE_BODY.len()));
+        let response = graphql_client.execute(
+            LARGE_BODY.to_string(),
+            &HeaderMap::new(),
+            false,
+            EndpointKind::ApolloStudio,
+        );
+
+        assert!(response.is_ok());
+        uncompressed.assert();
+    }
+
+    #[test]
+    fn it_falls_back_to_uncompressed_requests() {
+        let server = MockServer::start();
+        let compressed = server.mock(|when, then| {
+            when.method(POST).matches(is_gzipped_large_body);
+            then.status(415);
+        });
+        let uncompressed = server.mock(|when, then| {
+            when.method(POST).matches(is_uncompressed_large_body);
+            then.status(200).body("{}");
+        });
+
+        let graphql_client = GraphQLClient::new(&server.url("/graphql"), ReqwestClient::new())
+            .with_compression_threshold(Some(0));
+        let response = graphql_client.execute(
+            LARGE_BODY.to_string(),
+            &HeaderMap::new(),
+            true,
+            EndpointKind::ApolloStudio,
+        );
+
+        assert!(response.is_ok());
+        compressed.assert();
+        uncompressed.assert();
+    }
+
     #[test]
     fn test_successful_response() {
         let server = MockServer::start();
@@ -360,6 +862,35 @@ mod tests {
         assert!(response.is_err());
     }
 
+    #[test]
+    fn test_async_requests_are_not_retried() {
+        let server = MockServer::start();
+        let unavailable_path = "/be-right-back";
+        let unavailable_mock = server.mock(|when, then| {
+            when.method(POST).path(unavailable_path);
+            then.status(503);
+        });
+
+        let async_client = reqwest::Client::new();
+        let graphql_client =
+            GraphQLClient::new(&server.url(unavailable_path), ReqwestClient::new());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let response = runtime.block_on(graphql_client.send_async(
+            &async_client,
+            b"{}".to_vec(),
+            &HeaderMap::new(),
+            EndpointKind::ApolloStudio,
+        ));
+
+        unavailable_mock.assert_hits(1);
+        let error = response.expect_err("Response didn't error");
+        assert!(error.to_string().contains("Service Unavailable"));
+    }
+
     #[test]
     fn test_unrecoverable_client_error() {
         let server = MockServer::start();
@@ -387,6 +918,43 @@ mod tests {
         assert!(error.to_string().contains("Not Found"));
     }
 
+    #[test]
+    fn test_unauthorized_error_is_not_retried() {
+        let server = MockServer::start();
+        let unauthorized_path = "/who-are-you";
+        let unauthorized_mock = server.mock(|when, then| {
+            when.method(POST).path(unauthorized_path);
+            then.status(401).body("expired key");
+        });
+
+        let client = ReqwestClient::new();
+        let graphql_client = GraphQLClient::new(&server.url(unauthorized_path), client);
+
+        let response = graphql_client.execute(
+            "{}".to_string(),
+            &HeaderMap::new(),
+            true,
+            EndpointKind::ApolloStudio,
+        );
+
+        assert_eq!(unauthorized_mock.hits(), 1);
+        assert!(matches!(
+            response,
+            Err(RoverClientError::AuthenticationFailed { msg }) if msg == "401 Unauthorized"
+        ));
+
+        let response = graphql_client.execute(
+            "{}".to_string(),
+            &HeaderMap::new(),
+            true,
+            EndpointKind::Customer,
+        );
+        assert!(matches!(
+            response,
+            Err(RoverClientError::SendRequest { .. })
+        ));
+    }
+
     #[test]
     fn test_timeout_error() {
         let server = MockServer::start();
@@ -419,4 +987,51 @@ mod tests {
         let error = response.expect_err("Response didn't error");
         assert!(error.to_string().contains("operation timed out"));
     }
+
+    #[test]
+    fn test_rate_limited_error() {
+        let server = MockServer::start();
+        let rate_limited_path = "/slow-down";
+        let rate_limited_mock = server.mock(|when, then| {
+            when.method(POST).path(rate_limited_path);
+            then.status(429).header("Retry-After", "7");
+        });
+
+        let client = ReqwestClient::new();
+        let graphql_client = GraphQLClient::new(&server.url(rate_limited_path), client);
+
+        let response = graphql_client.execute(
+            "{}".to_string(),
+            &HeaderMap::new(),
+            false,
+            EndpointKind::ApolloStudio,
+        );
+
+        assert_eq!(rate_limited_mock.hits(), 1);
+        assert!(matches!(
+            response,
+            Err(RoverClientError::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            }) if retry_after == Duration::from_secs(7)
+        ));
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("86400"));
+        assert_eq!(retry_after(&headers), Some(MAX_RETRY_AFTER));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), None);
+    }
 }
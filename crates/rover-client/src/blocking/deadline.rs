@@ -0,0 +1,68 @@
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+/// A point in time that a whole operation, however many requests it takes,
+/// has to finish by. Its clones share one clock, which starts when the first
+/// of them is started.
+#[derive(Debug, Clone)]
+pub struct Deadline {
+    timeout: Duration,
+    expires_at: Arc<OnceLock<Instant>>,
+}
+
+impl Deadline {
+    /// Construct a [Deadline] that expires `timeout` after it's started.
+    pub fn new(timeout: Duration) -> Deadline {
+        Deadline {
+            timeout,
+            expires_at: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Starts the clock, e.g. as the first request is sent, unless it's already running.
+    pub fn start(&self) {
+        self.expires_at
+            .get_or_init(|| Instant::now() + self.timeout);
+    }
+
+    /// How long the operation was given in total.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// How much time is left, or `None` once the deadline has passed.
+    /// All of it is left until the deadline is started.
+    pub fn remaining(&self) -> Option<Duration> {
+        let remaining = match self.expires_at.get() {
+            Some(expires_at) => expires_at.checked_duration_since(Instant::now()),
+            None => Some(self.timeout),
+        };
+        remaining.filter(|remaining| !remaining.is_zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_does_not_count_down_until_it_is_started() {
+        let deadline = Deadline::new(Duration::from_millis(50));
+        std::thread::sleep(Duration::from_millis(100));
+
+        assert_eq!(deadline.remaining(), Some(Duration::from_millis(50)));
+        deadline.start();
+        assert!(deadline.remaining().is_some());
+    }
+
+    #[test]
+    fn it_shares_its_clock_with_its_clones() {
+        let deadline = Deadline::new(Duration::from_millis(50));
+        let clone = deadline.clone();
+
+        clone.start();
+        std::thread::sleep(Duration::from_millis(100));
+
+        assert_eq!(deadline.remaining(), None);
+    }
+}
@@ -1,7 +1,9 @@
 mod client;
+mod deadline;
 mod studio_client;
 
 pub use client::GraphQLClient;
+pub use deadline::Deadline;
 pub use studio_client::StudioClient;
 
 pub(crate) const CLIENT_NAME: &str = "rover-client";
@@ -1,5 +1,5 @@
 use crate::{
-    blocking::{GraphQLClient, CLIENT_NAME},
+    blocking::{Deadline, GraphQLClient, CLIENT_NAME},
     error::EndpointKind,
     RoverClientError,
 };
@@ -9,6 +9,7 @@ use houston::{Credential, CredentialOrigin};
 use graphql_client::GraphQLQuery;
 use reqwest::blocking::Client as ReqwestClient;
 use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Client as AsyncReqwestClient;
 
 /// Represents a client for making GraphQL requests to Apollo Studio.
 pub struct StudioClient {
@@ -16,6 +17,7 @@ pub struct StudioClient {
     client: GraphQLClient,
     version: String,
     is_sudo: bool,
+    headers: HeaderMap,
 }
 
 impl StudioClient {
@@ -33,6 +35,45 @@ impl StudioClient {
             client: GraphQLClient::new(graphql_endpoint, client),
             version: version.to_string(),
             is_sudo,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Sends `headers` along with every request. They can't replace the
+    /// headers Apollo Studio uses for authentication and client identification.
+    pub fn with_headers(self, headers: HeaderMap) -> StudioClient {
+        StudioClient { headers, ..self }
+    }
+
+    /// Sends the requests made with [`StudioClient::post_no_retry_async`] with `async_client`.
+    pub fn with_async_client(self, async_client: AsyncReqwestClient) -> StudioClient {
+        StudioClient {
+            client: self.client.with_async_client(async_client),
+            ..self
+        }
+    }
+
+    /// Makes every request this client sends give up once `deadline` passes.
+    pub fn with_deadline(self, deadline: Option<Deadline>) -> StudioClient {
+        StudioClient {
+            client: self.client.with_deadline(deadline),
+            ..self
+        }
+    }
+
+    /// Gzip compresses every request body larger than `threshold` bytes.
+    pub fn with_compression_threshold(self, threshold: Option<usize>) -> StudioClient {
+        StudioClient {
+            client: self.client.with_compression_threshold(threshold),
+            ..self
+        }
+    }
+
+    /// Logs every request and response, with API keys redacted, at info level.
+    pub fn with_trace_http(self, trace_http: bool) -> StudioClient {
+        StudioClient {
+            client: self.client.with_trace_http(trace_http),
+            ..self
         }
     }
 
@@ -62,13 +103,28 @@ impl StudioClient {
             .post_no_retry::<Q>(variables, &mut header_map, EndpointKind::ApolloStudio)
     }
 
+    /// Client method for making a GraphQL request to Apollo Studio without
+    /// blocking, so that several requests can be in flight at once.
+    ///
+    /// Takes one argument, `variables`. Returns a Response or a RoverClientError.
+    /// Does not automatically retry requests.
+    pub async fn post_no_retry_async<Q: GraphQLQuery>(
+        &self,
+        variables: Q::Variables,
+    ) -> Result<Q::ResponseData, RoverClientError> {
+        let mut header_map = self.build_studio_headers()?;
+        self.client
+            .post_no_retry_async::<Q>(variables, &mut header_map, EndpointKind::ApolloStudio)
+            .await
+    }
+
     /// Function for building a [HeaderMap] for making http requests. Use for making
     /// requests to Apollo Studio. We're leaving this separate from `build` since we
     /// need to be able to mark the api_key as sensitive (at the bottom)
     ///
     /// Takes an `api_key` and a `client_version`, and returns a [HeaderMap].
     pub fn build_studio_headers(&self) -> Result<HeaderMap, RoverClientError> {
-        let mut headers = HeaderMap::new();
+        let mut headers = self.headers.clone();
 
         // The headers "apollographql-client-name" and "apollographql-client-version"
         // are used for client identification in Apollo Studio.
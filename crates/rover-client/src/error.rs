@@ -81,6 +81,18 @@ pub enum RoverClientError {
         endpoint_kind: EndpointKind,
     },
 
+    /// The operation took longer than its [`Deadline`](crate::blocking::Deadline) allowed.
+    #[error("The operation did not finish within its {:?} timeout.", timeout)]
+    OperationTimedOut { timeout: std::time::Duration },
+
+    /// The server responded with a 429, possibly saying how long to wait
+    /// before trying again.
+    #[error("Too many requests were sent, so the server is rate limiting them.")]
+    RateLimited {
+        retry_after: Option<std::time::Duration>,
+        endpoint_kind: EndpointKind,
+    },
+
     /// when someone provides a bad graph/variant combination or isn't
     /// validated properly, we don't know which reason is at fault for data.service
     /// being empty, so this error tells them to check both.
@@ -178,6 +190,11 @@ pub enum RoverClientError {
     #[error("The registry did not recognize the provided API key")]
     InvalidKey,
 
+    /// Apollo Studio refused the API key a request was sent with, usually
+    /// because it expired or was deleted
+    #[error("Apollo Studio rejected your API key, which may have expired or been revoked: {msg}")]
+    AuthenticationFailed { msg: String },
+
     /// Could not parse the latest version
     #[error("Could not parse the latest release version")]
     UnparseableReleaseVersion { source: semver::Error },
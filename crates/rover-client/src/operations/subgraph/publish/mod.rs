@@ -1,5 +1,5 @@
 mod runner;
 mod types;
 
-pub use runner::run;
+pub use runner::{check_convert, run, run_async};
 pub use types::{SubgraphPublishInput, SubgraphPublishResponse};
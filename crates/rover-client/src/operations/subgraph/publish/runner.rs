@@ -30,8 +30,37 @@ pub fn run(
     input: SubgraphPublishInput,
     client: &StudioClient,
 ) -> Result<SubgraphPublishResponse, RoverClientError> {
+    check_convert(&input, client)?;
+    let graph_ref = input.graph_ref.clone();
+    let variables: MutationVariables = input.into();
+    // the caller decides whether to retry, since it can tell how many attempts
+    // it has left and whether it's being rate limited
+    let data = client.post_no_retry::<SubgraphPublishMutation>(variables)?;
+    let publish_response = get_publish_response_from_data(data, graph_ref)?;
+    Ok(build_response(publish_response))
+}
+
+/// Publishes the subgraph like [`run`], without blocking and without checking
+/// whether the graph is federated first. Call [`check_convert`] before it.
+pub async fn run_async(
+    input: SubgraphPublishInput,
+    client: &StudioClient,
+) -> Result<SubgraphPublishResponse, RoverClientError> {
+    let graph_ref = input.graph_ref.clone();
+    let variables: MutationVariables = input.into();
+    let data = client
+        .post_no_retry_async::<SubgraphPublishMutation>(variables)
+        .await?;
+    let publish_response = get_publish_response_from_data(data, graph_ref)?;
+    Ok(build_response(publish_response))
+}
+
+/// Errors if publishing `input` would implicitly convert a non-federated graph.
+pub fn check_convert(
+    input: &SubgraphPublishInput,
+    client: &StudioClient,
+) -> Result<(), RoverClientError> {
     let graph_ref = input.graph_ref.clone();
-    let variables: MutationVariables = input.clone().into();
     // We don't want to implicitly convert non-federated graph to supergraphs.
     // Error here if no --convert flag is passed _and_ the current context
     // is non-federated. Add a suggestion to require a --convert flag.
@@ -70,9 +99,7 @@ pub fn run(
             );
         }
     }
-    let data = client.post::<SubgraphPublishMutation>(variables)?;
-    let publish_response = get_publish_response_from_data(data, graph_ref)?;
-    Ok(build_response(publish_response))
+    Ok(())
 }
 
 fn get_publish_response_from_data(
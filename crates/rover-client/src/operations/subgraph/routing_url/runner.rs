@@ -24,7 +24,9 @@ pub fn run(
     client: &StudioClient,
 ) -> Result<String, RoverClientError> {
     let variables = input.clone().into();
-    let response_data = client.post::<SubgraphRoutingUrlQuery>(variables)?;
+    // the caller decides whether to retry, since it can tell how many attempts
+    // it has left and whether it's being rate limited
+    let response_data = client.post_no_retry::<SubgraphRoutingUrlQuery>(variables)?;
     get_routing_url_from_response_data(input, response_data)
 }
 
@@ -4,49 +4,137 @@ use git2::{Reference, Repository};
 use git_url_parse::GitUrl;
 use serde::Serialize;
 
+/// environment variables that CI providers set to the commit being built,
+/// in the order they're checked
+const CI_COMMIT_ENV_VARS: &[&str] = &[
+    "GITHUB_SHA",
+    "CI_COMMIT_SHA",
+    "CIRCLE_SHA1",
+    "BITBUCKET_COMMIT",
+    "BUILDKITE_COMMIT",
+    "GIT_COMMIT",
+];
+
+/// environment variables that CI providers set to the branch being built,
+/// in the order they're checked. `GITHUB_REF` is handled separately, since
+/// it's a full ref that isn't always a branch.
+const CI_BRANCH_ENV_VARS: &[&str] = &[
+    "CI_COMMIT_REF_NAME",
+    "CIRCLE_BRANCH",
+    "BITBUCKET_BRANCH",
+    "BUILDKITE_BRANCH",
+    "GIT_BRANCH",
+];
+
 #[derive(Debug, Serialize, Clone, Eq, PartialEq)]
 pub struct GitContext {
     pub branch: Option<String>,
     pub author: Option<String>,
     pub commit: Option<String>,
     pub remote_url: Option<String>,
+
+    /// where `branch` came from, if it's set
+    pub branch_source: Option<GitContextSource>,
+
+    /// where `commit` came from, if it's set
+    pub commit_source: Option<GitContextSource>,
+}
+
+/// Where a value in a [GitContext] came from.
+#[derive(Debug, Serialize, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitContextSource {
+    /// The value was passed in as an override.
+    Override,
+
+    /// The value was read from the git repository.
+    Git,
+
+    /// Git couldn't tell, because HEAD is detached or there is no repository,
+    /// so the value was read from the environment variables CI providers set.
+    Ci,
 }
 
 impl GitContext {
-    pub fn new_with_override(override_git_context: GitContext) -> Self {
-        let repo = GitContext::get_repo();
-
-        let mut remote_url = override_git_context.remote_url;
-
-        if let Some(repo) = repo {
-            remote_url = remote_url.or_else(|| GitContext::get_remote_url(&repo));
-            if let Ok(head) = repo.head() {
-                let branch = override_git_context
-                    .branch
-                    .or_else(|| GitContext::get_branch(&head));
-
-                let author = override_git_context
-                    .author
-                    .or_else(|| GitContext::get_author(&head));
-
-                let commit = override_git_context
-                    .commit
-                    .or_else(|| GitContext::get_commit(&head));
-
-                return GitContext {
-                    branch,
-                    author,
-                    commit,
-                    remote_url,
-                };
-            }
+    /// a git context with nothing in it, for when none should be sent
+    pub fn empty() -> Self {
+        Self {
+            author: None,
+            branch: None,
+            commit: None,
+            remote_url: None,
+            branch_source: None,
+            commit_source: None,
         }
+    }
+
+    pub fn new_with_override(override_git_context: GitContext) -> Self {
+        GitContext::from_repo_and_env(
+            override_git_context,
+            GitContext::get_repo().as_ref(),
+            |name| env::var(name).ok(),
+        )
+    }
+
+    /// fills in whatever `override_git_context` leaves out from `repo`,
+    /// falling back to the CI environment variables `env_var` returns
+    fn from_repo_and_env<F>(
+        override_git_context: GitContext,
+        repo: Option<&Repository>,
+        env_var: F,
+    ) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let env_var = |name: &str| env_var(name).filter(|value| !value.trim().is_empty());
+        let head = repo.and_then(|repo| repo.head().ok());
+
+        let remote_url = override_git_context
+            .remote_url
+            .or_else(|| repo.and_then(GitContext::get_remote_url));
+
+        let author = override_git_context
+            .author
+            .or_else(|| head.as_ref().and_then(GitContext::get_author));
+
+        let (branch, branch_source) = GitContext::resolve(
+            override_git_context.branch,
+            || head.as_ref().and_then(GitContext::get_branch),
+            || GitContext::get_ci_branch(env_var),
+        );
+
+        let (commit, commit_source) = GitContext::resolve(
+            override_git_context.commit,
+            || head.as_ref().and_then(GitContext::get_commit),
+            || CI_COMMIT_ENV_VARS.iter().find_map(|name| env_var(name)),
+        );
 
         GitContext {
-            branch: override_git_context.branch,
-            author: override_git_context.author,
-            commit: override_git_context.commit,
+            branch,
+            author,
+            commit,
             remote_url,
+            branch_source,
+            commit_source,
+        }
+    }
+
+    /// the overridden value if there is one, then the one from git, then the
+    /// one from CI, along with where it came from
+    fn resolve(
+        overridden: Option<String>,
+        from_git: impl FnOnce() -> Option<String>,
+        from_ci: impl FnOnce() -> Option<String>,
+    ) -> (Option<String>, Option<GitContextSource>) {
+        if let Some(value) = overridden {
+            return (Some(value), Some(GitContextSource::Override));
+        }
+        if let Some(value) = from_git() {
+            return (Some(value), Some(GitContextSource::Git));
+        }
+        match from_ci() {
+            Some(value) => (Some(value), Some(GitContextSource::Ci)),
+            None => (None, None),
         }
     }
 
@@ -57,10 +145,26 @@ impl GitContext {
             .flatten()
     }
 
+    /// the checked out branch, or `None` when HEAD is detached, since its
+    /// shorthand is then just "HEAD"
     fn get_branch(head: &Reference) -> Option<String> {
+        if !head.is_branch() {
+            return None;
+        }
         head.shorthand().map(|s| s.to_string())
     }
 
+    fn get_ci_branch(env_var: impl Fn(&str) -> Option<String>) -> Option<String> {
+        // GitHub only sets GITHUB_HEAD_REF for pull requests, where GITHUB_REF
+        // is the pull request's merge ref rather than a branch
+        env_var("GITHUB_HEAD_REF")
+            .or_else(|| {
+                env_var("GITHUB_REF")
+                    .and_then(|git_ref| git_ref.strip_prefix("refs/heads/").map(str::to_string))
+            })
+            .or_else(|| CI_BRANCH_ENV_VARS.iter().find_map(|name| env_var(name)))
+    }
+
     fn get_commit(head: &Reference) -> Option<String> {
         if let Ok(head_commit) = head.peel_to_commit() {
             Some(head_commit.id().to_string())
@@ -123,12 +227,7 @@ impl GitContext {
 
 impl Default for GitContext {
     fn default() -> Self {
-        Self::new_with_override(Self {
-            author: None,
-            branch: None,
-            commit: None,
-            remote_url: None,
-        })
+        Self::new_with_override(Self::empty())
     }
 }
 
@@ -276,11 +375,20 @@ mod tests {
             author: Some(author),
             commit: Some(commit),
             remote_url: Some(remote_url),
+            branch_source: None,
+            commit_source: None,
         };
 
         let actual_git_context = GitContext::new_with_override(override_git_context.clone());
 
-        assert_eq!(override_git_context, actual_git_context);
+        assert_eq!(
+            GitContext {
+                branch_source: Some(GitContextSource::Override),
+                commit_source: Some(GitContextSource::Override),
+                ..override_git_context
+            },
+            actual_git_context
+        );
     }
 
     #[test]
@@ -309,4 +417,138 @@ mod tests {
         let clean = GitContext::sanitize_remote_url("ssh://user@github.com/repo-name");
         assert_eq!(clean, Some("ssh://github.com:repo-name".to_string()));
     }
+
+    fn no_overrides() -> GitContext {
+        GitContext {
+            branch: None,
+            author: None,
+            commit: None,
+            remote_url: None,
+            branch_source: None,
+            commit_source: None,
+        }
+    }
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: Vec<(String, String)> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| {
+            vars.iter()
+                .find(|(var, _)| var == name)
+                .map(|(_, value)| value.clone())
+        }
+    }
+
+    /// a repository with a single commit, returning the commit's id
+    fn init_repo(dir: &assert_fs::TempDir) -> (Repository, String) {
+        let repo = Repository::init(dir.path()).unwrap();
+        let signature = git2::Signature::now("Test Author", "author@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let commit_id = {
+            let tree = repo.find_tree(tree_id).unwrap();
+            repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])
+                .unwrap()
+        };
+        (repo, commit_id.to_string())
+    }
+
+    #[test]
+    fn it_reads_the_branch_and_commit_from_git() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let (repo, commit) = init_repo(&dir);
+        let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+
+        let git_context = GitContext::from_repo_and_env(
+            no_overrides(),
+            Some(&repo),
+            env(&[
+                ("GITHUB_SHA", "ci-commit"),
+                ("GITHUB_REF", "refs/heads/ci-branch"),
+            ]),
+        );
+
+        assert_eq!(git_context.branch, Some(branch));
+        assert_eq!(git_context.branch_source, Some(GitContextSource::Git));
+        assert_eq!(git_context.commit, Some(commit));
+        assert_eq!(git_context.commit_source, Some(GitContextSource::Git));
+        assert!(git_context.author.unwrap().contains("Test Author"));
+    }
+
+    #[test]
+    fn it_reads_the_branch_from_ci_when_head_is_detached() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let (repo, commit) = init_repo(&dir);
+        repo.set_head_detached(git2::Oid::from_str(&commit).unwrap())
+            .unwrap();
+
+        let git_context = GitContext::from_repo_and_env(
+            no_overrides(),
+            Some(&repo),
+            env(&[("GITHUB_SHA", &commit), ("GITHUB_REF", "refs/heads/main")]),
+        );
+
+        assert_eq!(git_context.branch.as_deref(), Some("main"));
+        assert_eq!(git_context.branch_source, Some(GitContextSource::Ci));
+        assert_eq!(git_context.commit, Some(commit));
+        assert_eq!(git_context.commit_source, Some(GitContextSource::Git));
+    }
+
+    #[test]
+    fn it_reads_the_commit_from_ci_when_head_has_no_commits() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let git_context = GitContext::from_repo_and_env(
+            no_overrides(),
+            Some(&repo),
+            env(&[
+                ("CI_COMMIT_SHA", "f84b32caddddfdd9fa87d7ce2140d56eabe805ee"),
+                ("CI_COMMIT_REF_NAME", "feature"),
+            ]),
+        );
+
+        assert_eq!(
+            git_context.commit.as_deref(),
+            Some("f84b32caddddfdd9fa87d7ce2140d56eabe805ee")
+        );
+        assert_eq!(git_context.commit_source, Some(GitContextSource::Ci));
+        assert_eq!(git_context.branch.as_deref(), Some("feature"));
+        assert_eq!(git_context.branch_source, Some(GitContextSource::Ci));
+    }
+
+    #[test]
+    fn it_prefers_the_pull_request_branch_on_github() {
+        let git_context = GitContext::from_repo_and_env(
+            no_overrides(),
+            None,
+            env(&[
+                ("GITHUB_SHA", "f84b32caddddfdd9fa87d7ce2140d56eabe805ee"),
+                ("GITHUB_REF", "refs/pull/42/merge"),
+                ("GITHUB_HEAD_REF", "feature"),
+            ]),
+        );
+
+        assert_eq!(git_context.branch.as_deref(), Some("feature"));
+    }
+
+    #[test]
+    fn it_ignores_github_refs_that_are_not_branches() {
+        let git_context = GitContext::from_repo_and_env(
+            no_overrides(),
+            None,
+            env(&[("GITHUB_REF", "refs/tags/v1.0.0"), ("GITHUB_HEAD_REF", "")]),
+        );
+
+        assert_eq!(git_context.branch, None);
+        assert_eq!(git_context.branch_source, None);
+    }
+
+    #[test]
+    fn it_is_empty_outside_a_repo_without_ci() {
+        let git_context = GitContext::from_repo_and_env(no_overrides(), None, env(&[]));
+
+        assert_eq!(git_context, no_overrides());
+    }
 }
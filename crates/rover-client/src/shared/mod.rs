@@ -11,6 +11,6 @@ pub use check_response::{
     LintCheckResponse, OperationCheckResponse, SchemaChange, ValidationPeriod,
 };
 pub use fetch_response::{FetchResponse, Sdl, SdlType};
-pub use git_context::GitContext;
+pub use git_context::{GitContext, GitContextSource};
 pub use graph_ref::GraphRef;
 pub use lint_response::{Diagnostic, LintResponse};
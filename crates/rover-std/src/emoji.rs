@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use console::Emoji as ConsoleEmoji;
+use crate::style::{is_bool_env_var_set, is_no_color_set, is_plain};
 
 #[derive(Debug, Copy, Clone)]
 pub enum Emoji {
@@ -48,14 +48,107 @@ impl Emoji {
             Memo => "📝 ",
         }
     }
+
+    /// the plain ASCII stand-in used where emoji can't be rendered
+    fn ascii(&self) -> &str {
+        use Emoji::*;
+        match self {
+            Action => "[>] ",
+            Hourglass => "[~] ",
+            Person => "[@] ",
+            Web => "[web] ",
+            Note => "[i] ",
+            Rocket => "[^] ",
+            Stop => "[x] ",
+            Success => "[ok] ",
+            Watch => "[o] ",
+            Reload => "[r] ",
+            Listen => "[<] ",
+            Start => "[>>] ",
+            New => "[+] ",
+            Sparkle => "[*] ",
+            Skull => "[x_x] ",
+            Compose => "[&] ",
+            Warn => "[!] ",
+            Memo => "[#] ",
+        }
+    }
 }
 
 impl Display for Emoji {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if std::env::var_os("NO_EMOJI").is_some() {
-            Ok(())
+        match EmojiMode::from_env() {
+            EmojiMode::Hidden => Ok(()),
+            EmojiMode::Ascii => write!(f, "{}", self.ascii()),
+            EmojiMode::Unicode => write!(f, "{}", self.get()),
+        }
+    }
+}
+
+/// How every [`Emoji`] is rendered, decided in one place from the environment
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum EmojiMode {
+    Unicode,
+    Ascii,
+    Hidden,
+}
+
+impl EmojiMode {
+    fn from_env() -> Self {
+        if is_plain() || std::env::var_os("NO_EMOJI").is_some() {
+            EmojiMode::Hidden
+        } else if is_bool_env_var_set("ROVER_NO_EMOJI") {
+            EmojiMode::Ascii
+        } else if is_bool_env_var_set("ROVER_EMOJI") {
+            // keeps emoji for those who only want color disabled
+            EmojiMode::Unicode
+        } else if is_no_color_set() || !terminal_supports_emoji() {
+            EmojiMode::Ascii
         } else {
-            write!(f, "{}", ConsoleEmoji::new(self.get(), ""))
+            EmojiMode::Unicode
+        }
+    }
+}
+
+// older Windows consoles render emoji as boxes
+#[cfg(windows)]
+fn terminal_supports_emoji() -> bool {
+    console::Term::stdout().wants_emoji()
+}
+
+#[cfg(not(windows))]
+fn terminal_supports_emoji() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::with_env_vars;
+
+    #[test]
+    fn it_falls_back_to_ascii_when_rover_no_emoji_is_set() {
+        assert_eq!(with_env_vars(&[], || Emoji::Warn.to_string()), "⚠️  ");
+        assert_eq!(
+            with_env_vars(&[("ROVER_NO_EMOJI", "1")], || Emoji::Warn.to_string()),
+            "[!] "
+        );
+    }
+
+    #[test]
+    fn it_follows_no_color_unless_emoji_are_kept() {
+        let cases: [(&[(&str, &str)], &str); 4] = [
+            (&[], "⚠️  "),
+            (&[("ROVER_EMOJI", "1")], "⚠️  "),
+            (&[("NO_COLOR", "1")], "[!] "),
+            (&[("NO_COLOR", "1"), ("ROVER_EMOJI", "1")], "⚠️  "),
+        ];
+        for (vars, expected) in cases {
+            assert_eq!(
+                with_env_vars(vars, || Emoji::Warn.to_string()),
+                expected,
+                "{vars:?}"
+            );
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::{path::PathBuf, time::Duration};
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -12,4 +14,66 @@ pub enum RoverStdError {
         /// The empty file path
         empty_file: String,
     },
+
+    /// This error is thrown when a file is larger than the size limit for reading it
+    #[error("\"{path}\" is {size} bytes, which is larger than the limit of {limit} bytes.")]
+    FileTooLarge {
+        /// The path of the file that was too large
+        path: String,
+        /// The size of the file in bytes
+        size: u64,
+        /// The maximum number of bytes that could be read
+        limit: usize,
+    },
+
+    /// This error is thrown when a file does not contain valid UTF-8
+    #[error("\"{path}\" is not a valid UTF-8 file.")]
+    NotUtf8 {
+        /// The path of the file that could not be decoded
+        path: String,
+    },
+
+    /// This error is thrown when a `.gz` file can't be decompressed
+    #[error("\"{path}\" could not be decompressed as gzip: {source}")]
+    InvalidGzip {
+        /// The path of the file that could not be decompressed
+        path: String,
+        /// The error from the decompressor
+        source: std::io::Error,
+    },
+
+    /// This error is thrown when a file system operation fails
+    #[error("failed to {operation} `{}`: {source}", path.display())]
+    Io {
+        /// The underlying IO error
+        source: std::io::Error,
+        /// The path that was being operated on
+        path: PathBuf,
+        /// What was being done to the path, e.g. "read" or "write"
+        operation: &'static str,
+    },
+
+    /// This error is thrown when copying a file fails
+    #[error("failed to copy `{}` to `{}`: {source}", from.display(), to.display())]
+    Copy {
+        /// The underlying IO error
+        source: std::io::Error,
+        /// The path that was being copied
+        from: PathBuf,
+        /// The path it was being copied to
+        to: PathBuf,
+    },
+
+    /// This error is thrown when the user cancels a prompt, e.g. with Ctrl-C
+    #[error("The operation was cancelled.")]
+    Interrupted,
+
+    /// This error is thrown when nothing finished writing to a pipe before the timeout
+    #[error("Timed out after {timeout:?} waiting for input on \"{path}\". Make sure another process is writing to it.")]
+    ReadTimeout {
+        /// The path of the pipe that was being read
+        path: String,
+        /// How long the read waited
+        timeout: Duration,
+    },
 }
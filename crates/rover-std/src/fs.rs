@@ -1,17 +1,34 @@
 use anyhow::{anyhow, Context};
-use camino::{ReadDirUtf8, Utf8Path};
-use crossbeam_channel::Sender;
+use camino::{ReadDirUtf8, Utf8Path, Utf8PathBuf};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TryRecvError};
+use flate2::read::GzDecoder;
 use notify::{watcher, DebouncedEvent, RecursiveMode, Watcher};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
 
 use std::{
     fs::{self, File},
+    io::{self, Read, Write},
     str,
-    sync::mpsc::channel,
+    sync::mpsc::{self, channel},
+    thread::{self, JoinHandle},
     time::Duration,
 };
 
 use crate::{Emoji, RoverStdError};
 
+/// how much of a file [`Fs::copy_with_progress`] copies at a time
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// how often a file watch checks whether it has been stopped
+const WATCH_TICK: Duration = Duration::from_millis(100);
+
+/// how long to wait on a pipe before telling the user that rover is waiting on it
+const PIPE_NOTICE_DELAY: Duration = Duration::from_secs(2);
+
+/// the bytes every gzip stream starts with
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
 /// Interact with a file system
 #[derive(Default, Copy, Clone)]
 pub struct Fs {}
@@ -27,8 +44,7 @@ impl Fs {
             Ok(metadata) => {
                 if metadata.is_file() {
                     tracing::info!("reading {} from disk", &path);
-                    let contents = fs::read_to_string(path)
-                        .with_context(|| format!("could not read {}", &path))?;
+                    let contents = fs::read_to_string(path).map_err(io_error("read", path))?;
                     if contents.is_empty() {
                         Err(RoverStdError::EmptyFile {
                             empty_file: path.to_string(),
@@ -40,8 +56,99 @@ impl Fs {
                     Err(anyhow!("'{}' is not a file", path).into())
                 }
             }
-            Err(e) => Err(anyhow!("could not find '{}'", path).context(e).into()),
+            Err(e) => Err(io_error("read", path)(e)),
+        }
+    }
+
+    /// reads a file from disk, erroring if it is larger than `max_bytes`
+    /// or if it is not valid UTF-8
+    pub fn read_file_to_string<P>(
+        path: P,
+        max_bytes: Option<usize>,
+    ) -> Result<String, RoverStdError>
+    where
+        P: AsRef<Utf8Path>,
+    {
+        let path = path.as_ref();
+        let metadata = Self::metadata(path)?;
+        if !metadata.is_file() {
+            return Err(anyhow!("'{}' is not a file", path).into());
+        }
+        let too_large = |size| RoverStdError::FileTooLarge {
+            path: path.to_string(),
+            size,
+            limit: max_bytes.unwrap_or_default(),
+        };
+        if let Some(limit) = max_bytes {
+            if metadata.len() > limit as u64 {
+                return Err(too_large(metadata.len()));
+            }
+        }
+        tracing::info!("reading {} from disk", &path);
+        let file = File::open(path).map_err(io_error("read", path))?;
+        let mut contents = Vec::new();
+        // the file may have grown since its metadata was read,
+        // so never read more than one byte past the limit
+        file.take(max_bytes.map_or(u64::MAX, |limit| limit as u64 + 1))
+            .read_to_end(&mut contents)
+            .map_err(io_error("read", path))?;
+        if let Some(limit) = max_bytes {
+            if contents.len() > limit {
+                return Err(too_large(contents.len() as u64));
+            }
+        }
+        String::from_utf8(contents).map_err(|_| RoverStdError::NotUtf8 {
+            path: path.to_string(),
+        })
+    }
+
+    /// reads a gzip compressed file from disk, like a schema saved as
+    /// `schema.graphql.gz`, decompressing it into a string
+    pub fn read_gzip_file<P>(path: P) -> Result<String, RoverStdError>
+    where
+        P: AsRef<Utf8Path>,
+    {
+        let path = path.as_ref();
+        if !Self::metadata(path)?.is_file() {
+            return Err(anyhow!("'{}' is not a file", path).into());
         }
+        tracing::info!("reading and decompressing {} from disk", &path);
+        let file = File::open(path).map_err(io_error("read", path))?;
+        let contents = gunzip(path, file)?;
+        if contents.is_empty() {
+            return Err(RoverStdError::EmptyFile {
+                empty_file: path.to_string(),
+            });
+        }
+        String::from_utf8(contents).map_err(|_| RoverStdError::NotUtf8 {
+            path: path.to_string(),
+        })
+    }
+
+    /// reads something that blocks until another process writes to it, like a
+    /// named pipe (FIFO), by reading on a separate thread. a notice is printed
+    /// if nothing has arrived after a couple of seconds, and an error is
+    /// returned if the writer hasn't finished once `timeout` has elapsed. a
+    /// pipe's name says nothing about what's written to it, so whatever
+    /// arrives is decompressed if it starts like a gzip stream
+    pub fn read_pipe<P>(path: P, timeout: Duration) -> Result<String, RoverStdError>
+    where
+        P: AsRef<Utf8Path>,
+    {
+        read_pipe_with_notice(path.as_ref(), timeout, &mut io::stderr())
+    }
+
+    /// returns the hex-encoded sha256 digest of a file's contents,
+    /// reading it in chunks so large files are never fully loaded into memory
+    pub fn sha256<P>(path: P) -> Result<String, RoverStdError>
+    where
+        P: AsRef<Utf8Path>,
+    {
+        let path = path.as_ref();
+        let mut file = File::open(path).map_err(io_error("read", path))?;
+        let mut hasher = Sha256::new();
+        io::copy(&mut file, &mut hasher).map_err(io_error("read", path))?;
+        Ok(format!("{:x}", hasher.finalize()))
     }
 
     /// writes a file to disk
@@ -58,18 +165,73 @@ impl Fs {
             )
         })?;
         if !path.exists() {
-            File::create(path)
-                .with_context(|| format!("{} does not exist and it could not be created", &path))?;
+            File::create(path).map_err(io_error("create", path))?;
         }
         if !path.exists() {
-            File::create(path)
-                .with_context(|| format!("{} does not exist and it could not be created", &path))?;
+            File::create(path).map_err(io_error("create", path))?;
         }
         tracing::info!("writing {} to disk", &path);
-        fs::write(path, contents).with_context(|| format!("could not write {}", &path))?;
+        fs::write(path, contents).map_err(io_error("write", path))?;
         Ok(())
     }
 
+    /// writes a file to disk, creating any of its parent directories that
+    /// do not exist yet. existing files are truncated rather than recreated,
+    /// so their permissions are left as they were
+    pub fn write_file_creating_dirs<P, C>(path: P, contents: C) -> Result<(), RoverStdError>
+    where
+        P: AsRef<Utf8Path>,
+        C: AsRef<[u8]>,
+    {
+        let path = path.as_ref();
+        let missing: Vec<&Utf8Path> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| !dir.as_str().is_empty() && !dir.exists())
+            .collect();
+        // the directories are created one at a time, outermost first, so
+        // that an error names the one that couldn't be created
+        for dir in missing.into_iter().rev() {
+            tracing::info!("creating {} directory", &dir);
+            match fs::create_dir(dir) {
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists && dir.is_dir() => {}
+                created => created.map_err(io_error("create directory", dir))?,
+            }
+        }
+        Self::write_file(path, contents)
+    }
+
+    /// writes a file to disk by writing to a temporary file in the same
+    /// directory and renaming it into place, so that readers never observe
+    /// a partially written file. a file that's replaced keeps its permissions
+    pub fn write_file_atomic<P, C>(path: P, contents: C) -> Result<(), RoverStdError>
+    where
+        P: AsRef<Utf8Path>,
+        C: AsRef<[u8]>,
+    {
+        let path = path.as_ref();
+        let contents = str::from_utf8(contents.as_ref()).with_context(|| {
+            format!(
+                "tried to write contents to {} that was invalid UTF-8",
+                &path
+            )
+        })?;
+        let mut temp_file = temp_file_beside(path)?;
+        tracing::info!("writing {} to disk", &path);
+        temp_file
+            .write_all(contents.as_bytes())
+            .and_then(|_| temp_file.as_file().sync_all())
+            .map_err(io_error("write", path))?;
+        // temporary files are only readable by their owner
+        if let Ok(metadata) = fs::metadata(path) {
+            temp_file
+                .as_file()
+                .set_permissions(metadata.permissions())
+                .map_err(io_error("write", path))?;
+        }
+        persist(temp_file, path)
+    }
+
     /// creates a directory
     pub fn create_dir_all<P>(path: P) -> Result<(), RoverStdError>
     where
@@ -77,8 +239,7 @@ impl Fs {
     {
         let path = path.as_ref();
         tracing::info!("creating {} directory", &path);
-        fs::create_dir_all(path)
-            .with_context(|| format!("could not create {} directory", &path))?;
+        fs::create_dir_all(path).map_err(io_error("create directory", path))?;
         Ok(())
     }
 
@@ -90,10 +251,103 @@ impl Fs {
         let dir = dir.as_ref();
         let entries = dir
             .read_dir_utf8()
-            .with_context(|| format!("could not read entries of {}", dir))?;
+            .map_err(io_error("read entries of", dir))?;
         Ok(entries)
     }
 
+    /// lists the entries of a directory in lexicographic order, leaving out
+    /// hidden ones whose names start with a `.`. with an `extension` like
+    /// `"graphql"`, only files with that extension are listed.
+    pub fn list_dir<D>(dir: D, extension: Option<&str>) -> Result<Vec<Utf8PathBuf>, RoverStdError>
+    where
+        D: AsRef<Utf8Path>,
+    {
+        let dir = dir.as_ref();
+        let extension = extension.map(|extension| extension.trim_start_matches('.'));
+        let mut paths = Vec::new();
+        for entry in Self::get_dir_entries(dir)? {
+            let entry = entry.map_err(io_error("read entries of", dir))?;
+            if entry.file_name().starts_with('.') {
+                continue;
+            }
+            if let Some(extension) = extension {
+                let is_file = entry
+                    .file_type()
+                    .map_err(io_error("read entries of", dir))?
+                    .is_file();
+                if !is_file || entry.path().extension() != Some(extension) {
+                    continue;
+                }
+            }
+            paths.push(entry.into_path());
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// lists the files under `root` in lexicographic order of their path
+    /// components, looking at most `max_depth` directories deep, where the
+    /// files directly in `root` are at a depth of 1. like with
+    /// [`Fs::list_dir`], hidden entries are left out and an `extension` like
+    /// `"graphql"` only lists files with it.
+    /// symlinks to files are listed, but symlinked directories aren't walked
+    /// so that a link back up the tree can't loop forever.
+    pub fn read_dir_recursive<D>(
+        root: D,
+        max_depth: usize,
+        extension: Option<&str>,
+    ) -> Result<Vec<Utf8PathBuf>, RoverStdError>
+    where
+        D: AsRef<Utf8Path>,
+    {
+        let root = root.as_ref();
+        if !Self::path_is_dir(root)? {
+            return Err(anyhow!("could not read {} because it is not a directory", root).into());
+        }
+        let extension = extension.map(|extension| extension.trim_start_matches('.'));
+        let mut paths = Vec::new();
+        Self::collect_files(root, max_depth, extension, &mut paths)?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn collect_files(
+        dir: &Utf8Path,
+        depth: usize,
+        extension: Option<&str>,
+        paths: &mut Vec<Utf8PathBuf>,
+    ) -> Result<(), RoverStdError> {
+        if depth == 0 {
+            return Ok(());
+        }
+        for entry in Self::get_dir_entries(dir)? {
+            let entry = entry.map_err(io_error("read entries of", dir))?;
+            if entry.file_name().starts_with('.') {
+                continue;
+            }
+            let file_type = entry
+                .file_type()
+                .map_err(io_error("read entries of", dir))?;
+            let is_file = if file_type.is_symlink() {
+                // a broken symlink is neither, so it's skipped
+                fs::metadata(entry.path()).map_or(false, |metadata| metadata.is_file())
+            } else if file_type.is_dir() {
+                Self::collect_files(entry.path(), depth - 1, extension, paths)?;
+                false
+            } else {
+                file_type.is_file()
+            };
+            if is_file
+                && extension.map_or(true, |extension| {
+                    entry.path().extension() == Some(extension)
+                })
+            {
+                paths.push(entry.into_path());
+            }
+        }
+        Ok(())
+    }
+
     /// assert that a file exists
     pub fn assert_path_exists<F>(file: F) -> Result<(), RoverStdError>
     where
@@ -110,8 +364,41 @@ impl Fs {
         F: AsRef<Utf8Path>,
     {
         let file = file.as_ref();
-        Ok(fs::metadata(file)
-            .with_context(|| format!("could not find a file at the path '{}'", file))?)
+        fs::metadata(file).map_err(io_error("find", file))
+    }
+
+    /// resolves a path that exists to an absolute path, without any `.`, `..`
+    /// or symlinks in it
+    pub fn canonicalize<P>(path: P) -> Result<Utf8PathBuf, RoverStdError>
+    where
+        P: AsRef<Utf8Path>,
+    {
+        let path = path.as_ref();
+        path.canonicalize_utf8().map_err(io_error("resolve", path))
+    }
+
+    /// like [`Fs::canonicalize`], but for a file that might not have been
+    /// created yet: its parent directory has to exist, and it's resolved with
+    /// the file's name added back on
+    pub fn canonicalize_new_file<P>(path: P) -> Result<Utf8PathBuf, RoverStdError>
+    where
+        P: AsRef<Utf8Path>,
+    {
+        let path = path.as_ref();
+        if path.exists() {
+            return Self::canonicalize(path);
+        }
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(file_name)) => {
+                let parent = if parent.as_str().is_empty() {
+                    Utf8Path::new(".")
+                } else {
+                    parent
+                };
+                Ok(Self::canonicalize(parent)?.join(file_name))
+            }
+            _ => Self::canonicalize(path),
+        }
     }
 
     /// copies one file to another
@@ -126,22 +413,86 @@ impl Fs {
         // attempt to remove the old file
         // but do not error if it doesn't exist.
         let _ = fs::remove_file(out_path);
-        fs::copy(in_path, out_path)
-            .with_context(|| format!("could not copy {} to {}", &in_path, &out_path))?;
+        fs::copy(in_path, out_path).map_err(copy_error(in_path, out_path))?;
         Ok(())
     }
 
-    /// recursively removes directories
+    /// copies one file to another in chunks, calling `on_progress` with the
+    /// total number of bytes copied so far after each one, so that large
+    /// copies can show their progress. the copy is written to a temporary
+    /// file that's renamed into place, so `out_path` is never left partially
+    /// written. returns the number of bytes copied.
+    pub fn copy_with_progress<I, O, F>(
+        in_path: I,
+        out_path: O,
+        mut on_progress: F,
+    ) -> Result<u64, RoverStdError>
+    where
+        I: AsRef<Utf8Path>,
+        O: AsRef<Utf8Path>,
+        F: FnMut(u64),
+    {
+        let in_path = in_path.as_ref();
+        let out_path = out_path.as_ref();
+        tracing::info!("copying {} to {}", in_path, out_path);
+        let mut in_file = File::open(in_path).map_err(copy_error(in_path, out_path))?;
+        let mut temp_file = temp_file_beside(out_path)?;
+        let mut buffer = vec![0; COPY_CHUNK_SIZE];
+        let mut copied = 0;
+        loop {
+            let read = match in_file.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(copy_error(in_path, out_path)(e)),
+            };
+            temp_file
+                .write_all(&buffer[..read])
+                .map_err(copy_error(in_path, out_path))?;
+            copied += read as u64;
+            on_progress(copied);
+        }
+        temp_file
+            .as_file()
+            .sync_all()
+            .map_err(copy_error(in_path, out_path))?;
+        persist(temp_file, out_path)?;
+        Ok(copied)
+    }
+
+    /// removes a file, doing nothing if it doesn't exist
+    pub fn remove_file<F>(file: F) -> Result<(), RoverStdError>
+    where
+        F: AsRef<Utf8Path>,
+    {
+        let file = file.as_ref();
+        tracing::info!("removing {}", file);
+        match fs::remove_file(file) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(io_error("remove", file)(e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// recursively removes directories, doing nothing if `dir` doesn't exist
     pub fn remove_dir_all<D>(dir: D) -> Result<(), RoverStdError>
     where
         D: AsRef<Utf8Path>,
     {
         let dir = dir.as_ref();
-        if Self::path_is_dir(dir)? {
-            fs::remove_dir_all(dir).with_context(|| format!("could not remove {}", dir))?;
-            Ok(())
-        } else {
-            Err(anyhow!("could not remove {} because it is not a directory", dir).into())
+        match fs::metadata(dir) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(io_error("find", dir)(e)),
+            Ok(metadata) if !metadata.is_dir() => {
+                return Err(
+                    anyhow!("could not remove {} because it is not a directory", dir).into(),
+                )
+            }
+            Ok(_) => {}
+        }
+        tracing::info!("removing {}", dir);
+        match fs::remove_dir_all(dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(io_error("remove", dir)(e)),
+            _ => Ok(()),
         }
     }
 
@@ -171,9 +522,8 @@ impl Fs {
                     if let Some(entry_name) = entry_path.file_name() {
                         let out_file = out_dir.join(entry_name);
                         tracing::info!("copying {} to {}", &entry_path, &out_file);
-                        fs::copy(entry_path, &out_file).with_context(|| {
-                            format!("could not copy {} to {}", &entry_path, &out_file)
-                        })?;
+                        fs::copy(entry_path, &out_file)
+                            .map_err(copy_error(entry_path, &out_file))?;
                     }
                 } else if metadata.is_dir() && entry_path != in_dir {
                     if let Some(entry_name) = entry_path.file_name() {
@@ -238,4 +588,888 @@ impl Fs {
             }
         })
     }
+
+    /// watches a file for changes, yielding its path from the returned
+    /// [`FileWatch`] whenever it is written to. changes that happen within
+    /// `debounce` of each other are reported as a single event.
+    ///
+    /// the watch is torn down when the [`FileWatch`] is dropped.
+    ///
+    /// Example:
+    /// let watch = Fs::watch_file_debounced("./schema.graphql", Duration::from_millis(250))?;
+    /// for path in &watch {
+    ///   println!("file contents:\n{}", Fs::read_file(&path)?);
+    /// }
+    pub fn watch_file_debounced<P>(path: P, debounce: Duration) -> Result<FileWatch, RoverStdError>
+    where
+        P: AsRef<Utf8Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let (fs_tx, fs_rx) = channel();
+        let mut watcher = watcher(fs_tx, debounce)
+            .with_context(|| format!("could not watch {} for changes", &path))?;
+        watcher
+            .watch(&path, RecursiveMode::NonRecursive)
+            .with_context(|| format!("could not watch {} for changes", &path))?;
+
+        let (tx, changes) = crossbeam_channel::unbounded();
+        let (stop, stopped) = crossbeam_channel::bounded::<()>(0);
+        let thread_path = path.clone();
+        // the thread runs for as long as the file is watched,
+        // so it gets its own rather than holding one of rayon's
+        let thread = thread::Builder::new()
+            .name(format!("watch {}", &path))
+            .spawn(move || {
+                let path = thread_path;
+                // the watcher stops watching as soon as it is dropped,
+                // so it has to be moved into the thread it reports to
+                let _watcher = watcher;
+                // nothing is ever sent to `stopped`, it's only disconnected
+                // once the `FileWatch` is dropped
+                while let Err(TryRecvError::Empty) = stopped.try_recv() {
+                    match fs_rx.recv_timeout(WATCH_TICK) {
+                        Ok(DebouncedEvent::Write(_) | DebouncedEvent::Create(_)) => {
+                            let _ = tx.send(path.clone());
+                        }
+                        Ok(_) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                tracing::info!("stopped watching {} for changes", &path);
+            })
+            .with_context(|| format!("could not watch {} for changes", &path))?;
+        Ok(FileWatch {
+            changes,
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+}
+
+/// a watch on a file from [`Fs::watch_file_debounced`], which yields the
+/// file's path whenever it changes. dropping it stops the watch, waiting for
+/// the thread behind it to exit.
+#[derive(Debug)]
+pub struct FileWatch {
+    changes: Receiver<Utf8PathBuf>,
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FileWatch {
+    /// waits up to `timeout` for the next change
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Utf8PathBuf, RecvTimeoutError> {
+        self.changes.recv_timeout(timeout)
+    }
+}
+
+impl<'a> IntoIterator for &'a FileWatch {
+    type Item = Utf8PathBuf;
+    type IntoIter = crossbeam_channel::Iter<'a, Utf8PathBuf>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.iter()
+    }
+}
+
+impl Drop for FileWatch {
+    fn drop(&mut self) {
+        // disconnects the channel the thread checks on every tick
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// creates a temporary file to write `path` to before renaming it into place.
+/// it has to be on the same filesystem as `path` for the rename to be atomic,
+/// so it's created as a sibling.
+fn temp_file_beside(path: &Utf8Path) -> Result<NamedTempFile, RoverStdError> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_str().is_empty() => parent,
+        _ => Utf8Path::new("."),
+    };
+    NamedTempFile::new_in(parent).map_err(io_error("create a temporary file in", parent))
+}
+
+/// renames a temporary file from [`temp_file_beside`] to `path`
+fn persist(temp_file: NamedTempFile, path: &Utf8Path) -> Result<(), RoverStdError> {
+    // renaming over an existing file can fail on Windows
+    if cfg!(windows) && path.exists() {
+        fs::remove_file(path).map_err(io_error("replace", path))?;
+    }
+    temp_file
+        .persist(path)
+        .map_err(|e| io_error("write", path)(e.error))?;
+    Ok(())
+}
+
+/// reads the pipe at `path` for [`Fs::read_pipe`], writing the notice that
+/// it's being waited on to `notices`
+fn read_pipe_with_notice(
+    path: &Utf8Path,
+    timeout: Duration,
+    notices: &mut impl Write,
+) -> Result<String, RoverStdError> {
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    let reader_path = path.to_path_buf();
+    // the thread is left blocked if the read times out, which is fine
+    // for a short-lived CLI process
+    thread::spawn(move || {
+        let _ = tx.send(fs::read(&reader_path).map_err(io_error("read", &reader_path)));
+    });
+
+    let notice_after = PIPE_NOTICE_DELAY.min(timeout);
+    let received = match rx.recv_timeout(notice_after) {
+        Err(RecvTimeoutError::Timeout) if notice_after < timeout => {
+            let _ = writeln!(notices, "{}waiting for input on {}", Emoji::Hourglass, path);
+            rx.recv_timeout(timeout - notice_after)
+        }
+        received => received,
+    };
+    let contents = match received {
+        Ok(contents) => contents?,
+        Err(_) => {
+            return Err(RoverStdError::ReadTimeout {
+                path: path.to_string(),
+                timeout,
+            })
+        }
+    };
+    let contents = if contents.starts_with(&GZIP_MAGIC) {
+        gunzip(path, contents.as_slice())?
+    } else {
+        contents
+    };
+    String::from_utf8(contents).map_err(|_| RoverStdError::NotUtf8 {
+        path: path.to_string(),
+    })
+}
+
+/// decompresses the gzip stream `compressed`, which was read from `path`
+fn gunzip(path: &Utf8Path, compressed: impl Read) -> Result<Vec<u8>, RoverStdError> {
+    let mut contents = Vec::new();
+    GzDecoder::new(compressed)
+        .read_to_end(&mut contents)
+        .map_err(|source| RoverStdError::InvalidGzip {
+            path: path.to_string(),
+            source,
+        })?;
+    Ok(contents)
+}
+
+/// attaches the path being operated on to an IO error
+fn io_error<'a>(
+    operation: &'static str,
+    path: &'a Utf8Path,
+) -> impl FnOnce(io::Error) -> RoverStdError + 'a {
+    move |source| RoverStdError::Io {
+        source,
+        path: path.into(),
+        operation,
+    }
+}
+
+/// attaches both paths of a failed copy to an IO error
+fn copy_error<'a>(
+    from: &'a Utf8Path,
+    to: &'a Utf8Path,
+) -> impl FnOnce(io::Error) -> RoverStdError + 'a {
+    move |source| RoverStdError::Copy {
+        source,
+        from: from.into(),
+        to: to.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use assert_fs::TempDir;
+    use camino::Utf8PathBuf;
+    use std::time::Instant;
+
+    #[test]
+    fn it_reads_files_within_the_size_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql")).unwrap();
+        Fs::write_file(&file_path, "type Query { hello: String }").unwrap();
+
+        assert_eq!(
+            Fs::read_file_to_string(&file_path, Some(1024)).unwrap(),
+            "type Query { hello: String }"
+        );
+        assert_eq!(
+            Fs::read_file_to_string(&file_path, None).unwrap(),
+            "type Query { hello: String }"
+        );
+    }
+
+    #[test]
+    fn it_errors_when_reading_files_over_the_size_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql")).unwrap();
+        Fs::write_file(&file_path, "type Query { hello: String }").unwrap();
+
+        let err = Fs::read_file_to_string(&file_path, Some(10)).unwrap_err();
+        assert!(matches!(
+            err,
+            RoverStdError::FileTooLarge {
+                size: 28,
+                limit: 10,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("schema.graphql"));
+    }
+
+    #[test]
+    fn it_errors_when_reading_files_that_are_not_utf8() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql")).unwrap();
+        fs::write(&file_path, [0xff, 0xfe, 0x00, 0x01]).unwrap();
+
+        assert!(matches!(
+            Fs::read_file_to_string(&file_path, None),
+            Err(RoverStdError::NotUtf8 { .. })
+        ));
+    }
+
+    fn write_gzip_file(path: &Utf8Path, contents: &[u8]) {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(contents).unwrap();
+        fs::write(path, encoder.finish().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn it_reads_gzip_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql.gz")).unwrap();
+        write_gzip_file(&file_path, b"type Query { hello: String }");
+
+        assert_eq!(
+            Fs::read_gzip_file(&file_path).unwrap(),
+            "type Query { hello: String }"
+        );
+    }
+
+    #[test]
+    fn it_errors_when_reading_corrupted_gzip_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql.gz")).unwrap();
+        Fs::write_file(&file_path, "type Query { hello: String }").unwrap();
+
+        let err = Fs::read_gzip_file(&file_path).unwrap_err();
+        assert!(matches!(err, RoverStdError::InvalidGzip { .. }));
+        assert!(err.to_string().contains("schema.graphql.gz"));
+    }
+
+    #[test]
+    fn it_errors_when_reading_empty_gzip_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql.gz")).unwrap();
+        write_gzip_file(&file_path, b"");
+
+        assert!(matches!(
+            Fs::read_gzip_file(&file_path),
+            Err(RoverStdError::EmptyFile { .. })
+        ));
+    }
+
+    #[test]
+    fn it_includes_the_path_when_reading_a_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("missing.graphql")).unwrap();
+
+        for err in [
+            Fs::read_file(&file_path).unwrap_err(),
+            Fs::read_file_to_string(&file_path, None).unwrap_err(),
+            Fs::sha256(&file_path).unwrap_err(),
+        ] {
+            assert!(matches!(err, RoverStdError::Io { .. }));
+            let message = err.to_string();
+            assert!(message.contains(&format!("`{}`", file_path)), "{message}");
+        }
+        assert!(Fs::read_file(&file_path)
+            .unwrap_err()
+            .to_string()
+            .starts_with(&format!("failed to read `{}`: ", file_path)));
+    }
+
+    #[cfg(unix)]
+    fn make_fifo(temp_dir: &TempDir, name: &str) -> Utf8PathBuf {
+        let fifo_path = Utf8PathBuf::try_from(temp_dir.path().join(name)).unwrap();
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo_path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        fifo_path
+    }
+
+    #[test]
+    fn it_canonicalizes_an_existing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = Utf8PathBuf::try_from(temp_dir.path().to_path_buf()).unwrap();
+        let canonical_dir = Fs::canonicalize(&dir).unwrap();
+        Fs::write_file(dir.join("schema.graphql"), "type Query { hello: String }").unwrap();
+        Fs::create_dir_all(dir.join("subgraphs")).unwrap();
+
+        let path = dir.join("subgraphs").join("..").join("schema.graphql");
+        assert_eq!(
+            Fs::canonicalize(&path).unwrap(),
+            canonical_dir.join("schema.graphql")
+        );
+        assert_eq!(
+            Fs::canonicalize_new_file(&path).unwrap(),
+            canonical_dir.join("schema.graphql")
+        );
+    }
+
+    #[test]
+    fn it_canonicalizes_a_missing_file_in_an_existing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = Utf8PathBuf::try_from(temp_dir.path().to_path_buf()).unwrap();
+        let path = dir.join("supergraph.graphql");
+
+        let err = Fs::canonicalize(&path).unwrap_err();
+        assert!(matches!(err, RoverStdError::Io { .. }));
+        assert!(
+            err.to_string()
+                .contains(&format!("failed to resolve `{}`", path)),
+            "{err}"
+        );
+
+        assert_eq!(
+            Fs::canonicalize_new_file(&path).unwrap(),
+            Fs::canonicalize(&dir).unwrap().join("supergraph.graphql")
+        );
+    }
+
+    #[test]
+    fn it_does_not_canonicalize_a_file_in_a_missing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing_dir = Utf8PathBuf::try_from(temp_dir.path().join("missing")).unwrap();
+
+        let err = Fs::canonicalize_new_file(missing_dir.join("supergraph.graphql")).unwrap_err();
+        assert!(matches!(err, RoverStdError::Io { .. }));
+        assert!(
+            err.to_string()
+                .contains(&format!("failed to resolve `{}`", missing_dir)),
+            "{err}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_reads_from_a_fifo() {
+        let temp_dir = TempDir::new().unwrap();
+        let fifo_path = make_fifo(&temp_dir, "schema.fifo");
+        let writer_path = fifo_path.clone();
+        std::thread::spawn(move || fs::write(writer_path, "type Query { hello: String }"));
+
+        assert_eq!(
+            Fs::read_pipe(&fifo_path, Duration::from_secs(10)).unwrap(),
+            "type Query { hello: String }"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_decompresses_gzip_written_to_a_fifo() {
+        let temp_dir = TempDir::new().unwrap();
+        let compressed = temp_dir.path().join("compressed");
+        write_gzip_file(
+            Utf8Path::from_path(&compressed).unwrap(),
+            b"type Query { hello: String }",
+        );
+        for name in ["schema.graphql.gz", "schema.fifo"] {
+            let fifo_path = make_fifo(&temp_dir, name);
+            let (writer_path, compressed) = (fifo_path.clone(), compressed.clone());
+            std::thread::spawn(move || fs::write(writer_path, fs::read(compressed).unwrap()));
+
+            assert_eq!(
+                Fs::read_pipe(&fifo_path, Duration::from_secs(10)).unwrap(),
+                "type Query { hello: String }",
+                "{name}"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_waits_on_a_fifo_until_its_writer_is_done() {
+        let temp_dir = TempDir::new().unwrap();
+        let fifo_path = make_fifo(&temp_dir, "schema.fifo");
+        let writer_path = fifo_path.clone();
+        // a slow writer, like a build that takes a while to print a schema
+        std::thread::spawn(move || {
+            std::thread::sleep(PIPE_NOTICE_DELAY + Duration::from_millis(500));
+            fs::write(writer_path, "type Query { hello: String }")
+        });
+
+        let mut notices = Vec::new();
+        assert_eq!(
+            read_pipe_with_notice(&fifo_path, Duration::from_secs(10), &mut notices).unwrap(),
+            "type Query { hello: String }"
+        );
+        let notices = String::from_utf8(notices).unwrap();
+        assert!(
+            notices.contains(&format!("waiting for input on {}", fifo_path)),
+            "{notices}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_times_out_reading_from_a_fifo_with_no_writer() {
+        let temp_dir = TempDir::new().unwrap();
+        let fifo_path = make_fifo(&temp_dir, "schema.fifo");
+
+        let err = Fs::read_pipe(&fifo_path, Duration::from_millis(100)).unwrap_err();
+
+        assert!(matches!(err, RoverStdError::ReadTimeout { .. }));
+        assert!(err.to_string().contains(fifo_path.as_str()));
+        // unblock the abandoned reader thread
+        let _ = fs::write(&fifo_path, "");
+    }
+
+    #[test]
+    fn it_creates_parent_directories_when_writing() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = Utf8PathBuf::try_from(temp_dir.path().to_path_buf()).unwrap();
+        let file_path = temp_path
+            .join("a")
+            .join("b")
+            .join("c")
+            .join("schema.graphql");
+
+        Fs::write_file_creating_dirs(&file_path, "type Query { hello: String }").unwrap();
+
+        assert!(temp_path.join("a").join("b").join("c").is_dir());
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            "type Query { hello: String }"
+        );
+    }
+
+    #[test]
+    fn it_names_the_directory_that_could_not_be_created_when_writing() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = Utf8PathBuf::try_from(temp_dir.path().to_path_buf()).unwrap();
+        // a file where a directory has to go
+        let blocker = temp_path.join("a");
+        Fs::write_file(&blocker, "not a directory").unwrap();
+        let file_path = blocker.join("b").join("c").join("schema.graphql");
+
+        let err = Fs::write_file_creating_dirs(file_path, "type Query { hello: String }")
+            .unwrap_err()
+            .to_string();
+
+        assert!(
+            err.starts_with(&format!(
+                "failed to create directory `{}`",
+                blocker.join("b")
+            )),
+            "{err}"
+        );
+        assert!(!err.contains("schema.graphql"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_preserves_permissions_when_writing_existing_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql")).unwrap();
+        Fs::write_file(&file_path, "type Query { old: String }").unwrap();
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        Fs::write_file_creating_dirs(&file_path, "type Query { new: String }").unwrap();
+
+        let mode = fs::metadata(&file_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[test]
+    fn it_sends_one_event_per_debounced_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql")).unwrap();
+        Fs::write_file(&file_path, "type Query { hello: String }").unwrap();
+
+        let rx = Fs::watch_file_debounced(&file_path, Duration::from_millis(200)).unwrap();
+        Fs::write_file(&file_path, "type Query { hello: String! }").unwrap();
+        Fs::write_file(&file_path, "type Query { hello: [String!]! }").unwrap();
+
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), file_path);
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn it_stops_watching_when_the_watch_is_dropped() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql")).unwrap();
+        Fs::write_file(&file_path, "type Query { hello: String }").unwrap();
+
+        let mut watch = Fs::watch_file_debounced(&file_path, Duration::from_millis(200)).unwrap();
+        let thread = watch.thread.take().unwrap();
+        drop(watch);
+
+        // the file is never written to again, so only the drop can stop it
+        let started = Instant::now();
+        while !thread.is_finished() {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "the watch is still running"
+            );
+            thread::sleep(WATCH_TICK);
+        }
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn it_errors_when_watching_a_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("missing.graphql")).unwrap();
+
+        assert!(Fs::watch_file_debounced(file_path, Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn it_computes_the_sha256_of_a_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql")).unwrap();
+        Fs::write_file(&file_path, "hello world").unwrap();
+
+        assert_eq!(
+            Fs::sha256(&file_path).unwrap(),
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+    }
+
+    #[test]
+    fn it_errors_when_computing_the_sha256_of_a_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("missing.graphql")).unwrap();
+
+        let err = Fs::sha256(file_path).unwrap_err();
+        assert!(err.to_string().contains("missing.graphql"));
+    }
+
+    #[test]
+    fn it_writes_files_atomically() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = Utf8PathBuf::try_from(temp_dir.path().to_path_buf()).unwrap();
+        let file_path = temp_path.join("schema.graphql");
+        let contents = "type Query { hello: String }\n".repeat(100_000);
+
+        Fs::write_file(&file_path, "type Query { old: String }").unwrap();
+        Fs::write_file_atomic(&file_path, &contents).unwrap();
+
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), contents);
+        let entries: Vec<_> = Fs::get_dir_entries(&temp_path)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string())
+            .collect();
+        assert_eq!(entries, vec!["schema.graphql".to_string()]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_preserves_permissions_when_replacing_files_atomically() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql")).unwrap();
+        Fs::write_file(&file_path, "type Query { old: String }").unwrap();
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        Fs::write_file_atomic(&file_path, "type Query { new: String }").unwrap();
+
+        let mode = fs::metadata(&file_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[test]
+    fn it_reports_progress_while_copying() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = Utf8PathBuf::try_from(temp_dir.path().to_path_buf()).unwrap();
+        let in_path = temp_path.join("supergraph.graphql");
+        let out_path = temp_path.join("copy.graphql");
+        let contents = "type Query { hello: String }\n".repeat(10_000);
+        Fs::write_file(&in_path, &contents).unwrap();
+
+        let mut totals = Vec::new();
+        let copied =
+            Fs::copy_with_progress(&in_path, &out_path, |total| totals.push(total)).unwrap();
+
+        assert_eq!(copied, contents.len() as u64);
+        assert!(
+            totals.len() > 1,
+            "the copy was done in {} chunk(s)",
+            totals.len()
+        );
+        assert!(totals.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(totals.last(), Some(&(contents.len() as u64)));
+        assert_eq!(fs::read_to_string(&out_path).unwrap(), contents);
+    }
+
+    #[test]
+    fn it_names_both_paths_when_a_copy_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = Utf8PathBuf::try_from(temp_dir.path().to_path_buf()).unwrap();
+        let in_dir = temp_path.join("in");
+        Fs::create_dir_all(&in_dir).unwrap();
+        Fs::write_file(
+            in_dir.join("schema.graphql"),
+            "type Query { hello: String }",
+        )
+        .unwrap();
+        // a directory where the copy of the file would go
+        Fs::create_dir_all(temp_path.join("out").join("schema.graphql")).unwrap();
+
+        let err = Fs::copy(
+            temp_path.join("missing.graphql"),
+            temp_path.join("copy.graphql"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, RoverStdError::Copy { .. }));
+        assert!(err.to_string().contains("missing.graphql"));
+        assert!(err.to_string().contains("copy.graphql"));
+
+        let err = Fs::copy_dir_all(&in_dir, temp_path.join("out")).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(in_dir.join("schema.graphql").as_str()));
+        assert!(err
+            .to_string()
+            .contains(temp_path.join("out").join("schema.graphql").as_str()));
+    }
+
+    #[test]
+    fn it_leaves_the_destination_alone_when_a_copy_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = Utf8PathBuf::try_from(temp_dir.path().to_path_buf()).unwrap();
+        let out_path = temp_path.join("copy.graphql");
+        Fs::write_file(&out_path, "type Query { old: String }").unwrap();
+
+        let err = Fs::copy_with_progress(temp_path.join("missing.graphql"), &out_path, |_| {})
+            .unwrap_err();
+
+        assert!(err.to_string().contains("missing.graphql"));
+        assert!(err.to_string().contains("copy.graphql"));
+        assert_eq!(
+            fs::read_to_string(&out_path).unwrap(),
+            "type Query { old: String }"
+        );
+    }
+
+    #[test]
+    fn it_removes_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql")).unwrap();
+        Fs::write_file(&file_path, "type Query { hello: String }").unwrap();
+
+        Fs::remove_file(&file_path).unwrap();
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn it_treats_removing_a_missing_file_as_done() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("missing.graphql")).unwrap();
+
+        Fs::remove_file(&file_path).unwrap();
+        Fs::remove_dir_all(&file_path).unwrap();
+    }
+
+    #[test]
+    fn it_removes_populated_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = Utf8PathBuf::try_from(temp_dir.path().join("subgraphs")).unwrap();
+        Fs::write_file_creating_dirs(dir.join("products/schema.graphql"), "type Query { a: ID }")
+            .unwrap();
+        Fs::write_file(dir.join("accounts.graphql"), "type Query { b: ID }").unwrap();
+
+        Fs::remove_dir_all(&dir).unwrap();
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn it_does_not_remove_files_as_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = Utf8PathBuf::try_from(temp_dir.path().join("schema.graphql")).unwrap();
+        Fs::write_file(&file_path, "type Query { hello: String }").unwrap();
+
+        let err = Fs::remove_dir_all(&file_path).unwrap_err();
+        assert!(err.to_string().contains("schema.graphql"));
+        assert!(file_path.exists());
+    }
+
+    fn schema_dir() -> (TempDir, Utf8PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = Utf8PathBuf::try_from(temp_dir.path().to_path_buf()).unwrap();
+        for name in [
+            "products.graphql",
+            "accounts.graphql",
+            "reviews.gql",
+            "README.md",
+            ".hidden.graphql",
+        ] {
+            Fs::write_file(dir.join(name), "type Query { hello: String }").unwrap();
+        }
+        Fs::create_dir_all(dir.join("inventory.graphql")).unwrap();
+        (temp_dir, dir)
+    }
+
+    fn file_names(paths: &[Utf8PathBuf]) -> Vec<&str> {
+        paths.iter().filter_map(|path| path.file_name()).collect()
+    }
+
+    #[test]
+    fn it_lists_directories_in_order_without_hidden_entries() {
+        let (_temp_dir, dir) = schema_dir();
+
+        let paths = Fs::list_dir(&dir, None).unwrap();
+        assert_eq!(
+            file_names(&paths),
+            [
+                "README.md",
+                "accounts.graphql",
+                "inventory.graphql",
+                "products.graphql",
+                "reviews.gql"
+            ]
+        );
+        assert!(paths.iter().all(|path| path.starts_with(&dir)));
+        assert_eq!(Fs::list_dir(&dir, None).unwrap(), paths);
+    }
+
+    #[test]
+    fn it_lists_files_with_an_extension() {
+        let (_temp_dir, dir) = schema_dir();
+
+        let expected = ["accounts.graphql", "products.graphql"];
+        assert_eq!(
+            file_names(&Fs::list_dir(&dir, Some("graphql")).unwrap()),
+            expected
+        );
+        assert_eq!(
+            file_names(&Fs::list_dir(&dir, Some(".graphql")).unwrap()),
+            expected
+        );
+    }
+
+    #[test]
+    fn it_does_not_list_files() {
+        let (_temp_dir, dir) = schema_dir();
+        let file_path = dir.join("products.graphql");
+
+        let err = Fs::list_dir(file_path, None).unwrap_err();
+        assert!(err.to_string().contains("products.graphql"));
+    }
+
+    /// `schema_dir`, with `products/reviews/` nested in it
+    fn nested_schema_dir() -> (TempDir, Utf8PathBuf) {
+        let (temp_dir, dir) = schema_dir();
+        let reviews = dir.join("products").join("reviews");
+        Fs::create_dir_all(&reviews).unwrap();
+        Fs::write_file(dir.join("products").join("inventory.graphql"), "").unwrap();
+        Fs::write_file(reviews.join("reviews.graphql"), "").unwrap();
+        Fs::write_file(reviews.join("notes.txt"), "").unwrap();
+        (temp_dir, dir)
+    }
+
+    fn relative_paths<'a>(dir: &Utf8Path, paths: &'a [Utf8PathBuf]) -> Vec<&'a str> {
+        paths
+            .iter()
+            .map(|path| path.strip_prefix(dir).unwrap().as_str())
+            .collect()
+    }
+
+    #[test]
+    fn it_reads_directories_recursively_in_order() {
+        let (_temp_dir, dir) = nested_schema_dir();
+
+        let paths = Fs::read_dir_recursive(&dir, usize::MAX, None).unwrap();
+        assert_eq!(
+            relative_paths(&dir, &paths),
+            [
+                "README.md",
+                "accounts.graphql",
+                "products/inventory.graphql",
+                "products/reviews/notes.txt",
+                "products/reviews/reviews.graphql",
+                "products.graphql",
+                "reviews.gql",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_stops_reading_directories_at_the_max_depth() {
+        let (_temp_dir, dir) = nested_schema_dir();
+
+        let read = |max_depth| {
+            let paths = Fs::read_dir_recursive(&dir, max_depth, Some("graphql")).unwrap();
+            relative_paths(&dir, &paths)
+                .into_iter()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert!(read(0).is_empty());
+        assert_eq!(read(1), ["accounts.graphql", "products.graphql"]);
+        assert_eq!(
+            read(2),
+            [
+                "accounts.graphql",
+                "products/inventory.graphql",
+                "products.graphql"
+            ]
+        );
+        assert_eq!(read(3).len(), 4);
+    }
+
+    #[test]
+    fn it_reads_files_with_an_extension_recursively() {
+        let (_temp_dir, dir) = nested_schema_dir();
+
+        let paths = Fs::read_dir_recursive(&dir, usize::MAX, Some(".txt")).unwrap();
+        assert_eq!(relative_paths(&dir, &paths), ["products/reviews/notes.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_does_not_follow_symlinked_directories() {
+        let (_temp_dir, dir) = nested_schema_dir();
+        let reviews = dir.join("products").join("reviews");
+        // a link back to the root would loop forever if it was followed
+        std::os::unix::fs::symlink(&dir, reviews.join("loop")).unwrap();
+        std::os::unix::fs::symlink(dir.join("accounts.graphql"), reviews.join("users.graphql"))
+            .unwrap();
+
+        let paths = Fs::read_dir_recursive(&dir, usize::MAX, Some("graphql")).unwrap();
+        assert_eq!(
+            relative_paths(&dir, &paths),
+            [
+                "accounts.graphql",
+                "products/inventory.graphql",
+                "products/reviews/reviews.graphql",
+                "products/reviews/users.graphql",
+                "products.graphql",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_errors_when_the_root_is_not_a_directory() {
+        let (_temp_dir, dir) = schema_dir();
+
+        let err = Fs::read_dir_recursive(dir.join("products.graphql"), 1, None).unwrap_err();
+        assert!(err.to_string().contains("not a directory"));
+    }
 }
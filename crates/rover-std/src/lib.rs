@@ -1,11 +1,23 @@
 mod emoji;
 mod error;
 mod fs;
-mod style;
+mod redact;
+mod snippet;
+mod spinner;
+#[cfg(any(test, feature = "test-env"))]
+pub mod test_env;
 
 pub mod prompt;
+pub mod style;
+pub mod url;
 pub use emoji::Emoji;
 pub use error::RoverStdError;
-pub use fs::Fs;
+pub use fs::{FileWatch, Fs};
+pub use redact::redact;
+pub use snippet::snippet;
+pub use spinner::Spinner;
+pub use style::is_force_color_set;
 pub use style::is_no_color_set;
+pub use style::is_plain;
 pub use style::Style;
+pub use style::StyleTheme;
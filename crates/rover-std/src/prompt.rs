@@ -1,11 +1,29 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::sync::{Once, OnceLock};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+
+use crate::RoverStdError;
+
+/// the number of times an invalid answer is accepted before giving up
+const MAX_ATTEMPTS: usize = 3;
+
+/// The code Rover exits with when the user cancels it, e.g. with Ctrl-C at a
+/// prompt, which is 128 plus the number of SIGINT like shells use
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
 pub fn confirm_delete() -> std::io::Result<bool> {
     prompt_confirm_default_no("Would you like to continue?")
 }
 
 pub fn prompt_confirm_default_no(message: &str) -> std::io::Result<bool> {
     eprintln!("{} [y/N]", message);
+    exit_on_ctrlc();
     let term = console::Term::stdout();
-    let confirm = term.read_line()?;
+    let confirm = term.read_line().map_err(interrupted)?;
     if confirm.to_lowercase() == *"y" {
         Ok(true)
     } else {
@@ -15,11 +33,823 @@ pub fn prompt_confirm_default_no(message: &str) -> std::io::Result<bool> {
 
 pub fn prompt_confirm_default_yes(message: &str) -> std::io::Result<bool> {
     eprintln!("{} [Y/n]", message);
+    exit_on_ctrlc();
     let term = console::Term::stdout();
-    let confirm = term.read_line()?;
+    let confirm = term.read_line().map_err(interrupted)?;
     if confirm.to_lowercase() == *"n" {
         Ok(false)
     } else {
         Ok(true)
     }
 }
+
+/// turns an interrupted read, like Ctrl-C at a prompt, into an error wrapping
+/// [`RoverStdError::Interrupted`] so that it can be told apart from other IO errors
+fn interrupted(error: io::Error) -> io::Error {
+    if error.kind() == io::ErrorKind::Interrupted && !is_interrupted(&error) {
+        io::Error::new(io::ErrorKind::Interrupted, RoverStdError::Interrupted)
+    } else {
+        error
+    }
+}
+
+/// exits with [`INTERRUPTED_EXIT_CODE`] on Ctrl-C from now on. without a
+/// handler SIGINT kills Rover outright, so a read at a prompt never sees it
+fn exit_on_ctrlc() {
+    static HANDLER: Once = Once::new();
+    HANDLER.call_once(|| {
+        let handler = ctrlc::set_handler(|| {
+            eprintln!("\n{}", RoverStdError::Interrupted);
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        });
+        // e.g. `rover dev` has already set up its own
+        if let Err(e) = handler {
+            tracing::debug!("not handling Ctrl-C at prompts: {}", e);
+        }
+    });
+}
+
+/// whether a prompt failed because the user cancelled it, e.g. with Ctrl-C
+pub fn is_interrupted(error: &io::Error) -> bool {
+    error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<RoverStdError>())
+        .map_or(false, |inner| matches!(inner, RoverStdError::Interrupted))
+}
+
+/// the environment variable holding how many seconds to wait for an answer
+/// to a prompt before taking its default
+const PROMPT_TIMEOUT_ENV_VAR: &str = "ROVER_PROMPT_TIMEOUT";
+
+/// how long to wait for an answer to a prompt, read from `ROVER_PROMPT_TIMEOUT`
+/// in seconds. prompts wait forever when it isn't set.
+pub fn timeout_from_env() -> Option<Duration> {
+    let seconds = std::env::var(PROMPT_TIMEOUT_ENV_VAR).ok()?;
+    match seconds.trim().parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(e) => {
+            tracing::warn!(
+                "ignoring ${} because '{}' is not a number of seconds: {}",
+                PROMPT_TIMEOUT_ENV_VAR,
+                seconds,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// stdin to read answers to prompts from, which gives up waiting on an answer
+/// after the timeout in `ROVER_PROMPT_TIMEOUT` if it is set
+pub fn stdin() -> TimeoutReader {
+    stdin_with_timeout(timeout_from_env())
+}
+
+/// stdin, which gives up waiting on a read after `timeout` if there is one.
+/// every reader this returns shares one thread that reads stdin a line at a
+/// time, so a line that arrives after one read has timed out goes to the next
+/// reader rather than to a thread that's no longer listened to.
+pub fn stdin_with_timeout(timeout: Option<Duration>) -> TimeoutReader {
+    static LINES: OnceLock<Receiver<io::Result<Vec<u8>>>> = OnceLock::new();
+    exit_on_ctrlc();
+    let lines = LINES.get_or_init(|| read_lines(io::BufReader::new(io::stdin())));
+    TimeoutReader::from_lines(lines.clone(), timeout)
+}
+
+/// reads `reader` a line at a time on a background thread, which stops at EOF
+/// or the first error
+fn read_lines<R>(mut reader: R) -> Receiver<io::Result<Vec<u8>>>
+where
+    R: BufRead + Send + 'static,
+{
+    let (tx, lines) = crossbeam_channel::unbounded();
+    std::thread::spawn(move || loop {
+        let mut line = Vec::new();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            result => {
+                let failed = result.is_err();
+                if tx.send(result.map(|_| line)).is_err() || failed {
+                    break;
+                }
+            }
+        }
+    });
+    lines
+}
+
+/// Reads lines from a background thread so that waiting on them can time out,
+/// failing with [`io::ErrorKind::TimedOut`] if a line doesn't arrive in time.
+/// The prompts in this module take their default answer when that happens.
+pub struct TimeoutReader {
+    lines: Receiver<io::Result<Vec<u8>>>,
+    timeout: Option<Duration>,
+    line: Vec<u8>,
+    position: usize,
+}
+
+impl TimeoutReader {
+    pub fn new<R>(reader: R, timeout: Duration) -> Self
+    where
+        R: BufRead + Send + 'static,
+    {
+        Self::from_lines(read_lines(reader), Some(timeout))
+    }
+
+    fn from_lines(lines: Receiver<io::Result<Vec<u8>>>, timeout: Option<Duration>) -> Self {
+        Self {
+            lines,
+            timeout,
+            line: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// changes how long later reads wait, with `None` waiting forever
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+}
+
+impl Read for TimeoutReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let read = available.len().min(buf.len());
+        buf[..read].copy_from_slice(&available[..read]);
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl BufRead for TimeoutReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.position >= self.line.len() {
+            self.position = 0;
+            let received = match self.timeout {
+                Some(timeout) => self.lines.recv_timeout(timeout),
+                None => self
+                    .lines
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            self.line = match received {
+                Ok(line) => line?,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!(
+                            "no input was received within {:?}",
+                            self.timeout.unwrap_or_default()
+                        ),
+                    ))
+                }
+                // the reader hit EOF
+                Err(RecvTimeoutError::Disconnected) => Vec::new(),
+            };
+        }
+        Ok(&self.line[self.position..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position += amt;
+    }
+}
+
+/// reads an answer to a prompt, returning `None` if the reader timed out
+fn read_answer(reader: &mut impl BufRead, writer: &mut impl Write) -> io::Result<Option<String>> {
+    let mut response = String::new();
+    match reader.read_line(&mut response) {
+        Ok(_) => Ok(Some(response)),
+        Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+            tracing::info!("prompt timed out: {}", e);
+            writeln!(writer)?;
+            writeln!(
+                writer,
+                "No answer was given in time, so the default was used."
+            )?;
+            Ok(None)
+        }
+        Err(e) => Err(interrupted(e)),
+    }
+}
+
+/// asks a yes/no question on stderr and reads the answer from stdin
+pub fn confirm(message: &str, default: bool) -> io::Result<bool> {
+    confirm_with_io(message, default, &mut stdin(), &mut io::stderr())
+}
+
+/// asks a yes/no question, reading the answer from `reader` and writing
+/// the question to `writer`. `y`/`yes` and `n`/`no` are accepted regardless
+/// of case, and anything else (including an empty line or EOF) is `default`.
+pub fn confirm_with_io(
+    message: &str,
+    default: bool,
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+) -> io::Result<bool> {
+    let suffix = if default { "[Y/n]" } else { "[y/N]" };
+    write!(writer, "{} {} ", message, suffix)?;
+    writer.flush()?;
+    let Some(response) = read_answer(reader, writer)? else {
+        return Ok(default);
+    };
+    Ok(match response.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    })
+}
+
+/// asks for a value on stderr and reads it from stdin, falling back to `default`
+pub fn input_with_default(message: &str, default: &str) -> io::Result<String> {
+    input_with_default_with_io(message, default, &mut stdin(), &mut io::stderr())
+}
+
+/// asks for a value, reading it from `reader` and writing the question to
+/// `writer`, with `default` shown in brackets unless it's empty. the answer is
+/// trimmed, and a blank one (including EOF) is `default`.
+pub fn input_with_default_with_io(
+    message: &str,
+    default: &str,
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+) -> io::Result<String> {
+    if default.is_empty() {
+        write!(writer, "{} ", message)?;
+    } else {
+        write!(writer, "{} [{}] ", message, default)?;
+    }
+    writer.flush()?;
+    let Some(response) = read_answer(reader, writer)? else {
+        return Ok(default.to_string());
+    };
+    match response.trim() {
+        "" => Ok(default.to_string()),
+        response => Ok(response.to_string()),
+    }
+}
+
+/// asks the user to pick one of `options` on stderr, reading the answer from stdin
+pub fn select<T: Display>(message: &str, options: &[T], default: usize) -> io::Result<usize> {
+    select_with_io(message, options, default, &mut stdin(), &mut io::stderr())
+}
+
+/// asks the user to pick one of `options` by its number, returning the index
+/// of the chosen option. a blank answer picks `default`, and invalid answers
+/// are re-prompted up to three times before erroring.
+pub fn select_with_io<T: Display>(
+    message: &str,
+    options: &[T],
+    default: usize,
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+) -> io::Result<usize> {
+    if default >= options.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "the default selection {} is out of range for {} options",
+                default,
+                options.len()
+            ),
+        ));
+    }
+
+    writeln!(writer, "{}", message)?;
+    for (i, option) in options.iter().enumerate() {
+        writeln!(writer, "  {}) {}", i + 1, option)?;
+    }
+
+    for _ in 0..MAX_ATTEMPTS {
+        write!(
+            writer,
+            "Enter a number between 1 and {} [{}]: ",
+            options.len(),
+            default + 1
+        )?;
+        writer.flush()?;
+        let Some(response) = read_answer(reader, writer)? else {
+            return Ok(default);
+        };
+        let response = response.trim();
+        if response.is_empty() {
+            return Ok(default);
+        }
+        match response.parse::<usize>() {
+            Ok(choice) if (1..=options.len()).contains(&choice) => return Ok(choice - 1),
+            _ => writeln!(writer, "'{}' is not a valid selection.", response)?,
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+            "no valid selection was made after {} attempts",
+            MAX_ATTEMPTS
+        ),
+    ))
+}
+
+/// asks the user to pick any number of `options` on stderr, reading the answers from stdin
+pub fn multiselect<T: Display>(message: &str, options: &[T]) -> io::Result<Vec<usize>> {
+    multiselect_with_io(message, options, &mut stdin(), &mut io::stderr())
+}
+
+/// asks the user to pick any number of `options`, returning the indexes of
+/// the chosen ones in order. each answer is a comma-separated list of numbers
+/// and ranges like `1,3-5`, which toggles those options on or off, and a blank
+/// answer finishes the selection. invalid answers are explained and re-prompted
+/// up to three times in a row before erroring.
+pub fn multiselect_with_io<T: Display>(
+    message: &str,
+    options: &[T],
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+) -> io::Result<Vec<usize>> {
+    let mut selected = BTreeSet::new();
+    let mut invalid_answers = 0;
+    writeln!(writer, "{}", message)?;
+    while invalid_answers < MAX_ATTEMPTS {
+        for (i, option) in options.iter().enumerate() {
+            let mark = if selected.contains(&i) { 'x' } else { ' ' };
+            writeln!(writer, "  [{}] {}) {}", mark, i + 1, option)?;
+        }
+        write!(
+            writer,
+            "Enter numbers or ranges to toggle, like 1,3-5, or nothing to finish: "
+        )?;
+        writer.flush()?;
+        let Some(response) = read_answer(reader, writer)? else {
+            break;
+        };
+        let response = response.trim();
+        if response.is_empty() {
+            break;
+        }
+        match parse_selection(response, options.len()) {
+            Ok(toggled) => {
+                invalid_answers = 0;
+                for i in toggled {
+                    if !selected.remove(&i) {
+                        selected.insert(i);
+                    }
+                }
+            }
+            Err(explanation) => {
+                invalid_answers += 1;
+                writeln!(writer, "{}", explanation)?;
+            }
+        }
+    }
+    if invalid_answers == MAX_ATTEMPTS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "no valid selection was made after {} attempts",
+                MAX_ATTEMPTS
+            ),
+        ));
+    }
+    Ok(selected.into_iter().collect())
+}
+
+/// the indexes that an answer like `1,3-5` picks out of `len` options.
+/// every index is only returned once, even if the answer repeats it,
+/// and nothing is returned if any part of the answer is invalid.
+fn parse_selection(answer: &str, len: usize) -> Result<BTreeSet<usize>, String> {
+    let number = |token: &str, part: &str| match part.trim().parse::<usize>() {
+        Ok(n) if (1..=len).contains(&n) => Ok(n - 1),
+        Ok(_) => Err(format!(
+            "'{}' is not a valid selection: there are only options 1 to {}.",
+            token, len
+        )),
+        Err(_) => Err(format!(
+            "'{}' is not a valid selection: enter a number like 3 or a range like 3-5.",
+            token
+        )),
+    };
+    let mut indexes = BTreeSet::new();
+    for token in answer.split(',').map(str::trim) {
+        if token.is_empty() {
+            continue;
+        }
+        match token.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (number(token, start)?, number(token, end)?);
+                if start > end {
+                    return Err(format!(
+                        "'{}' is not a valid selection: a range has to start with the lower number.",
+                        token
+                    ));
+                }
+                indexes.extend(start..=end);
+            }
+            None => {
+                indexes.insert(number(token, token)?);
+            }
+        }
+    }
+    Ok(indexes)
+}
+
+/// Somewhere a secret can be read from without it being shown on screen
+pub trait SecretInput {
+    /// reads one line, without its trailing newline
+    fn read_secret_line(&mut self) -> io::Result<String>;
+}
+
+/// An interactive terminal, which turns echo off while a line is read
+/// and back on afterward, even if reading fails
+pub struct Terminal(pub console::Term);
+
+impl SecretInput for Terminal {
+    fn read_secret_line(&mut self) -> io::Result<String> {
+        self.0.read_secure_line()
+    }
+}
+
+/// Input that isn't a terminal, like a pipe, which has no echo to turn off
+/// and is read one line at a time as usual
+pub struct Piped<R: BufRead>(pub R);
+
+impl<R: BufRead> SecretInput for Piped<R> {
+    fn read_secret_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        self.0.read_line(&mut line)?;
+        Ok(line.trim_end_matches(['\n', '\r']).to_string())
+    }
+}
+
+/// asks for a secret such as an API key on stderr. in a terminal the answer
+/// is not echoed, and otherwise a line is read from stdin so that secrets
+/// can still be piped in
+pub fn password(message: &str) -> io::Result<String> {
+    if io::stdin().is_terminal() && io::stderr().is_terminal() {
+        password_with_io(
+            message,
+            &mut Terminal(console::Term::stderr()),
+            &mut io::stderr(),
+        )
+    } else {
+        password_with_io(message, &mut Piped(io::stdin().lock()), &mut io::stderr())
+    }
+}
+
+/// asks for a secret, writing `message` to `writer` and reading the answer from `input`
+pub fn password_with_io(
+    message: &str,
+    input: &mut impl SecretInput,
+    writer: &mut impl Write,
+) -> io::Result<String> {
+    write!(writer, "{} ", message)?;
+    writer.flush()?;
+    input.read_secret_line().map_err(interrupted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::InterruptedInput;
+
+    /// stdin that never produces anything
+    struct SilentInput;
+
+    impl Read for SilentInput {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            loop {
+                std::thread::park();
+            }
+        }
+    }
+
+    fn silent_reader() -> TimeoutReader {
+        TimeoutReader::new(io::BufReader::new(SilentInput), Duration::from_millis(50))
+    }
+
+    #[test]
+    fn it_takes_the_default_when_a_confirmation_times_out() {
+        for default in [true, false] {
+            let mut output = Vec::new();
+            let confirmed =
+                confirm_with_io("Continue?", default, &mut silent_reader(), &mut output).unwrap();
+            assert_eq!(confirmed, default);
+            assert!(String::from_utf8(output)
+                .unwrap()
+                .contains("No answer was given in time"));
+        }
+    }
+
+    #[test]
+    fn it_takes_the_default_when_a_selection_times_out() {
+        let selected = select_with_io(
+            "Pick one",
+            &["a", "b", "c"],
+            1,
+            &mut silent_reader(),
+            &mut io::sink(),
+        )
+        .unwrap();
+        assert_eq!(selected, 1);
+    }
+
+    #[test]
+    fn it_reads_answers_that_arrive_before_the_timeout() {
+        let mut reader = TimeoutReader::new("n\ny\n".as_bytes(), Duration::from_secs(10));
+        assert!(!confirm_with_io("Continue?", true, &mut reader, &mut io::sink()).unwrap());
+        assert!(confirm_with_io("Continue?", false, &mut reader, &mut io::sink()).unwrap());
+        // EOF takes the default without waiting for the timeout
+        assert!(confirm_with_io("Continue?", true, &mut reader, &mut io::sink()).unwrap());
+    }
+
+    #[test]
+    fn it_gives_a_line_that_arrives_after_a_timeout_to_the_next_prompt() {
+        let (tx, lines) = crossbeam_channel::unbounded();
+        let timeout = Some(Duration::from_millis(50));
+
+        let mut first = TimeoutReader::from_lines(lines.clone(), timeout);
+        assert!(confirm_with_io("Continue?", true, &mut first, &mut io::sink()).unwrap());
+        tx.send(Ok(b"products\n".to_vec())).unwrap();
+        let mut second = TimeoutReader::from_lines(lines, timeout);
+        let answer =
+            input_with_default_with_io("Subgraph?", "accounts", &mut second, &mut io::sink());
+        assert_eq!(answer.unwrap(), "products");
+    }
+
+    #[test]
+    fn it_shares_one_input_between_prompts_in_a_row() {
+        let lines = read_lines("n\nproducts\n".as_bytes());
+        let timeout = Some(Duration::from_secs(10));
+
+        let mut first = TimeoutReader::from_lines(lines.clone(), timeout);
+        assert!(!confirm_with_io("Continue?", true, &mut first, &mut io::sink()).unwrap());
+        let mut second = TimeoutReader::from_lines(lines, timeout);
+        let answer =
+            input_with_default_with_io("Subgraph?", "accounts", &mut second, &mut io::sink());
+        assert_eq!(answer.unwrap(), "products");
+    }
+
+    fn assert_interrupted(error: io::Error) {
+        assert!(is_interrupted(&error), "{:?}", error);
+        assert!(matches!(
+            error.into_inner().unwrap().downcast::<RoverStdError>(),
+            Ok(inner) if matches!(*inner, RoverStdError::Interrupted)
+        ));
+    }
+
+    #[test]
+    fn it_reports_interrupted_prompts() {
+        assert_interrupted(
+            confirm_with_io("Continue?", true, &mut InterruptedInput, &mut io::sink()).unwrap_err(),
+        );
+        assert_interrupted(
+            select_with_io(
+                "Pick one",
+                &["a", "b"],
+                0,
+                &mut InterruptedInput,
+                &mut io::sink(),
+            )
+            .unwrap_err(),
+        );
+        assert_interrupted(
+            password_with_io(
+                "Enter your API key:",
+                &mut InterruptedInput,
+                &mut io::sink(),
+            )
+            .unwrap_err(),
+        );
+    }
+
+    #[test]
+    fn it_does_not_mistake_other_errors_for_interrupts() {
+        assert!(!is_interrupted(&io::ErrorKind::Interrupted.into()));
+        assert!(!is_interrupted(&io::Error::new(
+            io::ErrorKind::Other,
+            RoverStdError::EmptyFile {
+                empty_file: "schema.graphql".to_string()
+            }
+        )));
+    }
+
+    #[test]
+    fn it_reads_a_piped_password() {
+        let mut output = Vec::new();
+        let password = password_with_io(
+            "Enter your API key:",
+            &mut Piped("user:abc123\r\nsomething else\n".as_bytes()),
+            &mut output,
+        )
+        .unwrap();
+
+        assert_eq!(password, "user:abc123");
+        assert_eq!(String::from_utf8(output).unwrap(), "Enter your API key: ");
+    }
+
+    #[test]
+    fn it_reads_an_empty_password_at_eof() {
+        let password = password_with_io(
+            "Enter your API key:",
+            &mut Piped("".as_bytes()),
+            &mut io::sink(),
+        )
+        .unwrap();
+        assert_eq!(password, "");
+    }
+
+    #[test]
+    fn it_never_writes_the_password() {
+        struct HiddenInput;
+        impl SecretInput for HiddenInput {
+            fn read_secret_line(&mut self) -> io::Result<String> {
+                Ok("user:abc123".to_string())
+            }
+        }
+
+        let mut output = Vec::new();
+        let password =
+            password_with_io("Enter your API key:", &mut HiddenInput, &mut output).unwrap();
+
+        assert_eq!(password, "user:abc123");
+        assert!(!String::from_utf8(output).unwrap().contains("abc123"));
+    }
+
+    fn confirm_with_input(input: &str, default: bool) -> (bool, String) {
+        let mut reader = input.as_bytes();
+        let mut writer: Vec<u8> = Vec::new();
+        let answer = confirm_with_io("Continue?", default, &mut reader, &mut writer).unwrap();
+        (answer, String::from_utf8(writer).unwrap())
+    }
+
+    #[test]
+    fn it_defaults_to_no() {
+        let (answer, output) = confirm_with_input("\n", false);
+        assert!(!answer);
+        assert_eq!(output, "Continue? [y/N] ");
+
+        assert!(!confirm_with_input("", false).0);
+        assert!(!confirm_with_input("maybe\n", false).0);
+        assert!(!confirm_with_input("no\n", false).0);
+        assert!(confirm_with_input("Yes\n", false).0);
+        assert!(confirm_with_input(" y \n", false).0);
+    }
+
+    #[test]
+    fn it_defaults_to_yes() {
+        let (answer, output) = confirm_with_input("\n", true);
+        assert!(answer);
+        assert_eq!(output, "Continue? [Y/n] ");
+
+        assert!(confirm_with_input("", true).0);
+        assert!(confirm_with_input("maybe\n", true).0);
+        assert!(confirm_with_input("yes\n", true).0);
+        assert!(!confirm_with_input("NO\n", true).0);
+        assert!(!confirm_with_input(" n \n", true).0);
+    }
+
+    fn input_with_input(input: &str, default: &str) -> (String, String) {
+        let mut reader = input.as_bytes();
+        let mut writer: Vec<u8> = Vec::new();
+        let answer =
+            input_with_default_with_io("Routing URL:", default, &mut reader, &mut writer).unwrap();
+        (answer, String::from_utf8(writer).unwrap())
+    }
+
+    #[test]
+    fn it_takes_the_default_input_on_blank() {
+        let (answer, output) = input_with_input("\n", "http://localhost:4001");
+        assert_eq!(answer, "http://localhost:4001");
+        assert_eq!(output, "Routing URL: [http://localhost:4001] ");
+
+        assert_eq!(
+            input_with_input("", "http://localhost:4001").0,
+            "http://localhost:4001"
+        );
+        assert_eq!(input_with_input("  \n", "").0, "");
+        assert_eq!(input_with_input("\n", "").1, "Routing URL: ");
+    }
+
+    #[test]
+    fn it_takes_typed_input_over_the_default() {
+        assert_eq!(
+            input_with_input("https://accounts.example.com\n", "http://localhost:4001").0,
+            "https://accounts.example.com"
+        );
+    }
+
+    #[test]
+    fn it_trims_typed_input() {
+        assert_eq!(
+            input_with_input("  https://accounts.example.com \r\n", "").0,
+            "https://accounts.example.com"
+        );
+    }
+
+    #[test]
+    fn it_takes_the_default_input_when_it_times_out() {
+        let mut reader = silent_reader();
+        let answer =
+            input_with_default_with_io("Routing URL:", "none", &mut reader, &mut io::sink())
+                .unwrap();
+        assert_eq!(answer, "none");
+    }
+
+    fn select_with_input(input: &str) -> (io::Result<usize>, String) {
+        let mut reader = input.as_bytes();
+        let mut writer: Vec<u8> = Vec::new();
+        let choice = select_with_io(
+            "Pick a variant",
+            &["current", "staging", "prod"],
+            1,
+            &mut reader,
+            &mut writer,
+        );
+        (choice, String::from_utf8(writer).unwrap())
+    }
+
+    #[test]
+    fn it_selects_an_option_by_number() {
+        let (choice, output) = select_with_input("3\n");
+        assert_eq!(choice.unwrap(), 2);
+        assert!(output.starts_with("Pick a variant\n  1) current\n  2) staging\n  3) prod\n"));
+    }
+
+    #[test]
+    fn it_selects_the_default_on_blank() {
+        assert_eq!(select_with_input("\n").0.unwrap(), 1);
+        assert_eq!(select_with_input("").0.unwrap(), 1);
+    }
+
+    #[test]
+    fn it_retries_invalid_selections() {
+        let (choice, output) = select_with_input("0\nabc\n1\n");
+        assert_eq!(choice.unwrap(), 0);
+        assert!(output.contains("'0' is not a valid selection."));
+        assert!(output.contains("'abc' is not a valid selection."));
+    }
+
+    #[test]
+    fn it_errors_after_three_invalid_selections() {
+        let (choice, _) = select_with_input("4\n5\n6\n1\n");
+        assert_eq!(choice.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    fn multiselect_with_input(input: &str) -> (io::Result<Vec<usize>>, String) {
+        let mut reader = input.as_bytes();
+        let mut writer: Vec<u8> = Vec::new();
+        let choices = multiselect_with_io(
+            "Pick subgraphs",
+            &["accounts", "products", "reviews", "inventory", "shipping"],
+            &mut reader,
+            &mut writer,
+        );
+        (choices, String::from_utf8(writer).unwrap())
+    }
+
+    #[test]
+    fn it_multiselects_a_comma_separated_list() {
+        let (choices, output) = multiselect_with_input("1, 3,5\n\n");
+        assert_eq!(choices.unwrap(), vec![0, 2, 4]);
+        assert!(output.starts_with("Pick subgraphs\n  [ ] 1) accounts\n"));
+        assert!(output.contains("  [x] 3) reviews\n  [ ] 4) inventory\n  [x] 5) shipping\n"));
+    }
+
+    #[test]
+    fn it_multiselects_ranges() {
+        let (choices, _) = multiselect_with_input("1,3-5\n");
+        assert_eq!(choices.unwrap(), vec![0, 2, 3, 4]);
+    }
+
+    #[test]
+    fn it_toggles_each_option_once_per_answer() {
+        // the overlap only counts once, so 3 stays selected
+        let (choices, _) = multiselect_with_input("2-4,3,3-3\n");
+        assert_eq!(choices.unwrap(), vec![1, 2, 3]);
+
+        // a later answer toggles options back off
+        let (choices, output) = multiselect_with_input("1-3\n2\n\n");
+        assert_eq!(choices.unwrap(), vec![0, 2]);
+        assert!(output.contains("  [x] 2) products\n"));
+    }
+
+    #[test]
+    fn it_selects_nothing_on_a_blank_answer() {
+        assert!(multiselect_with_input("\n").0.unwrap().is_empty());
+        assert!(multiselect_with_input("").0.unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_explains_invalid_multiselect_answers() {
+        let (choices, output) = multiselect_with_input("1,x\n6\n2\n4-2\n\n");
+        assert_eq!(choices.unwrap(), vec![1]);
+        assert!(output.contains("'x' is not a valid selection: enter a number like 3"));
+        assert!(output.contains("'6' is not a valid selection: there are only options 1 to 5."));
+        assert!(output.contains("'4-2' is not a valid selection: a range has to start"));
+    }
+
+    #[test]
+    fn it_errors_after_three_invalid_multiselect_answers() {
+        let (choices, _) = multiselect_with_input("0\n1-9\n-\n1\n");
+        assert_eq!(choices.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+}
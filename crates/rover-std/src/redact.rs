@@ -0,0 +1,75 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// what every redacted secret is replaced with
+const REDACTED: &str = "****";
+
+/// Apollo API keys look like `user:gh.1234:abcd` or `service:my-graph:abcd`.
+/// the kind and id are kept so it's still clear which key was used.
+fn api_key_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"\b((?:user|service|graph):[^\s:]+:)[A-Za-z0-9_\-]{8,}").unwrap()
+    })
+}
+
+/// headers and fields that carry credentials, followed by their value
+fn sensitive_field_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r#"(?i)\b((?:x-api-key|proxy-authorization|authorization|api_key|apollo_key)["']?\s*[:=]\s*["']?(?:bearer\s+|basic\s+)?)[^\s"',}]+"#,
+        )
+        .unwrap()
+    })
+}
+
+/// Replaces anything in `s` that looks like a secret, such as an Apollo API key
+/// or the value of an `x-api-key` or `Authorization` header, with `****`.
+pub fn redact(s: &str) -> String {
+    let s = api_key_pattern().replace_all(s, format!("${{1}}{}", REDACTED));
+    sensitive_field_pattern()
+        .replace_all(&s, format!("${{1}}{}", REDACTED))
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::redact;
+
+    #[test]
+    fn it_redacts_user_api_keys() {
+        assert_eq!(
+            redact("using user:gh.EverlastingGobstopper:7nZ1b_QzR2xW-9kTy4Fq to authenticate"),
+            "using user:gh.EverlastingGobstopper:**** to authenticate"
+        );
+    }
+
+    #[test]
+    fn it_redacts_graph_api_keys() {
+        assert_eq!(
+            redact(r#"{"key": "service:my-graph:a1b2c3d4e5f6g7h8"}"#),
+            r#"{"key": "service:my-graph:****"}"#
+        );
+    }
+
+    #[test]
+    fn it_redacts_credential_headers() {
+        assert_eq!(
+            redact(r#"{"x-api-key": "not-an-apollo-key", "accept": "*/*"}"#),
+            r#"{"x-api-key": "****", "accept": "*/*"}"#
+        );
+        assert_eq!(
+            redact("Authorization: Bearer abc.def.ghi"),
+            "Authorization: Bearer ****"
+        );
+        assert_eq!(redact("APOLLO_KEY=secret"), "APOLLO_KEY=****");
+    }
+
+    #[test]
+    fn it_leaves_everything_else_alone() {
+        let log = "Publishing \ntype Query { me: User }\n to my-graph@current";
+        assert_eq!(redact(log), log);
+    }
+}
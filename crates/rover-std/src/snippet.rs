@@ -0,0 +1,122 @@
+use crate::Style;
+
+/// how many lines are shown above and below the highlighted one
+const CONTEXT_LINES: usize = 2;
+
+/// Renders the lines of `source` around `line`, with `line` highlighted and a
+/// caret under `column`, so an error can point at exactly where it was found.
+/// `line` and `column` start from 1, like they do in editors.
+///
+/// ```text
+/// 2 | type Query {
+/// 3 |   me: User
+/// 4 |   name String
+///   |        ^
+/// 5 | }
+/// ```
+///
+/// The highlighted line and caret are colored with [`Style`], so they're
+/// plain text when color is turned off with `NO_COLOR`.
+pub fn snippet(source: &str, line: usize, column: usize) -> String {
+    let lines: Vec<&str> = source
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    let line = line.clamp(1, lines.len());
+    let first = line.saturating_sub(CONTEXT_LINES).max(1);
+    let last = (line + CONTEXT_LINES).min(lines.len());
+    let gutter_width = last.to_string().len();
+
+    let mut rendered = Vec::new();
+    for number in first..=last {
+        let text = lines[number - 1];
+        if number == line {
+            rendered.push(format!(
+                "{:>width$} | {}",
+                number,
+                Style::Failure.paint(text),
+                width = gutter_width
+            ));
+            // tabs are kept so the caret lines up however wide they're shown
+            let padding: String = text
+                .chars()
+                .take(column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            rendered.push(format!(
+                "{:>width$} | {}{}",
+                "",
+                padding,
+                Style::ErrorPrefix.paint("^"),
+                width = gutter_width
+            ));
+        } else {
+            rendered.push(format!(
+                "{:>width$} | {}",
+                number,
+                text,
+                width = gutter_width
+            ));
+        }
+    }
+    rendered.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::snippet;
+    use crate::test_env::with_env_vars;
+
+    const SDL: &str = "type Query {\n  me: User\n  name String\n}\n\ntype User {\n  id: ID!\n}";
+
+    fn plain_snippet(source: &str, line: usize, column: usize) -> String {
+        with_env_vars(&[("NO_COLOR", "1")], || snippet(source, line, column))
+    }
+
+    #[test]
+    fn it_puts_the_caret_under_the_column() {
+        assert_eq!(
+            plain_snippet(SDL, 3, 8),
+            "1 | type Query {\n2 |   me: User\n3 |   name String\n  |        ^\n4 | }\n5 | "
+        );
+    }
+
+    #[test]
+    fn it_keeps_tabs_so_the_caret_lines_up() {
+        assert_eq!(
+            plain_snippet("type Query {\n\tname String\n}", 2, 7),
+            "1 | type Query {\n2 | \tname String\n  | \t     ^\n3 | }"
+        );
+    }
+
+    #[test]
+    fn it_shows_fewer_lines_near_the_start_and_end() {
+        assert_eq!(
+            plain_snippet(SDL, 1, 1),
+            "1 | type Query {\n  | ^\n2 |   me: User\n3 |   name String"
+        );
+        assert_eq!(
+            plain_snippet(SDL, 8, 2),
+            "6 | type User {\n7 |   id: ID!\n8 | }\n  |  ^"
+        );
+    }
+
+    #[test]
+    fn it_widens_the_gutter_for_longer_line_numbers() {
+        let sdl = (1..=10)
+            .map(|n| format!("scalar S{}", n))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(
+            plain_snippet(&sdl, 9, 8),
+            " 7 | scalar S7\n 8 | scalar S8\n 9 | scalar S9\n   |        ^\n10 | scalar S10"
+        );
+    }
+
+    #[test]
+    fn it_highlights_the_line_and_caret() {
+        let colored = with_env_vars(&[("CLICOLOR_FORCE", "1")], || snippet(SDL, 3, 8));
+        assert!(colored.contains("\u{1b}[31m  name String\u{1b}[0m"));
+        assert!(colored.contains("^\u{1b}[0m"));
+    }
+}
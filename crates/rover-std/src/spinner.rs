@@ -0,0 +1,132 @@
+use std::{
+    io::IsTerminal,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use console::Term;
+
+use crate::is_plain;
+
+const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const FRAME_INTERVAL: Duration = Duration::from_millis(80);
+
+/// An animated spinner shown on stderr while something slow happens,
+/// like a network call, so that rover doesn't look frozen.
+///
+/// It's only drawn when stderr is a terminal, so it never ends up in logs or
+/// CI output, and it clears its line when it's stopped or dropped.
+#[derive(Debug)]
+pub struct Spinner {
+    running: Option<Running>,
+}
+
+#[derive(Debug)]
+struct Running {
+    stopped: Arc<AtomicBool>,
+    /// held while a frame is drawn, so that nothing else is printed halfway through one
+    drawing: Arc<Mutex<()>>,
+    handle: JoinHandle<()>,
+}
+
+impl Spinner {
+    /// starts a spinner next to `message` if `show` is true and stderr is a terminal.
+    /// passing `!quiet` as `show` keeps it from being drawn with `--quiet`,
+    /// and it's never drawn with `--plain`, since it's redrawn with escape codes.
+    pub fn start(message: impl Into<String>, show: bool) -> Self {
+        Self::start_with(
+            message,
+            show && !is_plain(),
+            std::io::stderr().is_terminal(),
+        )
+    }
+
+    fn start_with(message: impl Into<String>, show: bool, is_terminal: bool) -> Self {
+        if !show || !is_terminal {
+            return Self { running: None };
+        }
+        let message = message.into();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let drawing = Arc::new(Mutex::new(()));
+        let handle = thread::spawn({
+            let stopped = stopped.clone();
+            let drawing = drawing.clone();
+            move || {
+                let term = Term::stderr();
+                for frame in FRAMES.iter().cycle() {
+                    {
+                        let _drawing = drawing.lock().unwrap_or_else(|e| e.into_inner());
+                        if stopped.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let _ = term.write_str(&format!("\r{frame} {message}"));
+                    }
+                    thread::sleep(FRAME_INTERVAL);
+                }
+                let _ = term.clear_line();
+            }
+        });
+        Self {
+            running: Some(Running {
+                stopped,
+                drawing,
+                handle,
+            }),
+        }
+    }
+
+    /// whether the spinner is being drawn
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// clears the spinner's line and keeps it from being drawn while `f` runs,
+    /// so that `f` can print to stderr without the output getting mixed up
+    pub fn suspend<T>(&self, f: impl FnOnce() -> T) -> T {
+        match &self.running {
+            Some(running) => {
+                let _drawing = running.drawing.lock().unwrap_or_else(|e| e.into_inner());
+                let _ = Term::stderr().clear_line();
+                f()
+            }
+            None => f(),
+        }
+    }
+
+    /// stops the spinner and clears its line
+    pub fn stop(self) {}
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        if let Some(running) = self.running.take() {
+            running.stopped.store(true, Ordering::Relaxed);
+            let _ = running.handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_never_starts_without_a_terminal() {
+        assert!(!Spinner::start_with("Publishing", true, false).is_running());
+    }
+
+    #[test]
+    fn it_does_not_start_when_hidden() {
+        assert!(!Spinner::start_with("Publishing", false, true).is_running());
+    }
+
+    #[test]
+    fn it_runs_suspended_work_without_a_terminal() {
+        let spinner = Spinner::start_with("Publishing", true, false);
+        assert_eq!(spinner.suspend(|| 42), 42);
+    }
+}
@@ -1,14 +1,17 @@
-use console::style;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 pub enum Style {
     Link,    // URLs and graph refs
     Command, // Commands, inline code, env variable keys, and profile names
+    Dimmed,  // secondary details, like byte counts, that shouldn't compete with the rest
     Failure,
     Path, // File paths
     Pending,
-    HintPrefix,    // "HINT:" text
-    WarningPrefix, // "WARN:" text
-    ErrorPrefix,   // "ERROR:", "error:", and "error[code]:" text
+    HintPrefix,        // "HINT:" text
+    WarningPrefix,     // "WARN:" text
+    WarningLinePrefix, // "WARN:" text before a message painted with `Warning`
+    Warning,           // the message following "WARN:"
+    ErrorPrefix,       // "ERROR:", "error:", and "error[code]:" text
     Heading,
     CallToAction,
     WhoAmIKey,
@@ -24,35 +27,539 @@ impl Style {
     pub fn paint<S: AsRef<str>>(&self, message: S) -> String {
         let message_ref = message.as_ref();
 
-        if is_no_color_set() {
+        let color_mode = ColorMode::from_env();
+        if color_mode == ColorMode::Never {
             return message_ref.to_string();
         }
 
-        match &self {
-            Style::Link | Style::PersistedQueryList | Style::Version => style(message_ref).cyan(),
-            Style::Command | Style::TotalOperationCount => style(message_ref).yellow(),
-            Style::CallToAction => style(message_ref).yellow().italic(),
-            Style::Failure => style(message_ref).red(),
-            Style::WhoAmIKey | Style::NewOperationCount => style(message_ref).green(),
-            Style::HintPrefix => style(message_ref).cyan().bold(),
-            Style::WarningPrefix => style(message_ref).red(),
-            Style::ErrorPrefix => style(message_ref).red().bold(),
-            Style::Variant => style(message_ref).white().bold(),
-            Style::Path | Style::Heading => style(message_ref).bold(),
-            Style::Pending => style(message_ref).yellow(),
-            Style::Success => style(message_ref).green(),
+        let styled = match self.theme_style(StyleTheme::from_env()) {
+            Some(theme_style) => theme_style.apply_to(message_ref),
+            None => return message_ref.to_string(),
+        };
+
+        let painted = match color_mode {
+            ColorMode::Always => styled.force_styling(true).to_string(),
+            _ => styled.to_string(),
+        };
+
+        let is_styled = color_mode == ColorMode::Always || console::colors_enabled();
+        if matches!(self, Style::Link) && is_styled && is_hyperlink_set() && is_web_url(message_ref)
+        {
+            hyperlink(message_ref, &painted)
+        } else {
+            painted
+        }
+    }
+
+    /// [`Style::paint`]s `message` only if `condition` holds, like stdout
+    /// being a terminal, and leaves it as plain text otherwise, so call sites
+    /// don't need to branch. `NO_COLOR` turns the styling off either way.
+    pub fn paint_if<S: AsRef<str>>(&self, condition: bool, message: S) -> String {
+        if condition {
+            self.paint(message)
+        } else {
+            message.as_ref().to_string()
+        }
+    }
+
+    /// Removes every ANSI escape sequence from `text`, like the colors from
+    /// [`Style::paint`] and the hyperlinks around URLs, leaving the plain
+    /// text that was styled. A sequence cut off at the end of `text` is
+    /// removed too.
+    pub fn strip(text: &str) -> String {
+        let mut plain = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\u{1b}' {
+                plain.push(c);
+                continue;
+            }
+            match chars.next() {
+                // CSI, like colors: parameters up to a final byte from `@` to `~`
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC, like hyperlinks: up to a BEL or an ESC `\`
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' {
+                            break;
+                        }
+                        if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // any other escape is a single character
+                _ => {}
+            }
+        }
+        plain
+    }
+
+    /// the colors and attributes for this style in the given theme,
+    /// or `None` if it should be printed as plain text
+    fn theme_style(&self, theme: StyleTheme) -> Option<console::Style> {
+        let base = console::Style::new();
+        let theme_style = match theme {
+            StyleTheme::Monochrome => return None,
+            StyleTheme::Dark => match &self {
+                Style::Link | Style::PersistedQueryList | Style::Version => base.cyan(),
+                Style::Command | Style::TotalOperationCount => base.yellow(),
+                Style::CallToAction => base.yellow().italic(),
+                Style::Failure => base.red(),
+                Style::WhoAmIKey | Style::NewOperationCount => base.green(),
+                Style::HintPrefix => base.cyan().bold(),
+                Style::WarningPrefix => base.red(),
+                Style::WarningLinePrefix => base.yellow().bold(),
+                Style::Warning => base.yellow(),
+                Style::ErrorPrefix => base.red().bold(),
+                Style::Variant => base.white().bold(),
+                Style::Path | Style::Heading => base.bold(),
+                Style::Pending => base.yellow(),
+                Style::Success => base.green(),
+                Style::Dimmed => base.dim(),
+            },
+            // cyan, yellow and white are hard to read on a light background,
+            // so they are swapped for darker colors
+            StyleTheme::Light => match &self {
+                Style::Link | Style::PersistedQueryList | Style::Version => base.blue(),
+                Style::Command | Style::TotalOperationCount => base.magenta(),
+                Style::CallToAction => base.magenta().italic(),
+                Style::Failure => base.red(),
+                Style::WhoAmIKey | Style::NewOperationCount => base.green(),
+                Style::HintPrefix => base.blue().bold(),
+                Style::WarningPrefix => base.red(),
+                Style::WarningLinePrefix => base.color256(130).bold(),
+                Style::Warning => base.color256(130),
+                Style::ErrorPrefix => base.red().bold(),
+                Style::Variant => base.black().bold(),
+                Style::Path | Style::Heading => base.bold(),
+                Style::Pending => base.magenta(),
+                Style::Success => base.green(),
+                Style::Dimmed => base.dim(),
+            },
+        };
+        Some(theme_style)
+    }
+}
+
+/// The color palette used by [`Style`], selected with the `ROVER_THEME`
+/// environment variable (`dark`, `light` or `monochrome`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StyleTheme {
+    #[default]
+    Dark,
+    Light,
+    Monochrome,
+}
+
+impl StyleTheme {
+    pub fn from_env() -> Self {
+        match std::env::var("ROVER_THEME") {
+            Ok(theme) => theme.parse().unwrap_or_default(),
+            Err(_) => StyleTheme::default(),
+        }
+    }
+}
+
+impl std::str::FromStr for StyleTheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dark" => Ok(StyleTheme::Dark),
+            "light" => Ok(StyleTheme::Light),
+            "monochrome" => Ok(StyleTheme::Monochrome),
+            _ => Err(format!(
+                "'{s}' is not a valid theme, expected one of 'dark', 'light' or 'monochrome'"
+            )),
         }
-        .to_string()
     }
 }
 
+/// Whether styling should be emitted, in order of precedence:
+/// `NO_COLOR` disables it, `CLICOLOR_FORCE`/`FORCE_COLOR` enables it,
+/// and otherwise it depends on whether the output is a TTY.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorMode {
+    Never,
+    Always,
+    Auto,
+}
+
+impl ColorMode {
+    fn from_env() -> Self {
+        if is_no_color_set() {
+            ColorMode::Never
+        } else if is_force_color_set() {
+            ColorMode::Always
+        } else {
+            ColorMode::Auto
+        }
+    }
+}
+
+/// Terminals that render OSC 8 hyperlinks, as reported by `TERM_PROGRAM`
+const HYPERLINK_TERM_PROGRAMS: [&str; 4] = ["iTerm.app", "WezTerm", "vscode", "ghostty"];
+
+/// Whether URLs painted with [`Style::Link`] should be clickable. `ROVER_HYPERLINKS`
+/// turns this on or off explicitly, otherwise it is on for terminals known to support it.
+pub fn is_hyperlink_set() -> bool {
+    if is_plain() {
+        return false;
+    }
+    match std::env::var("ROVER_HYPERLINKS") {
+        Ok(_) => is_bool_env_var_set("ROVER_HYPERLINKS"),
+        Err(_) => std::env::var("TERM_PROGRAM").map_or(false, |term| {
+            HYPERLINK_TERM_PROGRAMS.contains(&term.as_str())
+        }),
+    }
+}
+
+fn is_web_url(text: &str) -> bool {
+    url::Url::parse(text).map_or(false, |url| matches!(url.scheme(), "http" | "https"))
+}
+
+const OSC8_START: &str = "\u{1b}]8;;";
+const OSC8_END: &str = "\u{1b}\\";
+
+/// wraps already-painted text in an OSC 8 escape sequence linking to `url`
+fn hyperlink(url: &str, painted: &str) -> String {
+    format!("{OSC8_START}{url}{OSC8_END}{painted}{OSC8_START}{OSC8_END}")
+}
+
+/// The width of the terminal attached to stderr, or 80 columns if there isn't one.
+pub fn terminal_width() -> usize {
+    console::Term::stderr()
+        .size_checked()
+        .map(|(_rows, columns)| columns as usize)
+        .unwrap_or(80)
+}
+
+/// Word-wraps `text` so that no line is wider than `width` visible columns.
+///
+/// Widths are measured without the ANSI escape sequences from [`Style::paint`],
+/// and lines are only broken on spaces so those sequences are never split.
+/// Existing line breaks are kept, and words wider than `width` get their own line.
+pub fn wrap(text: &str, width: usize) -> String {
+    text.split('\n')
+        .map(|line| wrap_line(line, width.max(1)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn wrap_line(line: &str, width: usize) -> String {
+    let mut wrapped = String::with_capacity(line.len());
+    let mut line_width = 0;
+    for word in line.split(' ').filter(|word| !word.is_empty()) {
+        let word_width = visible_width(word);
+        if line_width > 0 && line_width + 1 + word_width > width {
+            wrapped.push('\n');
+            line_width = 0;
+        } else if line_width > 0 {
+            wrapped.push(' ');
+            line_width += 1;
+        }
+        wrapped.push_str(word);
+        line_width += word_width;
+    }
+    wrapped
+}
+
+/// the number of columns `text` takes up once color and hyperlink
+/// escape sequences are left out
+fn visible_width(text: &str) -> usize {
+    let mut rest = text;
+    let mut width = 0;
+    while let Some(start) = rest.find(OSC8_START) {
+        width += console::measure_text_width(&rest[..start]);
+        rest = match rest[start..].find(OSC8_END) {
+            Some(end) => &rest[start + end + OSC8_END.len()..],
+            None => "",
+        };
+    }
+    width + console::measure_text_width(rest)
+}
+
+/// set for the whole process by `--plain`
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Makes everything printed from now on plain text, with no color, emoji or
+/// hyperlinks, whatever the environment asks for. This is what `--plain` does,
+/// for tools that don't cope well with escape sequences.
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+/// Whether output has to be plain text, as set with [`set_plain`]. Colors,
+/// emoji and hyperlinks all check this before anything in the environment.
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
 pub fn is_no_color_set() -> bool {
-    is_bool_env_var_set("NO_COLOR") || is_bool_env_var_set("APOLLO_NO_COLOR")
+    is_plain() || is_bool_env_var_set("NO_COLOR") || is_bool_env_var_set("APOLLO_NO_COLOR")
 }
 
-fn is_bool_env_var_set(key: &str) -> bool {
+pub fn is_force_color_set() -> bool {
+    is_bool_env_var_set("CLICOLOR_FORCE") || is_bool_env_var_set("FORCE_COLOR")
+}
+
+pub(crate) fn is_bool_env_var_set(key: &str) -> bool {
     !matches!(
         std::env::var(key).as_deref(),
         Err(..) | Ok("") | Ok("0") | Ok("false") | Ok("False") | Ok("FALSE")
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{with_env_vars, with_plain_output};
+    use crate::Emoji;
+
+    #[test]
+    fn it_decides_color_mode_from_env() {
+        let cases: [(&[(&str, &str)], ColorMode); 8] = [
+            (&[], ColorMode::Auto),
+            (&[("NO_COLOR", "1")], ColorMode::Never),
+            (&[("APOLLO_NO_COLOR", "true")], ColorMode::Never),
+            (&[("CLICOLOR_FORCE", "1")], ColorMode::Always),
+            (&[("FORCE_COLOR", "1")], ColorMode::Always),
+            (&[("FORCE_COLOR", "0")], ColorMode::Auto),
+            (
+                &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")],
+                ColorMode::Never,
+            ),
+            (&[("NO_COLOR", "1"), ("FORCE_COLOR", "1")], ColorMode::Never),
+        ];
+        for (vars, expected) in cases {
+            assert_eq!(
+                with_env_vars(vars, ColorMode::from_env),
+                expected,
+                "{vars:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_forces_color_when_output_is_not_a_tty() {
+        let painted = with_env_vars(&[("CLICOLOR_FORCE", "1")], || {
+            console::set_colors_enabled(false);
+            Style::Warning.paint("careful")
+        });
+        assert!(painted.contains("\u{1b}[33m"));
+    }
+
+    #[test]
+    fn it_paints_links_according_to_the_theme() {
+        let paint_link = |theme| {
+            with_env_vars(&[("CLICOLOR_FORCE", "1"), ("ROVER_THEME", theme)], || {
+                Style::Link.paint("x")
+            })
+        };
+        let dark = paint_link("dark");
+        let light = paint_link("light");
+        assert!(dark.contains('\u{1b}'));
+        assert!(light.contains('\u{1b}'));
+        assert_ne!(dark, light);
+        assert_eq!(paint_link("monochrome"), "x");
+        assert_eq!(paint_link("not-a-theme"), dark);
+    }
+
+    #[test]
+    fn it_dims_secondary_text_unless_color_is_off() {
+        let dimmed = with_env_vars(&[("CLICOLOR_FORCE", "1")], || {
+            Style::Dimmed.paint("28 bytes")
+        });
+        assert_eq!(dimmed, "\u{1b}[2m28 bytes\u{1b}[0m");
+
+        let plain = with_env_vars(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")], || {
+            Style::Dimmed.paint("28 bytes")
+        });
+        assert_eq!(plain, "28 bytes");
+    }
+
+    #[test]
+    fn it_only_paints_if_the_condition_holds() {
+        for (condition, no_color, painted) in [
+            (true, false, "\u{1b}[2m28 bytes\u{1b}[0m"),
+            (false, false, "28 bytes"),
+            (true, true, "28 bytes"),
+            (false, true, "28 bytes"),
+        ] {
+            let mut vars = vec![("CLICOLOR_FORCE", "1")];
+            if no_color {
+                vars.push(("NO_COLOR", "1"));
+            }
+            assert_eq!(
+                with_env_vars(&vars, || Style::Dimmed.paint_if(condition, "28 bytes")),
+                painted,
+                "condition: {condition}, NO_COLOR: {no_color}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_turns_off_color_emoji_and_hyperlinks_when_plain() {
+        let vars = [
+            ("CLICOLOR_FORCE", "1"),
+            ("ROVER_HYPERLINKS", "1"),
+            ("ROVER_EMOJI", "1"),
+        ];
+        let url = "https://studio.apollographql.com";
+        let decorate = || {
+            (
+                format!("{}{}", Emoji::Rocket, Style::Link.paint(url)),
+                is_hyperlink_set(),
+            )
+        };
+
+        let (decorated, hyperlinks) = with_env_vars(&vars, decorate);
+        assert!(decorated.starts_with("🚀 \u{1b}]8;;"));
+        assert!(hyperlinks);
+
+        let (plain, hyperlinks) = with_plain_output(&vars, decorate);
+        assert_eq!(plain, url);
+        assert!(!hyperlinks);
+        assert!(with_plain_output(&[], is_no_color_set));
+    }
+
+    #[test]
+    fn it_strips_painted_text_back_to_plain_text() {
+        let link = with_env_vars(
+            &[("CLICOLOR_FORCE", "1"), ("ROVER_HYPERLINKS", "1")],
+            || Style::Link.paint("https://example.com"),
+        );
+        assert!(link.contains(OSC8_START));
+        assert_eq!(Style::strip(&link), "https://example.com");
+    }
+
+    #[test]
+    fn it_strips_adjacent_and_nested_styles() {
+        let message = with_env_vars(&[("CLICOLOR_FORCE", "1")], || {
+            format!(
+                "{}{} {}",
+                Style::WarningPrefix.paint("WARN:"),
+                Style::Command.paint("--name"),
+                Style::Heading.paint(format!("is {}", Style::Failure.paint("missing")))
+            )
+        });
+        assert_eq!(Style::strip(&message), "WARN:--name is missing");
+    }
+
+    #[test]
+    fn it_strips_escape_codes_cut_off_at_the_end() {
+        assert_eq!(Style::strip("done\u{1b}[3"), "done");
+        assert_eq!(Style::strip("done\u{1b}]8;;https://exa"), "done");
+        assert_eq!(Style::strip("done\u{1b}"), "done");
+        assert_eq!(Style::strip("\u{1b}]8;;x\u{7}link\u{1b}[0m!"), "link!");
+    }
+
+    #[test]
+    fn it_leaves_plain_text_alone() {
+        let text = "Published accounts to my-graph@current [100%] ~ ✅";
+        assert_eq!(Style::strip(text), text);
+        assert_eq!(Style::strip(""), "");
+    }
+
+    #[test]
+    fn it_wraps_on_word_boundaries() {
+        assert_eq!(
+            wrap("the quick brown fox jumps over the lazy dog", 12),
+            "the quick\nbrown fox\njumps over\nthe lazy dog"
+        );
+        assert_eq!(wrap("first line\nsecond", 80), "first line\nsecond");
+        assert_eq!(
+            wrap("a https://very-long.example.com b", 10),
+            "a\nhttps://very-long.example.com\nb"
+        );
+    }
+
+    #[test]
+    fn it_wraps_styled_text_without_splitting_escape_codes() {
+        let link = with_env_vars(&[("CLICOLOR_FORCE", "1")], || {
+            Style::Link.paint("https://example.com")
+        });
+        let text = format!("the host {link} is not routable via the public internet");
+        let wrapped = wrap(&text, 30);
+
+        assert_eq!(
+            console::strip_ansi_codes(&wrapped),
+            "the host https://example.com\nis not routable via the public\ninternet"
+        );
+        assert!(wrapped.contains(&link));
+        for line in wrapped.lines() {
+            assert!(console::measure_text_width(line) <= 30);
+        }
+    }
+
+    #[test]
+    fn it_only_hyperlinks_urls_when_enabled() {
+        let paint_link = |vars: &[(&str, &str)], text| {
+            let mut vars = vars.to_vec();
+            vars.push(("CLICOLOR_FORCE", "1"));
+            with_env_vars(&vars, || Style::Link.paint(text))
+        };
+        let osc8 = "\u{1b}]8;;https://example.com\u{1b}\\";
+
+        let linked = paint_link(&[("ROVER_HYPERLINKS", "1")], "https://example.com");
+        assert!(linked.starts_with(osc8));
+        assert!(linked.ends_with("\u{1b}]8;;\u{1b}\\"));
+        assert!(
+            paint_link(&[("TERM_PROGRAM", "WezTerm")], "https://example.com").starts_with(osc8)
+        );
+
+        for vars in [
+            &[][..],
+            &[("TERM_PROGRAM", "Apple_Terminal")],
+            &[("TERM_PROGRAM", "WezTerm"), ("ROVER_HYPERLINKS", "0")],
+            &[("ROVER_HYPERLINKS", "1"), ("NO_COLOR", "1")],
+        ] {
+            assert!(
+                !paint_link(vars, "https://example.com").contains("\u{1b}]8"),
+                "{vars:?}"
+            );
+        }
+        assert_eq!(visible_width(&linked), "https://example.com".len());
+        // graph refs are painted as links too, but aren't URLs
+        assert!(!paint_link(&[("ROVER_HYPERLINKS", "1")], "my-graph@current").contains("\u{1b}]8"));
+    }
+
+    #[test]
+    fn it_only_colors_warnings_when_color_is_enabled() {
+        with_env_vars(&[], || {
+            console::set_colors_enabled(true);
+            let painted = Style::Warning.paint("careful");
+            assert!(painted.contains("\u{1b}[33m"));
+            assert!(painted.contains("careful"));
+
+            console::set_colors_enabled(false);
+            assert_eq!(Style::Warning.paint("careful"), "careful");
+        });
+
+        let painted = with_env_vars(&[("NO_COLOR", "1")], || {
+            console::set_colors_enabled(true);
+            Style::Warning.paint("careful")
+        });
+        assert_eq!(painted, "careful");
+    }
+
+    #[test]
+    fn it_only_colors_the_prefix_of_warning_lines_like_their_message() {
+        let (prefix, line_prefix) = with_env_vars(&[("CLICOLOR_FORCE", "1")], || {
+            (
+                Style::WarningPrefix.paint("WARN:"),
+                Style::WarningLinePrefix.paint("WARN:"),
+            )
+        });
+        assert_eq!(prefix, "\u{1b}[31mWARN:\u{1b}[0m");
+        assert!(line_prefix.contains("\u{1b}[33m"), "{line_prefix:?}");
+    }
+}
@@ -0,0 +1,77 @@
+use std::io::{self, BufRead, Read};
+use std::sync::Mutex;
+
+use crate::prompt::SecretInput;
+
+// environment variables are process-wide, so tests touching them take turns
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+const DECORATION_ENV_VARS: [&str; 10] = [
+    "NO_COLOR",
+    "APOLLO_NO_COLOR",
+    "CLICOLOR_FORCE",
+    "FORCE_COLOR",
+    "ROVER_THEME",
+    "NO_EMOJI",
+    "ROVER_NO_EMOJI",
+    "ROVER_EMOJI",
+    "ROVER_HYPERLINKS",
+    "TERM_PROGRAM",
+];
+
+/// runs `f` with only the given color and emoji env vars set
+pub fn with_env_vars<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    set_vars(vars, f)
+}
+
+/// runs `f` like [`with_env_vars`], with `--plain` output turned on
+pub fn with_plain_output<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    crate::style::set_plain(true);
+    let result = set_vars(vars, f);
+    crate::style::set_plain(false);
+    result
+}
+
+fn set_vars<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+    for key in DECORATION_ENV_VARS {
+        std::env::remove_var(key);
+    }
+    for (key, value) in vars {
+        std::env::set_var(key, value);
+    }
+    let result = f();
+    for (key, _) in vars {
+        std::env::remove_var(key);
+    }
+    result
+}
+
+/// stdin that's interrupted by Ctrl-C before an answer is given
+pub struct InterruptedInput;
+
+impl Read for InterruptedInput {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::Interrupted.into())
+    }
+}
+
+impl BufRead for InterruptedInput {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Err(io::ErrorKind::Interrupted.into())
+    }
+
+    fn consume(&mut self, _amt: usize) {}
+
+    // the default implementation retries interrupted reads forever
+    fn read_line(&mut self, _buf: &mut String) -> io::Result<usize> {
+        Err(io::ErrorKind::Interrupted.into())
+    }
+}
+
+impl SecretInput for InterruptedInput {
+    fn read_secret_line(&mut self) -> io::Result<String> {
+        Err(io::ErrorKind::Interrupted.into())
+    }
+}
@@ -0,0 +1,547 @@
+use std::fmt::Display;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use url::{Host, Url};
+
+/// The URL schemes that the router is able to route operations to over the network
+pub const SUPPORTED_SCHEMES: [&str; 4] = ["http", "https", "ws", "wss"];
+
+/// The URL scheme for subgraphs listening on a Unix domain socket, which is
+/// accepted too but only reachable by a router on the same machine
+const UNIX_SOCKET_SCHEME: &str = "unix";
+
+/// The URL scheme for local files, which only offline setups on the same
+/// machine can make use of
+const FILE_SCHEME: &str = "file";
+
+/// Hostnames that are only reachable from the local machine
+const NON_ROUTABLE_DOMAINS: [&str; 1] = ["localhost"];
+
+/// Domains that are only used inside private networks, which can still be
+/// reachable by a router running in the same network
+const INTERNAL_DOMAINS: [&str; 4] = ["internal", "local", "lan", "home.arpa"];
+
+/// A routing URL that the router will be able to reach
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedUrl(Url);
+
+impl ValidatedUrl {
+    /// the parsed URL
+    pub fn as_url(&self) -> &Url {
+        &self.0
+    }
+
+    /// whether the URL points at a Unix domain socket, which is only
+    /// reachable by a router running on the same machine
+    pub fn is_unix_socket(&self) -> bool {
+        self.0.scheme().eq_ignore_ascii_case(UNIX_SOCKET_SCHEME)
+    }
+}
+
+impl Display for ValidatedUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The reasons a routing URL may not be usable by the router
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoutingUrlWarning {
+    /// The URL could not be parsed at all
+    Unparsable {
+        /// The parse error reported by the URL parser
+        reason: String,
+    },
+
+    /// The URL parsed, but the router does not support its scheme
+    UnsupportedScheme {
+        /// The unsupported scheme, e.g. `ftp`
+        scheme: String,
+    },
+
+    /// The URL points at a local file, which only offline setups, like
+    /// composing on the same machine, can make use of
+    LocalFile {
+        /// The path of the file, e.g. `/etc/subgraphs/accounts.graphql`
+        path: String,
+    },
+
+    /// The URL parsed, but its host is not reachable via the public internet
+    NonRoutableHost {
+        /// The local-only host as it was written, e.g. `localhost`, rather
+        /// than the punycode that internationalized domains are compared as
+        host: String,
+    },
+
+    /// The URL uses the default port of a different scheme,
+    /// e.g. `http://` with port 443, which is usually a typo
+    SchemePortMismatch {
+        /// The scheme the URL was given with, e.g. `http`
+        scheme: String,
+        /// The port the URL was given with, e.g. `443`
+        port: u16,
+        /// The scheme that usually goes with `port`, e.g. `https`
+        expected_scheme: String,
+    },
+
+    /// The URL has a port on a host that looks like it's only reachable
+    /// inside a private network, e.g. `http://accounts:4001`, which is
+    /// usually a development URL that was published by mistake
+    InternalHostPort {
+        /// The internal-looking host as it was written, e.g. `accounts`
+        host: String,
+        /// The port the URL was given with, e.g. `4001`
+        port: u16,
+    },
+}
+
+/// validates that a routing URL can be reached by the router,
+/// returning the reason it can't if it is unusable.
+///
+/// schemes and hostnames are case-insensitive, so both are compared lowercased
+/// rather than relying on the URL parser to have normalized them. domains are
+/// also compared in their punycode form, so internationalized ones that map to
+/// a reserved name, like a fullwidth `ｌｏｃａｌｈｏｓｔ`, are caught too.
+pub fn validate_routing_url(routing_url: &str) -> Result<ValidatedUrl, RoutingUrlWarning> {
+    let parsed_url = Url::parse(routing_url).map_err(|e| RoutingUrlWarning::Unparsable {
+        reason: e.to_string(),
+    })?;
+
+    let scheme = parsed_url.scheme().to_ascii_lowercase();
+    if scheme == FILE_SCHEME {
+        return Err(RoutingUrlWarning::LocalFile {
+            path: parsed_url.path().to_string(),
+        });
+    }
+    if scheme != UNIX_SOCKET_SCHEME && !SUPPORTED_SCHEMES.contains(&scheme.as_str()) {
+        return Err(RoutingUrlWarning::UnsupportedScheme { scheme });
+    }
+
+    // a URL without a host, like a Unix domain socket's, is fine as it is
+    if let Some(host) = parsed_url.host().filter(|_| !is_routable(&parsed_url)) {
+        return Err(RoutingUrlWarning::NonRoutableHost {
+            host: display_host(routing_url, &host),
+        });
+    }
+
+    if let Some(port) = parsed_url.port() {
+        if let Some(expected_scheme) = mismatched_scheme_for_port(&scheme, port) {
+            return Err(RoutingUrlWarning::SchemePortMismatch {
+                scheme,
+                port,
+                expected_scheme: expected_scheme.to_string(),
+            });
+        }
+        if let Some(host) = parsed_url.host().filter(|_| is_internal(&parsed_url)) {
+            return Err(RoutingUrlWarning::InternalHostPort {
+                host: display_host(routing_url, &host),
+                port,
+            });
+        }
+    }
+
+    Ok(ValidatedUrl(parsed_url))
+}
+
+/// whether `url` has a domain that looks like it's only used inside a private
+/// network: one without any dots, like a container's name, or one under a
+/// domain like `internal` that's reserved for private use
+fn is_internal(url: &Url) -> bool {
+    let Some(Host::Domain(domain)) = url.host() else {
+        return false;
+    };
+    let domain = ascii_domain(domain);
+    let domain = domain.trim_end_matches('.');
+    // the hosts of non-special schemes aren't parsed, so IPs can still be strings here
+    if domain.parse::<IpAddr>().is_ok() {
+        return false;
+    }
+    !domain.contains('.')
+        || INTERNAL_DOMAINS
+            .iter()
+            .any(|internal| domain.ends_with(&format!(".{internal}")))
+}
+
+/// returns the scheme that `port` is the default for, if it isn't `scheme`.
+/// the parser drops a scheme's own default port, so only ports that belong
+/// to the other of a plaintext/TLS pair are ever seen here
+fn mismatched_scheme_for_port(scheme: &str, port: u16) -> Option<&'static str> {
+    match (scheme, port) {
+        ("http", 443) => Some("https"),
+        ("https", 80) => Some("http"),
+        ("ws", 443) => Some("wss"),
+        ("wss", 80) => Some("ws"),
+        _ => None,
+    }
+}
+
+/// the punycode form of `domain`, which is how domains are compared.
+/// the parser already converts the domains of special schemes like `http`,
+/// but leaves those of other schemes as written
+fn ascii_domain(domain: &str) -> String {
+    idna::domain_to_ascii(domain).unwrap_or_else(|_| domain.to_ascii_lowercase())
+}
+
+/// how a host is shown in warnings: as it was written in `routing_url`,
+/// rather than the punycode the parser turns domains into
+fn display_host(routing_url: &str, host: &Host<&str>) -> String {
+    original_host(routing_url)
+        .filter(|original| !original.is_empty())
+        .map_or_else(|| host.to_string(), str::to_string)
+}
+
+/// the host in `routing_url`, sliced out of the authority between the
+/// scheme and the path, without any user info or port
+fn original_host(routing_url: &str) -> Option<&str> {
+    let (_, rest) = routing_url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_and_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host_and_port)| host_and_port);
+    if host_and_port.starts_with('[') {
+        return host_and_port.find(']').map(|end| &host_and_port[..=end]);
+    }
+    Some(
+        host_and_port
+            .rsplit_once(':')
+            .map_or(host_and_port, |(host, _)| host),
+    )
+}
+
+/// whether `url` points at a host that's reachable via the public internet.
+///
+/// loopback, unspecified, link-local and private addresses, including IPv6
+/// unique local addresses, aren't, and neither are `localhost` and the
+/// domains under it. hosts are only looked at as written, never resolved, and
+/// URLs without a host, like Unix domain sockets, aren't routable either.
+pub fn is_routable(url: &Url) -> bool {
+    let host = match url.host() {
+        Some(Host::Domain(domain)) => ascii_domain(domain),
+        Some(Host::Ipv4(ip)) => return is_routable_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => return is_routable_ip(IpAddr::V6(ip)),
+        None => return false,
+    };
+    // the hosts of non-special schemes aren't parsed, so IPs can still be strings here
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return is_routable_ip(ip);
+    }
+    let domain = host.trim_end_matches('.');
+    !NON_ROUTABLE_DOMAINS
+        .iter()
+        .any(|local| domain == *local || domain.ends_with(&format!(".{local}")))
+}
+
+fn is_routable_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_routable_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_routable_ipv4(ip),
+            None => is_routable_ipv6(ip),
+        },
+    }
+}
+
+fn is_routable_ipv4(ip: Ipv4Addr) -> bool {
+    !(ip.is_loopback() || ip.is_unspecified() || ip.is_link_local() || ip.is_private())
+}
+
+fn is_routable_ipv6(ip: Ipv6Addr) -> bool {
+    let first_segment = ip.segments()[0];
+    // fe80::/10
+    let is_link_local = first_segment & 0xffc0 == 0xfe80;
+    // fc00::/7
+    let is_unique_local = first_segment & 0xfe00 == 0xfc00;
+    !(ip.is_loopback() || ip.is_unspecified() || is_link_local || is_unique_local)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_accepts_a_public_https_url() {
+        let validated = validate_routing_url("https://subgraph.example.com/graphql").unwrap();
+        assert_eq!(
+            validated.as_url().as_str(),
+            "https://subgraph.example.com/graphql"
+        );
+    }
+
+    #[test]
+    fn it_accepts_websocket_urls() {
+        for url in ["ws://subgraph.example.com", "wss://subgraph.example.com"] {
+            let validated = validate_routing_url(url).unwrap();
+            assert!(!validated.is_unix_socket());
+        }
+    }
+
+    #[test]
+    fn it_accepts_a_unix_socket_url() {
+        let validated = validate_routing_url("unix:///tmp/subgraph.sock").unwrap();
+        assert!(validated.is_unix_socket());
+    }
+
+    #[test]
+    fn it_rejects_an_unparsable_url() {
+        assert!(matches!(
+            validate_routing_url("invalid-url"),
+            Err(RoutingUrlWarning::Unparsable { .. })
+        ));
+    }
+
+    #[test]
+    fn it_rejects_an_unsupported_scheme() {
+        assert_eq!(
+            validate_routing_url("ftp://invalid-scheme"),
+            Err(RoutingUrlWarning::UnsupportedScheme {
+                scheme: "ftp".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn it_warns_about_a_local_file() {
+        for url in [
+            "file:///etc/subgraphs/accounts.graphql",
+            "FILE:///etc/subgraphs/accounts.graphql",
+        ] {
+            assert_eq!(
+                validate_routing_url(url),
+                Err(RoutingUrlWarning::LocalFile {
+                    path: "/etc/subgraphs/accounts.graphql".to_string()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn it_warns_about_a_non_routable_host() {
+        assert_eq!(
+            validate_routing_url("http://localhost:8000"),
+            Err(RoutingUrlWarning::NonRoutableHost {
+                host: "localhost".to_string()
+            })
+        );
+        assert_eq!(
+            validate_routing_url("http://127.0.0.1:8000"),
+            Err(RoutingUrlWarning::NonRoutableHost {
+                host: "127.0.0.1".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn it_warns_about_loopback_and_unspecified_ips() {
+        for (url, host) in [
+            ("http://[::1]:4000", "[::1]"),
+            ("http://127.0.0.2", "127.0.0.2"),
+            ("http://127.255.255.254", "127.255.255.254"),
+            ("http://0.0.0.0:80", "0.0.0.0"),
+            ("http://[::]:4000", "[::]"),
+        ] {
+            assert_eq!(
+                validate_routing_url(url),
+                Err(RoutingUrlWarning::NonRoutableHost {
+                    host: host.to_string()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn it_warns_about_every_host_that_is_not_routable() {
+        for (url, host) in [
+            ("http://accounts.localhost:4000", "accounts.localhost"),
+            ("http://10.0.0.12", "10.0.0.12"),
+            ("http://192.168.1.10:8080", "192.168.1.10"),
+            ("http://169.254.169.254", "169.254.169.254"),
+            ("http://[fe80::1]", "[fe80::1]"),
+            ("http://[fd12:3456:789a::1]", "[fd12:3456:789a::1]"),
+        ] {
+            assert_eq!(
+                validate_routing_url(url),
+                Err(RoutingUrlWarning::NonRoutableHost {
+                    host: host.to_string()
+                }),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_compares_schemes_and_hosts_case_insensitively() {
+        assert!(validate_routing_url("HTTPS://Example.com").is_ok());
+        assert!(validate_routing_url("UNIX:///tmp/subgraph.sock")
+            .unwrap()
+            .is_unix_socket());
+        assert_eq!(
+            validate_routing_url("FTP://example.com"),
+            Err(RoutingUrlWarning::UnsupportedScheme {
+                scheme: "ftp".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn it_lowercases_hosts_before_the_loopback_check() {
+        for (url, host) in [
+            ("http://LOCALHOST:4000", "LOCALHOST"),
+            ("WS://LocalHost", "LocalHost"),
+        ] {
+            assert_eq!(
+                validate_routing_url(url),
+                Err(RoutingUrlWarning::NonRoutableHost {
+                    host: host.to_string()
+                })
+            );
+        }
+        // hosts of non-special schemes are left as written by the parser
+        assert!(!is_routable(&Url::parse("unix-like://LOCALHOST").unwrap()));
+    }
+
+    #[test]
+    fn it_tells_which_hosts_are_routable() {
+        for (url, routable) in [
+            ("https://subgraph.example.com/graphql", true),
+            ("http://8.8.8.8", true),
+            ("http://172.32.0.1", true),
+            ("http://[2001:db8::1]:4000", true),
+            ("http://localhost:4000", false),
+            ("http://accounts.localhost", false),
+            ("http://Accounts.LOCALHOST.", false),
+            ("http://notlocalhost.com", true),
+            ("http://127.0.0.1:4000", false),
+            ("http://0.0.0.0", false),
+            ("http://10.0.0.12", false),
+            ("http://172.16.4.2", false),
+            ("http://172.31.255.255", false),
+            ("http://192.168.1.10:8080", false),
+            ("http://169.254.169.254", false),
+            ("http://[::1]", false),
+            ("http://[::]", false),
+            ("http://[fe80::1]", false),
+            ("http://[fd12:3456:789a::1]", false),
+            ("http://[fc00::1]", false),
+            ("http://[::ffff:192.168.1.10]", false),
+            ("unix-like://10.0.0.12", false),
+            ("unix-like://subgraph.example.com", true),
+            ("unix:///tmp/subgraph.sock", false),
+        ] {
+            assert_eq!(
+                is_routable(&Url::parse(url).unwrap()),
+                routable,
+                "{url} should be {}routable",
+                if routable { "" } else { "non-" }
+            );
+        }
+    }
+
+    #[test]
+    fn it_compares_internationalized_domains_as_punycode() {
+        assert!(validate_routing_url("http://exämple.com/graphql").is_ok());
+        assert!(is_routable(&Url::parse("http://exämple.com").unwrap()));
+        assert!(is_routable(&Url::parse("unix-like://exämple.com").unwrap()));
+        // a Cyrillic `о` makes this a different domain from `localhost`
+        assert!(is_routable(&Url::parse("http://lоcalhost").unwrap()));
+
+        // fullwidth letters map to plain `localhost`
+        for url in [
+            "http://ｌｏｃａｌｈｏｓｔ:4000",
+            "http://accounts.ｌｏｃａｌｈｏｓｔ",
+        ] {
+            assert!(!is_routable(&Url::parse(url).unwrap()), "{url}");
+        }
+        assert_eq!(
+            validate_routing_url("http://ｌｏｃａｌｈｏｓｔ:4000"),
+            Err(RoutingUrlWarning::NonRoutableHost {
+                host: "ｌｏｃａｌｈｏｓｔ".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn it_shows_hosts_as_they_were_written() {
+        let parsed = Url::parse("http://Exämple.com").unwrap();
+        assert_eq!(parsed.host_str(), Some("xn--exmple-cua.com"));
+        for (url, host) in [
+            ("http://Exämple.com", "Exämple.com"),
+            (
+                "http://user:pass@münchen.localhost:4000/graphql",
+                "münchen.localhost",
+            ),
+            ("http://[::1]:4000", "[::1]"),
+            ("http://127.0.0.1?query", "127.0.0.1"),
+        ] {
+            let parsed = Url::parse(url).unwrap();
+            assert_eq!(display_host(url, &parsed.host().unwrap()), host, "{url}");
+        }
+    }
+
+    #[test]
+    fn it_warns_about_scheme_and_port_mismatches() {
+        for (url, scheme, port, expected_scheme) in [
+            ("http://api.example.com:443", "http", 443, "https"),
+            ("https://api.example.com:80/graphql", "https", 80, "http"),
+            ("ws://api.example.com:443", "ws", 443, "wss"),
+            ("wss://api.example.com:80", "wss", 80, "ws"),
+        ] {
+            assert_eq!(
+                validate_routing_url(url),
+                Err(RoutingUrlWarning::SchemePortMismatch {
+                    scheme: scheme.to_string(),
+                    port,
+                    expected_scheme: expected_scheme.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn it_warns_about_ports_on_internal_hosts() {
+        for (url, host, port) in [
+            ("http://accounts:4001/graphql", "accounts", 4001),
+            ("http://accounts.internal:4001", "accounts.internal", 4001),
+            ("https://Accounts.Local:8443", "Accounts.Local", 8443),
+            ("ws://accounts.home.arpa:4001", "accounts.home.arpa", 4001),
+        ] {
+            assert_eq!(
+                validate_routing_url(url),
+                Err(RoutingUrlWarning::InternalHostPort {
+                    host: host.to_string(),
+                    port,
+                }),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_accepts_internal_hosts_without_a_port() {
+        for url in [
+            "http://accounts/graphql",
+            "http://accounts.internal",
+            "https://accounts.local:443",
+            "http://accounts.internal.example.com:4001",
+        ] {
+            assert!(validate_routing_url(url).is_ok(), "{url}");
+        }
+    }
+
+    #[test]
+    fn it_accepts_matching_and_custom_ports() {
+        for url in [
+            "http://api.example.com:80",
+            "https://api.example.com:443",
+            "https://api.example.com:8443",
+            "http://api.example.com:4000",
+        ] {
+            assert!(validate_routing_url(url).is_ok(), "{url}");
+        }
+    }
+
+    #[test]
+    fn it_accepts_a_public_ip() {
+        assert!(validate_routing_url("http://128.0.0.1:4000").is_ok());
+        assert!(validate_routing_url("http://[2001:db8::1]:4000").is_ok());
+    }
+}
@@ -4,8 +4,9 @@
 //! by `env_logger`, `log`, and/or `tracing`.
 
 use clap::ValueEnum;
-use std::io;
+use std::io::{self, Write};
 use tracing_subscriber::fmt;
+use tracing_subscriber::fmt::format::FmtSpan;
 
 pub use tracing_core::Level;
 
@@ -38,7 +39,27 @@ pub fn init(level: Option<Level>) {
         fmt()
             .with_max_level(level)
             .event_format(format)
-            .with_writer(io::stderr)
+            // report how long each span took when it closes
+            .with_span_events(FmtSpan::CLOSE)
+            .with_ansi(!rover_std::is_plain())
+            .with_writer(|| RedactingWriter)
             .init();
     }
 }
+
+/// Writes logs to stderr with API keys and other credentials redacted,
+/// so that debug output can be shared safely.
+struct RedactingWriter;
+
+impl Write for RedactingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // each event is written in a single call, so secrets are never split
+        let redacted = rover_std::redact(&String::from_utf8_lossy(buf));
+        io::stderr().write_all(redacted.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
@@ -1,14 +1,19 @@
 use camino::Utf8PathBuf;
 use clap::{Parser, ValueEnum};
 use lazycell::{AtomicLazyCell, LazyCell};
-use reqwest::blocking::Client;
+use reqwest::{blocking::Client, Url};
 use serde::Serialize;
 
 use crate::command::{self, RoverOutput};
 use crate::options::OutputOpts;
 use crate::utils::{
-    client::{ClientBuilder, ClientTimeout, StudioClientConfig},
+    client::{
+        skip_tls_verify_warning, ClientBuilder, ClientIdentity, ClientTimeout, StudioClientConfig,
+        DEFAULT_CONNECT_TIMEOUT_SECS, DEFAULT_MAX_REQUESTS_PER_HOST,
+        DEFAULT_POOL_IDLE_TIMEOUT_SECS, DEFAULT_POOL_MAX_IDLE_PER_HOST,
+    },
     env::{RoverEnv, RoverEnvKey},
+    retry,
     stringify::option_from_display,
     version,
 };
@@ -20,7 +25,7 @@ use rover_client::shared::GitContext;
 use sputnik::Session;
 use timber::Level;
 
-use std::{io, process, thread};
+use std::{io, num::NonZeroUsize, process, thread, time::Duration};
 
 #[derive(Debug, Serialize, Parser)]
 #[command(
@@ -83,6 +88,15 @@ pub struct Rover {
     #[arg(long = "insecure-accept-invalid-hostnames", global = true)]
     accept_invalid_hostnames: bool,
 
+    /// Skip verifying TLS certificates and hostnames when performing HTTPS requests,
+    /// such as for a test environment with a self-signed certificate.
+    ///
+    /// This makes every request open to man-in-the-middle attacks, so a warning is
+    /// printed whenever it's used. It can only be set with this flag, never with a
+    /// config file or environment variable.
+    #[arg(long = "insecure-skip-tls-verify", global = true)]
+    insecure_skip_tls_verify: bool,
+
     /// Configure the timeout length (in seconds) when performing HTTP(S) requests.
     #[arg(
         long = "client-timeout",
@@ -91,10 +105,132 @@ pub struct Rover {
     )]
     client_timeout: ClientTimeout,
 
+    /// The most time (in seconds) to wait for a connection to a server to be made.
+    ///
+    /// This applies to every request Rover makes, and is separate from `--client-timeout`.
+    #[arg(
+        long = "client-connect-timeout",
+        global = true,
+        value_name = "SECONDS",
+        env = "APOLLO_CLIENT_CONNECT_TIMEOUT",
+        default_value_t = DEFAULT_CONNECT_TIMEOUT_SECS
+    )]
+    client_connect_timeout: u64,
+
+    /// How many idle connections to each host are kept open for reuse.
+    ///
+    /// Raising this lets large manifest publishes reuse more connections to Apollo Studio.
+    #[arg(
+        long = "client-pool-max-idle-per-host",
+        global = true,
+        value_name = "CONNECTIONS",
+        env = "APOLLO_CLIENT_POOL_MAX_IDLE_PER_HOST",
+        default_value_t = DEFAULT_POOL_MAX_IDLE_PER_HOST
+    )]
+    client_pool_max_idle_per_host: usize,
+
+    /// How long (in seconds) an idle connection is kept open for reuse.
+    #[arg(
+        long = "client-pool-idle-timeout",
+        global = true,
+        value_name = "SECONDS",
+        env = "APOLLO_CLIENT_POOL_IDLE_TIMEOUT",
+        default_value_t = DEFAULT_POOL_IDLE_TIMEOUT_SECS
+    )]
+    client_pool_idle_timeout: u64,
+
+    /// The most requests to send to any one host at the same time.
+    ///
+    /// This caps the `--max-concurrency` of a manifest publish.
+    #[arg(
+        long = "client-max-requests-per-host",
+        global = true,
+        value_name = "REQUESTS",
+        env = "APOLLO_CLIENT_MAX_REQUESTS_PER_HOST",
+        default_value_t = DEFAULT_MAX_REQUESTS_PER_HOST
+    )]
+    client_max_requests_per_host: NonZeroUsize,
+
+    /// A PEM encoded certificate to present to servers that require client
+    /// authentication (mTLS). Must be passed along with --client-key.
+    #[arg(
+        long = "client-cert",
+        global = true,
+        value_name = "PATH",
+        requires = "client_key"
+    )]
+    #[serde(skip_serializing)]
+    client_cert: Option<Utf8PathBuf>,
+
+    /// The PEM encoded PKCS#8 private key for --client-cert.
+    #[arg(
+        long = "client-key",
+        global = true,
+        value_name = "PATH",
+        requires = "client_cert"
+    )]
+    #[serde(skip_serializing)]
+    client_key: Option<Utf8PathBuf>,
+
+    /// Read the API key from this file instead of a profile or the APOLLO_KEY
+    /// environment variable, so it doesn't show up in process listings or shell history.
+    #[arg(long = "api-key-file", global = true, value_name = "PATH")]
+    #[serde(skip_serializing)]
+    api_key_file: Option<Utf8PathBuf>,
+
+    /// Read Rover's environment variables, like APOLLO_KEY, from a `.env` file
+    /// when they aren't already set in the environment.
+    ///
+    /// Without a path, `.env` in the current directory is read, and skipped if
+    /// it's missing. A file given with `--env-file=PATH` has to exist.
+    #[arg(
+        long = "env-file",
+        global = true,
+        value_name = "PATH",
+        num_args = 0..=1,
+        require_equals = true
+    )]
+    #[serde(skip_serializing)]
+    env_file: Option<Option<Utf8PathBuf>>,
+
+    /// A PEM encoded root certificate to trust along with the system's, such as
+    /// the one for a TLS-inspecting proxy. May be passed more than once.
+    #[arg(long = "ca-cert", global = true, value_name = "PATH")]
+    #[serde(skip_serializing)]
+    ca_certs: Vec<Utf8PathBuf>,
+
+    /// Send requests through this proxy instead of the ones set with the
+    /// HTTPS_PROXY and HTTP_PROXY environment variables.
+    ///
+    /// Hosts listed in NO_PROXY are still requested directly.
+    #[arg(long = "proxy", global = true, value_name = "URL")]
+    #[serde(skip_serializing)]
+    proxy: Option<Url>,
+
+    /// The most time (in seconds) a command may spend on requests to Apollo Studio,
+    /// including any retries.
+    ///
+    /// Unlike --client-timeout, which applies to each request, this bounds all of them together.
+    #[arg(long = "timeout", global = true, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
     /// Skip checking for newer versions of rover.
     #[arg(long = "skip-update-check", global = true)]
     skip_update_check: bool,
 
+    /// Print plain text only, with no color, emoji, or clickable links.
+    ///
+    /// This overrides NO_COLOR, CLICOLOR_FORCE, ROVER_EMOJI, ROVER_HYPERLINKS, and
+    /// whether the output is a terminal, for tools that don't handle escape codes well.
+    #[arg(long = "plain", global = true)]
+    plain: bool,
+
+    /// Suppress informational messages on stderr.
+    ///
+    /// Errors, prompts, and the output of the command itself are still printed.
+    #[arg(long = "quiet", global = true)]
+    quiet: bool,
+
     #[arg(skip)]
     #[serde(skip_serializing)]
     env_store: LazyCell<RoverEnv>,
@@ -114,6 +250,8 @@ impl Rover {
     }
 
     pub fn run(&self) -> RoverResult<()> {
+        // before anything is printed, logs included
+        rover_std::style::set_plain(self.plain);
         timber::init(self.log_level);
         tracing::trace!(command_structure = ?self);
         self.output_opts.validate_options();
@@ -152,14 +290,16 @@ impl Rover {
 
         match rover_output {
             Ok(output) => {
+                let exit_code = output.exit_code();
                 self.output_opts.handle_output(output)?;
 
-                process::exit(0);
+                process::exit(exit_code);
             }
             Err(error) => {
+                let exit_code = error.exit_code();
                 self.output_opts.handle_output(error)?;
 
-                process::exit(1);
+                process::exit(exit_code);
             }
         }
     }
@@ -201,6 +341,8 @@ impl Rover {
                 self.get_client_config()?,
                 self.get_git_context()?,
                 self.get_checks_timeout_seconds()?,
+                self.get_routing_url_fetch_attempts()?,
+                self.quiet,
                 &self.output_opts,
             ),
             Command::Update(command) => {
@@ -220,7 +362,11 @@ impl Rover {
             .get_env_var(RoverEnvKey::ConfigHome)?
             .map(|p| Utf8PathBuf::from(&p));
         let override_api_key = self.get_env_var(RoverEnvKey::Key)?;
-        Ok(Config::new(override_home.as_ref(), override_api_key)?)
+        let config = Config::new(override_home.as_ref(), override_api_key)?;
+        Ok(match &self.api_key_file {
+            Some(api_key_file) => config.with_api_key_file(api_key_file)?,
+            None => config,
+        })
     }
 
     pub(crate) fn get_client_config(&self) -> RoverResult<StudioClientConfig> {
@@ -237,7 +383,8 @@ impl Rover {
             config,
             is_sudo,
             self.get_reqwest_client_builder()?,
-        ))
+        )
+        .with_timeout(self.timeout.map(Duration::from_secs)))
     }
 
     pub(crate) fn get_install_override_path(&self) -> RoverResult<Option<Utf8PathBuf>> {
@@ -253,6 +400,8 @@ impl Rover {
             commit: self.get_env_var(RoverEnvKey::VcsCommit)?,
             author: self.get_env_var(RoverEnvKey::VcsAuthor)?,
             remote_url: self.get_env_var(RoverEnvKey::VcsRemoteUrl)?,
+            branch_source: None,
+            commit_source: None,
         };
 
         let git_context = GitContext::new_with_override(override_git_context);
@@ -274,21 +423,44 @@ impl Rover {
     pub(crate) fn get_reqwest_client_builder(&self) -> RoverResult<ClientBuilder> {
         // return a copy of the underlying client builder if it's already been populated
         if let Some(client_builder) = self.client_builder.borrow() {
-            Ok(*client_builder)
+            Ok(client_builder.clone())
         } else {
             // if a request hasn't been made yet, this cell won't be populated yet
+            if self.insecure_skip_tls_verify {
+                eprintln!("{}", skip_tls_verify_warning());
+            }
             self.client_builder
                 .fill(
                     ClientBuilder::new()
                         .accept_invalid_certs(self.accept_invalid_certs)
                         .accept_invalid_hostnames(self.accept_invalid_hostnames)
-                        .with_timeout(self.client_timeout.get_duration()),
+                        .skip_tls_verify(self.insecure_skip_tls_verify)
+                        .with_timeout(self.client_timeout.get_duration())
+                        .with_proxy(self.proxy.clone())
+                        .with_client_identity(self.client_identity())
+                        .with_ca_certs(self.ca_certs.clone())
+                        .with_connect_timeout(Duration::from_secs(self.client_connect_timeout))
+                        .with_pool(
+                            self.client_pool_max_idle_per_host,
+                            Duration::from_secs(self.client_pool_idle_timeout),
+                        )
+                        .with_max_requests_per_host(self.client_max_requests_per_host),
                 )
                 .expect("Could not overwrite existing request client builder");
             self.get_reqwest_client_builder()
         }
     }
 
+    fn client_identity(&self) -> Option<ClientIdentity> {
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => Some(ClientIdentity {
+                cert: cert.clone(),
+                key: key.clone(),
+            }),
+            _ => None,
+        }
+    }
+
     pub(crate) fn get_checks_timeout_seconds(&self) -> RoverResult<u64> {
         if let Some(seconds) = self.get_env_var(RoverEnvKey::ChecksTimeoutSeconds)? {
             Ok(seconds.parse::<u64>()?)
@@ -298,11 +470,31 @@ impl Rover {
         }
     }
 
+    pub(crate) fn get_routing_url_fetch_attempts(&self) -> RoverResult<usize> {
+        if let Some(attempts) = self.get_env_var(RoverEnvKey::RoutingUrlFetchAttempts)? {
+            Ok(attempts.parse::<usize>().map_err(|e| {
+                anyhow::anyhow!(
+                    "{} must be a number of attempts, like `3`, but it is `{}`: {}",
+                    RoverEnvKey::RoutingUrlFetchAttempts,
+                    attempts,
+                    e
+                )
+            })?)
+        } else {
+            Ok(retry::DEFAULT_ATTEMPTS)
+        }
+    }
+
     pub(crate) fn get_env_var(&self, key: RoverEnvKey) -> io::Result<Option<String>> {
         Ok(if let Some(env_store) = self.env_store.borrow() {
             env_store.get(key)
         } else {
-            let env_store = RoverEnv::new()?;
+            let mut env_store = RoverEnv::new()?;
+            match &self.env_file {
+                Some(Some(env_file)) => env_store.load_env_file(env_file)?,
+                Some(None) => env_store.load_default_env_file()?,
+                None => {}
+            }
             let val = env_store.get(key);
             self.env_store
                 .fill(env_store)
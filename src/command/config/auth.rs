@@ -26,6 +26,7 @@ pub struct Auth {
 
 impl Auth {
     pub fn run(&self, config: config::Config) -> RoverResult<RoverOutput> {
+        let config = self.profile.config(&config)?;
         let api_key = api_key_prompt()?;
         Profile::set_api_key(&self.profile.profile_name, &config, &api_key)?;
         Profile::get_credential(&self.profile.profile_name, &config).map(|_| {
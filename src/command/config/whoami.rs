@@ -42,8 +42,10 @@ impl WhoAmI {
             )));
         }
 
-        let credential =
-            config::Profile::get_credential(&self.profile.profile_name, &client_config.config)?;
+        let credential = config::Profile::get_credential(
+            &self.profile.profile_name,
+            &self.profile.config(&client_config.config)?,
+        )?;
 
         Ok(RoverOutput::ConfigWhoAmIOutput {
             api_key: self.get_maybe_masked_api_key(&credential),
@@ -63,6 +65,7 @@ impl WhoAmI {
         match client.get_credential_origin() {
             CredentialOrigin::ConfigFile(path) => format!("--profile {}", &path),
             CredentialOrigin::EnvVar => format!("${}", &RoverEnvKey::Key),
+            CredentialOrigin::ApiKeyFile(path) => format!("--api-key-file {}", &path),
         }
     }
 
@@ -104,6 +107,7 @@ mod tests {
         WhoAmI {
             profile: ProfileOpt {
                 profile_name: "default".to_string(),
+                config_dir: None,
             },
             insecure_unmask_key: unmasked_key,
         }
@@ -1,7 +1,6 @@
-use std::fs;
 use std::io::prelude::*;
 
-use anyhow::{Context, Error};
+use anyhow::Error;
 use apollo_federation_types::config::{FederationVersion, SupergraphConfig};
 use camino::Utf8PathBuf;
 use rover_std::{Emoji, Fs};
@@ -108,8 +107,7 @@ impl ComposeRunner {
     fn remove_supergraph_schema(&self) -> RoverResult<()> {
         if Fs::assert_path_exists(&self.write_path).is_ok() {
             eprintln!("{}composition failed, killing the router", Emoji::Skull);
-            Ok(fs::remove_file(&self.write_path)
-                .with_context(|| format!("could not remove {}", &self.write_path))?)
+            Ok(Fs::remove_file(&self.write_path)?)
         } else {
             Ok(())
         }
@@ -36,9 +36,11 @@ impl Check {
         checks_timeout_seconds: u64,
     ) -> RoverResult<RoverOutput> {
         let client = client_config.get_authenticated_client(&self.profile)?;
-        let proposed_schema = self
-            .schema
-            .read_file_descriptor("SDL", &mut std::io::stdin())?;
+        let proposed_schema = self.schema.read_file_descriptor(
+            "SDL",
+            &mut std::io::stdin(),
+            &client_config.get_reqwest_client()?,
+        )?;
 
         eprintln!(
             "Checking the proposed schema against {}",
@@ -27,9 +27,11 @@ impl Lint {
     pub fn run(&self, client_config: StudioClientConfig) -> RoverResult<RoverOutput> {
         let client = client_config.get_authenticated_client(&self.profile)?;
 
-        let file_with_metadata = self
-            .schema
-            .read_file_descriptor_with_metadata("SDL", &mut std::io::stdin())?;
+        let file_with_metadata = self.schema.read_file_descriptor_with_metadata(
+            "SDL",
+            &mut std::io::stdin(),
+            &client_config.get_reqwest_client()?,
+        )?;
 
         let lint_result: rover_client::shared::LintResponse = lint::run(
             LintGraphInput {
@@ -36,11 +36,13 @@ impl Publish {
             Style::Command.paint(&self.profile.profile_name)
         );
 
-        let proposed_schema = self
-            .schema
-            .read_file_descriptor("SDL", &mut std::io::stdin())?;
+        let proposed_schema = self.schema.read_file_descriptor(
+            "SDL",
+            &mut std::io::stdin(),
+            &client_config.get_reqwest_client()?,
+        )?;
 
-        tracing::debug!("Publishing \n{}", &proposed_schema);
+        tracing::trace!("Publishing \n{}", &proposed_schema);
 
         let publish_response = publish::run(
             GraphPublishInput {
@@ -1,7 +1,10 @@
 use std::collections::BTreeMap;
 use std::io::{self, IsTerminal};
+use std::time::Duration;
 
+use crate::command::subgraph::PublishAttempts;
 use crate::command::supergraph::compose::CompositionOutput;
+use crate::error::PublishWarning;
 use crate::options::JsonVersion;
 use crate::utils::table::{self, row};
 use crate::RoverError;
@@ -22,7 +25,7 @@ use rover_client::shared::{
     SdlType,
 };
 use rover_client::RoverClientError;
-use rover_std::Style;
+use rover_std::{Emoji, Style};
 use serde_json::{json, Value};
 use termimad::{crossterm::style::Attribute::Underlined, MadSkin};
 
@@ -32,8 +35,11 @@ use termimad::{crossterm::style::Attribute::Underlined, MadSkin};
 /// in this enum, and its print logic should be handled in `RoverOutput::get_stdout`
 ///
 /// Not all commands will output machine readable information, and those should
-/// return `Ok(RoverOutput::EmptySuccess)`. If a new command is added and it needs to
-/// return something that is not described well in this enum, it should be added.
+/// return `Ok(RoverOutput::EmptySuccess)`. Commands that decide there's nothing
+/// to do, like a publish of an unchanged schema, should return
+/// `Ok(RoverOutput::Skipped { .. })` with the reason why. If a new command is
+/// added and it needs to return something that is not described well in this
+/// enum, it should be added.
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum RoverOutput {
     ConfigWhoAmIOutput {
@@ -61,7 +67,22 @@ pub enum RoverOutput {
     SubgraphPublishResponse {
         graph_ref: GraphRef,
         subgraph: String,
+        previous_routing_url: Option<String>,
+        routing_url: Option<String>,
         publish_response: SubgraphPublishResponse,
+        attempts: PublishAttempts,
+        warnings: Vec<PublishWarning>,
+    },
+    SubgraphPublishDryRun {
+        graph_ref: GraphRef,
+        subgraph: String,
+        routing_url: Option<String>,
+        schema_bytes: usize,
+    },
+    SubgraphPublishBatch {
+        graph_ref: GraphRef,
+        /// the output of each subgraph's publish, in the order they were listed
+        publishes: Vec<TimedPublish>,
     },
     SubgraphDeleteResponse {
         graph_ref: GraphRef,
@@ -89,9 +110,96 @@ pub enum RoverOutput {
     },
     PersistedQueriesPublishResponse(PersistedQueriesPublishResponse),
     EmptySuccess,
+    /// nothing was done, with a message saying why for humans, and the
+    /// subgraph and graph ref it was about, if any
+    Skipped {
+        reason: Option<String>,
+        subgraph: Option<String>,
+        graph_ref: Option<GraphRef>,
+    },
+    /// one line summing `output` up, which is printed in its place with
+    /// `--summary-only`. `--format json` and the exit code still come from `output`
+    Summary {
+        line: String,
+        output: Box<RoverOutput>,
+    },
+    /// `output`, printed as usual, of a publish whose `--response-file`
+    /// couldn't be written, which exits with [`RESPONSE_FILE_EXIT_CODE`]
+    UnsavedResponse {
+        output: Box<RoverOutput>,
+    },
+}
+
+/// The code Rover exits with when a publish succeeds but the supergraph fails
+/// to compose, which CI can tell apart from the `1` that every error exits with
+pub const COMPOSITION_ERRORS_EXIT_CODE: i32 = 3;
+
+/// The code Rover exits with when a publish's `--response-file` couldn't be
+/// written, unless the publish has a more important code of its own
+pub const RESPONSE_FILE_EXIT_CODE: i32 = 4;
+
+/// The output of one subgraph's publish in a batch, with how long it took
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct TimedPublish {
+    pub publish: RoverOutput,
+    pub elapsed: Duration,
 }
 
 impl RoverOutput {
+    /// the code to exit with once this output has been printed
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RoverOutput::SubgraphPublishResponse {
+                publish_response, ..
+            } if !publish_response.build_errors.is_empty() => COMPOSITION_ERRORS_EXIT_CODE,
+            RoverOutput::SubgraphPublishBatch { publishes, .. } => publishes
+                .iter()
+                .map(|publish| publish.publish.exit_code())
+                .max()
+                .unwrap_or(0),
+            RoverOutput::Summary { output, .. } => output.exit_code(),
+            RoverOutput::UnsavedResponse { output } => match output.exit_code() {
+                0 => RESPONSE_FILE_EXIT_CODE,
+                exit_code => exit_code,
+            },
+            _ => 0,
+        }
+    }
+
+    /// the line printed once a subgraph has been published without build errors
+    fn publish_confirmation(subgraph: &str, graph_ref: &GraphRef) -> String {
+        format!(
+            "{}{}",
+            Emoji::Success,
+            Style::Success.paint(format!("Published subgraph {subgraph} to {graph_ref}"))
+        )
+    }
+
+    /// the line printed before the subgraphs in a batch that were published,
+    /// or that would have been on a dry run
+    fn batch_summary(published: &[&TimedPublish], graph_ref: &GraphRef) -> String {
+        let dry_run = published
+            .iter()
+            .all(|timed| matches!(timed.publish, RoverOutput::SubgraphPublishDryRun { .. }));
+        format!(
+            "{} {} subgraphs to {}:",
+            if dry_run && !published.is_empty() {
+                "Would publish"
+            } else {
+                "Published"
+            },
+            published.len(),
+            Style::Link.paint(graph_ref.to_string())
+        )
+    }
+
+    /// the line printed when a command skipped doing anything
+    fn skip_notice(reason: Option<&str>) -> String {
+        reason
+            .unwrap_or("There was nothing to do, so this was skipped.")
+            .to_string()
+    }
+
     pub fn get_stdout(&self) -> io::Result<Option<String>> {
         Ok(match self {
             RoverOutput::ConfigWhoAmIOutput {
@@ -176,6 +284,7 @@ impl RoverOutput {
                 graph_ref,
                 subgraph,
                 publish_response,
+                ..
             } => {
                 if publish_response.subgraph_was_created {
                     stderrln!(
@@ -204,6 +313,52 @@ impl RoverOutput {
                     let warn_prefix = Style::WarningPrefix.paint("WARN:");
                     stderrln!("{} The following build errors occurred:", warn_prefix)?;
                     stderrln!("{}", &publish_response.build_errors)?;
+                } else {
+                    stderrln!("{}", Self::publish_confirmation(subgraph, graph_ref))?;
+                }
+                None
+            }
+            RoverOutput::SubgraphPublishDryRun {
+                graph_ref,
+                subgraph,
+                routing_url,
+                schema_bytes,
+            } => {
+                stderrln!(
+                    "This is a dry run. The following would be published to the {} subgraph in {}:",
+                    Style::Link.paint(subgraph),
+                    Style::Link.paint(graph_ref.to_string())
+                )?;
+                stderrln!(
+                    "  routing url: {}",
+                    match routing_url.as_deref() {
+                        Some("") => "(none)".to_string(),
+                        Some(routing_url) => Style::Link.paint(routing_url),
+                        None => "(unchanged)".to_string(),
+                    }
+                )?;
+                stderrln!("  schema: {} bytes", schema_bytes)?;
+                None
+            }
+            RoverOutput::SubgraphPublishBatch {
+                graph_ref,
+                publishes,
+            } => {
+                for TimedPublish { publish, .. } in publishes {
+                    publish.get_stdout()?;
+                }
+                // skipped subgraphs already said why they weren't published
+                let published: Vec<&TimedPublish> = publishes
+                    .iter()
+                    .filter(|timed| !matches!(timed.publish, RoverOutput::Skipped { .. }))
+                    .collect();
+                stderrln!("{}", Self::batch_summary(&published, graph_ref))?;
+                for TimedPublish { publish, elapsed } in published {
+                    if let RoverOutput::SubgraphPublishResponse { subgraph, .. }
+                    | RoverOutput::SubgraphPublishDryRun { subgraph, .. } = publish
+                    {
+                        stderrln!("  {} in {:.2}s", subgraph, elapsed.as_secs_f64())?;
+                    }
                 }
                 None
             }
@@ -350,9 +505,14 @@ impl RoverOutput {
                 Some((introspection_response).to_string())
             }
             RoverOutput::ErrorExplanation(explanation) => {
-                // underline bolded md
-                let mut skin = MadSkin::default();
-                skin.bold.add_attr(Underlined);
+                let skin = if rover_std::is_plain() {
+                    MadSkin::no_style()
+                } else {
+                    // underline bolded md
+                    let mut skin = MadSkin::default();
+                    skin.bold.add_attr(Underlined);
+                    skin
+                };
 
                 Some(format!("{}", skin.inline(explanation)))
             }
@@ -423,6 +583,12 @@ impl RoverOutput {
                 Some(result)
             }
             RoverOutput::EmptySuccess => None,
+            RoverOutput::Skipped { reason, .. } => {
+                stderrln!("{}", Self::skip_notice(reason.as_deref()))?;
+                None
+            }
+            RoverOutput::Summary { line, .. } => Some(line.clone()),
+            RoverOutput::UnsavedResponse { output } => return output.get_stdout(),
         })
     }
 
@@ -477,10 +643,54 @@ impl RoverOutput {
                 publish_response,
             } => json!(publish_response),
             RoverOutput::SubgraphPublishResponse {
-                graph_ref: _,
-                subgraph: _,
+                graph_ref,
+                subgraph,
+                previous_routing_url,
+                routing_url,
                 publish_response,
-            } => json!(publish_response),
+                attempts,
+                warnings,
+            } => {
+                let mut json = json!(publish_response);
+                json["graph_ref"] = json!(graph_ref.to_string());
+                json["subgraph"] = json!(subgraph);
+                json["previous_routing_url"] = json!(previous_routing_url);
+                json["routing_url"] = json!(routing_url);
+                json["attempts"] = json!(attempts);
+                json["warnings"] = json!(warnings);
+                json
+            }
+            RoverOutput::SubgraphPublishDryRun {
+                graph_ref,
+                subgraph,
+                routing_url,
+                schema_bytes,
+            } => {
+                json!({
+                    "graph_ref": graph_ref.to_string(),
+                    "subgraph": subgraph,
+                    "routing_url": routing_url,
+                    "schema_bytes": schema_bytes,
+                    "dry_run": true,
+                })
+            }
+            RoverOutput::SubgraphPublishBatch {
+                graph_ref,
+                publishes,
+            } => {
+                let publishes: Vec<Value> = publishes
+                    .iter()
+                    .map(|TimedPublish { publish, elapsed }| {
+                        let mut json = publish.get_internal_data_json();
+                        json["elapsed_ms"] = json!(elapsed.as_millis() as u64);
+                        json
+                    })
+                    .collect();
+                json!({
+                    "graph_ref": graph_ref.to_string(),
+                    "subgraphs": publishes,
+                })
+            }
             RoverOutput::SubgraphDeleteResponse {
                 graph_ref: _,
                 subgraph: _,
@@ -519,6 +729,19 @@ impl RoverOutput {
                 json!({ "readme": new_content, "last_updated_time": last_updated_time })
             }
             RoverOutput::EmptySuccess => json!(null),
+            RoverOutput::Skipped {
+                reason,
+                subgraph,
+                graph_ref,
+            } => json!({
+                "skipped": true,
+                "reason": reason.as_deref().map(Style::strip),
+                "subgraph": subgraph,
+                "graph_ref": graph_ref.as_ref().map(GraphRef::to_string),
+            }),
+            RoverOutput::Summary { output, .. } | RoverOutput::UnsavedResponse { output } => {
+                output.get_internal_data_json()
+            }
             RoverOutput::PersistedQueriesPublishResponse(response) => {
                 json!({
                   "revision": response.revision,
@@ -547,6 +770,7 @@ impl RoverOutput {
                 graph_ref,
                 subgraph,
                 publish_response,
+                ..
             } => {
                 if !publish_response.build_errors.is_empty() {
                     Some(RoverError::from(RoverClientError::SubgraphBuildErrors {
@@ -574,6 +798,9 @@ impl RoverOutput {
                     None
                 }
             }
+            RoverOutput::Summary { output, .. } | RoverOutput::UnsavedResponse { output } => {
+                return output.get_internal_error_json()
+            }
             _ => None,
         };
         json!(rover_error)
@@ -582,6 +809,9 @@ impl RoverOutput {
     pub(crate) fn get_json_version(&self) -> JsonVersion {
         match &self {
             Self::CheckWorkflowResponse(_) => JsonVersion::Two,
+            Self::Summary { output, .. } | Self::UnsavedResponse { output } => {
+                output.get_json_version()
+            }
             _ => JsonVersion::default(),
         }
     }
@@ -649,6 +879,8 @@ mod tests {
 
     use anyhow::anyhow;
 
+    use rover_std::test_env::{with_env_vars, with_plain_output};
+
     use crate::options::JsonOutput;
 
     use super::*;
@@ -1194,7 +1426,18 @@ mod tests {
                 variant: "variant".to_string(),
             },
             subgraph: "subgraph".to_string(),
+            previous_routing_url: Some("https://old.example.com".to_string()),
+            routing_url: Some("https://new.example.com".to_string()),
             publish_response: mock_publish_response,
+            attempts: PublishAttempts {
+                routing_url_fetch: 1,
+                publish: 2,
+            },
+            warnings: vec![PublishWarning {
+                code: "LOCAL_ROUTING_URL",
+                message: "The host `localhost` is not routable via the public internet."
+                    .to_string(),
+            }],
         }
         .into();
         let expected_json = json!(
@@ -1207,6 +1450,282 @@ mod tests {
                 "success": true,
                 "launch_url": "test.com/launchurl",
                 "launch_cli_copy": "You can monitor this launch in Apollo Studio: test.com/launchurl",
+                "graph_ref": "graph@variant",
+                "subgraph": "subgraph",
+                "previous_routing_url": "https://old.example.com",
+                "routing_url": "https://new.example.com",
+                "attempts": { "routing_url_fetch": 1, "publish": 2 },
+                "warnings": [
+                    {
+                        "code": "LOCAL_ROUTING_URL",
+                        "message": "The host `localhost` is not routable via the public internet.",
+                    }
+                ],
+            },
+            "error": null
+        });
+        assert_json_eq!(expected_json, actual_json);
+    }
+
+    fn publish_output(build_errors: BuildErrors) -> RoverOutput {
+        RoverOutput::SubgraphPublishResponse {
+            graph_ref: GraphRef {
+                name: "graph".to_string(),
+                variant: "variant".to_string(),
+            },
+            subgraph: "subgraph".to_string(),
+            previous_routing_url: None,
+            routing_url: None,
+            publish_response: SubgraphPublishResponse {
+                api_schema_hash: None,
+                build_errors,
+                supergraph_was_updated: false,
+                subgraph_was_created: false,
+                launch_url: None,
+                launch_cli_copy: None,
+            },
+            attempts: PublishAttempts::default(),
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn successful_publishes_are_confirmed_in_green() {
+        let graph_ref = GraphRef {
+            name: "graph".to_string(),
+            variant: "variant".to_string(),
+        };
+        let confirmation = with_env_vars(&[("CLICOLOR_FORCE", "1")], || {
+            RoverOutput::publish_confirmation("subgraph", &graph_ref)
+        });
+        assert!(confirmation.contains("\u{1b}[32m"));
+        assert!(confirmation.contains("Published subgraph subgraph to graph@variant"));
+
+        let confirmation = with_env_vars(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")], || {
+            RoverOutput::publish_confirmation("subgraph", &graph_ref)
+        });
+        assert!(!confirmation.contains('\u{1b}'));
+        assert!(confirmation.ends_with("Published subgraph subgraph to graph@variant"));
+    }
+
+    #[test]
+    fn plain_error_explanations_are_not_styled() {
+        let explanation = RoverOutput::ErrorExplanation("Run **rover config auth**".to_string());
+        let stdout = with_plain_output(&[], || explanation.get_stdout())
+            .unwrap()
+            .unwrap();
+        assert_eq!(stdout, "Run rover config auth");
+    }
+
+    #[test]
+    fn composition_errors_have_their_own_exit_code() {
+        let failed = publish_output(
+            vec![BuildError::composition_error(
+                None,
+                Some(
+                    "[accounts] User -> A @key selects id, but User.id could not be found"
+                        .to_string(),
+                ),
+                None,
+            )]
+            .into(),
+        );
+        assert_eq!(failed.exit_code(), COMPOSITION_ERRORS_EXIT_CODE);
+        assert_eq!(publish_output(BuildErrors::new()).exit_code(), 0);
+    }
+
+    #[test]
+    fn batches_with_composition_errors_have_their_own_exit_code() {
+        let batch = |outputs: Vec<RoverOutput>| RoverOutput::SubgraphPublishBatch {
+            graph_ref: GraphRef {
+                name: "graph".to_string(),
+                variant: "variant".to_string(),
+            },
+            publishes: outputs
+                .into_iter()
+                .map(|publish| TimedPublish {
+                    publish,
+                    elapsed: Duration::from_secs(1),
+                })
+                .collect(),
+        };
+        let failed = publish_output(vec![BuildError::composition_error(None, None, None)].into());
+
+        assert_eq!(
+            batch(vec![publish_output(BuildErrors::new()), failed]).exit_code(),
+            COMPOSITION_ERRORS_EXIT_CODE
+        );
+        assert_eq!(
+            batch(vec![publish_output(BuildErrors::new())]).exit_code(),
+            0
+        );
+    }
+
+    #[test]
+    fn dry_run_batches_say_what_would_be_published() {
+        let graph_ref = GraphRef {
+            name: "graph".to_string(),
+            variant: "variant".to_string(),
+        };
+        let timed = |publish| TimedPublish {
+            publish,
+            elapsed: Duration::from_secs(1),
+        };
+        let dry_run = timed(RoverOutput::SubgraphPublishDryRun {
+            graph_ref: graph_ref.clone(),
+            subgraph: "accounts".to_string(),
+            routing_url: None,
+            schema_bytes: 28,
+        });
+        let published = timed(publish_output(BuildErrors::new()));
+
+        assert!(
+            RoverOutput::batch_summary(&[&dry_run, &dry_run], &graph_ref)
+                .starts_with("Would publish 2 subgraphs to ")
+        );
+        assert!(RoverOutput::batch_summary(&[&published], &graph_ref)
+            .starts_with("Published 1 subgraphs to "));
+    }
+
+    #[test]
+    fn subgraph_publish_dry_run_json() {
+        let actual_json: JsonOutput = RoverOutput::SubgraphPublishDryRun {
+            graph_ref: GraphRef {
+                name: "name".to_string(),
+                variant: "current".to_string(),
+            },
+            subgraph: "subgraph".to_string(),
+            routing_url: Some("https://subgraph.example.com".to_string()),
+            schema_bytes: 28,
+        }
+        .into();
+        let expected_json = json!(
+        {
+            "json_version": "1",
+            "data": {
+                "graph_ref": "name@current",
+                "subgraph": "subgraph",
+                "routing_url": "https://subgraph.example.com",
+                "schema_bytes": 28,
+                "dry_run": true,
+                "success": true,
+            },
+            "error": null
+        });
+        assert_json_eq!(expected_json, actual_json);
+    }
+
+    #[test]
+    fn skipped_json() {
+        let actual_json: JsonOutput = RoverOutput::Skipped {
+            reason: Some("The schema is unchanged.".to_string()),
+            subgraph: Some("accounts".to_string()),
+            graph_ref: Some(GraphRef {
+                name: "my-graph".to_string(),
+                variant: "current".to_string(),
+            }),
+        }
+        .into();
+        let expected_json = json!(
+        {
+            "json_version": "1",
+            "data": {
+                "skipped": true,
+                "reason": "The schema is unchanged.",
+                "subgraph": "accounts",
+                "graph_ref": "my-graph@current",
+                "success": true,
+            },
+            "error": null
+        });
+        assert_json_eq!(expected_json, actual_json);
+
+        let actual_json: JsonOutput = RoverOutput::Skipped {
+            reason: None,
+            subgraph: None,
+            graph_ref: None,
+        }
+        .into();
+        let expected_json = json!(
+        {
+            "json_version": "1",
+            "data": {
+                "skipped": true,
+                "reason": null,
+                "subgraph": null,
+                "graph_ref": null,
+                "success": true,
+            },
+            "error": null
+        });
+        assert_json_eq!(expected_json, actual_json);
+    }
+
+    #[test]
+    fn skipped_outputs_print_their_reason() {
+        assert_eq!(
+            RoverOutput::skip_notice(Some("The schema is unchanged.")),
+            "The schema is unchanged."
+        );
+        assert_eq!(
+            RoverOutput::skip_notice(None),
+            "There was nothing to do, so this was skipped."
+        );
+        let skipped = RoverOutput::Skipped {
+            reason: None,
+            subgraph: None,
+            graph_ref: None,
+        };
+        assert_eq!(skipped.exit_code(), 0);
+    }
+
+    #[test]
+    fn subgraph_publish_batch_json() {
+        let graph_ref = GraphRef {
+            name: "name".to_string(),
+            variant: "current".to_string(),
+        };
+        let publishes = ["accounts", "products"]
+            .into_iter()
+            .map(|subgraph| TimedPublish {
+                publish: RoverOutput::SubgraphPublishDryRun {
+                    graph_ref: graph_ref.clone(),
+                    subgraph: subgraph.to_string(),
+                    routing_url: None,
+                    schema_bytes: 28,
+                },
+                elapsed: Duration::from_millis(1500),
+            })
+            .collect();
+        let actual_json: JsonOutput = RoverOutput::SubgraphPublishBatch {
+            graph_ref,
+            publishes,
+        }
+        .into();
+        let expected_json = json!(
+        {
+            "json_version": "1",
+            "data": {
+                "graph_ref": "name@current",
+                "subgraphs": [
+                    {
+                        "graph_ref": "name@current",
+                        "subgraph": "accounts",
+                        "routing_url": null,
+                        "schema_bytes": 28,
+                        "dry_run": true,
+                        "elapsed_ms": 1500,
+                    },
+                    {
+                        "graph_ref": "name@current",
+                        "subgraph": "products",
+                        "routing_url": null,
+                        "schema_bytes": 28,
+                        "dry_run": true,
+                        "elapsed_ms": 1500,
+                    }
+                ],
+                "success": true,
             },
             "error": null
         });
@@ -1242,7 +1761,14 @@ mod tests {
                 variant: "current".to_string(),
             },
             subgraph: "subgraph".to_string(),
+            previous_routing_url: Some("https://old.example.com".to_string()),
+            routing_url: Some("https://new.example.com".to_string()),
             publish_response: mock_publish_response,
+            attempts: PublishAttempts {
+                routing_url_fetch: 1,
+                publish: 2,
+            },
+            warnings: Vec::new(),
         }
         .into();
         let expected_json = json!(
@@ -1255,6 +1781,12 @@ mod tests {
                 "success": true,
                 "launch_url": null,
                 "launch_cli_copy": null,
+                "graph_ref": "name@current",
+                "subgraph": "subgraph",
+                "previous_routing_url": "https://old.example.com",
+                "routing_url": "https://new.example.com",
+                "attempts": { "routing_url_fetch": 1, "publish": 2 },
+                "warnings": [],
             },
             "error": {
                 "message": "Encountered 2 build errors while trying to build subgraph \"subgraph\" into supergraph \"name@current\".",
@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Context};
+use camino::{Utf8Path, Utf8PathBuf};
+use rover_std::Fs;
+use serde::Deserialize;
+
+use crate::RoverResult;
+
+/// Lists the subgraphs to publish with `rover subgraph publish --manifest`,
+/// in the order they appear in the file.
+///
+/// ```yaml
+/// accounts:
+///   routing_url: https://accounts.example.com/graphql
+///   schema_path: ./accounts.graphql
+/// products:
+///   schema_path: ./products.graphql
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PublishManifest {
+    pub subgraphs: Vec<ManifestSubgraph>,
+}
+
+/// A subgraph listed in a [`PublishManifest`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ManifestSubgraph {
+    pub name: String,
+    /// when left out, the subgraph keeps the routing url it already has
+    pub routing_url: Option<String>,
+    pub schema_path: Utf8PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestEntry {
+    routing_url: Option<String>,
+    schema_path: Utf8PathBuf,
+}
+
+impl PublishManifest {
+    /// reads a manifest, resolving relative schema paths against the
+    /// directory the manifest is in
+    pub fn load(path: &Utf8Path) -> RoverResult<Self> {
+        let contents = Fs::read_file(path)?;
+        let manifest_dir = path.parent().unwrap_or_else(|| Utf8Path::new("."));
+        Ok(Self::parse(&contents, manifest_dir)
+            .with_context(|| format!("Could not read the publish manifest at `{}`", path))?)
+    }
+
+    fn parse(contents: &str, manifest_dir: &Utf8Path) -> anyhow::Result<Self> {
+        // parsing into a mapping first keeps the subgraphs in the order they were written
+        let mapping: serde_yaml::Mapping = serde_yaml::from_str(contents)?;
+        let subgraphs = mapping
+            .into_iter()
+            .map(|(name, entry)| {
+                let name = name
+                    .as_str()
+                    .ok_or_else(|| anyhow!("subgraph names must be strings, found {:?}", name))?
+                    .to_string();
+                let entry: ManifestEntry = serde_yaml::from_value(entry)
+                    .with_context(|| format!("invalid entry for the `{}` subgraph", name))?;
+                Ok(ManifestSubgraph {
+                    name,
+                    routing_url: entry.routing_url,
+                    schema_path: manifest_dir.join(entry.schema_path),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if subgraphs.is_empty() {
+            return Err(anyhow!("the manifest does not list any subgraphs"));
+        }
+        Ok(Self { subgraphs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_subgraphs_in_order() {
+        let manifest = PublishManifest::parse(
+            r#"
+products:
+  routing_url: https://products.example.com/graphql
+  schema_path: ./products.graphql
+accounts:
+  schema_path: /schemas/accounts.graphql
+"#,
+            Utf8Path::new("supergraph"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.subgraphs,
+            vec![
+                ManifestSubgraph {
+                    name: "products".to_string(),
+                    routing_url: Some("https://products.example.com/graphql".to_string()),
+                    schema_path: Utf8PathBuf::from("supergraph/./products.graphql"),
+                },
+                ManifestSubgraph {
+                    name: "accounts".to_string(),
+                    routing_url: None,
+                    schema_path: Utf8PathBuf::from("/schemas/accounts.graphql"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_errs_on_entries_without_a_schema_path() {
+        let error = PublishManifest::parse(
+            "accounts:\n  routing_url: https://accounts.example.com/graphql\n",
+            Utf8Path::new("."),
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", error).contains("invalid entry for the `accounts` subgraph"));
+    }
+
+    #[test]
+    fn it_errs_on_unknown_fields() {
+        assert!(PublishManifest::parse(
+            "accounts:\n  schema_path: a.graphql\n  url: https://accounts.example.com\n",
+            Utf8Path::new("."),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn it_errs_on_empty_manifests() {
+        assert!(PublishManifest::parse("{}", Utf8Path::new(".")).is_err());
+    }
+}
@@ -4,7 +4,9 @@ mod fetch;
 mod introspect;
 mod lint;
 mod list;
+mod manifest;
 mod publish;
+mod status;
 
 pub use check::Check;
 pub use delete::Delete;
@@ -12,7 +14,7 @@ pub use fetch::Fetch;
 pub use introspect::Introspect;
 pub use lint::Lint;
 pub use list::List;
-pub use publish::Publish;
+pub use publish::{Publish, PublishAttempts};
 
 use clap::Parser;
 use serde::Serialize;
@@ -51,6 +53,9 @@ pub enum Command {
     List(list::List),
 
     /// Publish an updated subgraph schema to the Apollo graph registry and trigger composition in the graph router
+    ///
+    /// Exits with code 3 if the schema is published but the supergraph fails to compose,
+    /// and with code 1 for any other failure, like a network or authentication error.
     Publish(publish::Publish),
 }
 
@@ -60,6 +65,8 @@ impl Subgraph {
         client_config: StudioClientConfig,
         git_context: GitContext,
         checks_timeout_seconds: u64,
+        routing_url_fetch_attempts: usize,
+        quiet: bool,
         output_opts: &OutputOpts,
     ) -> RoverResult<RoverOutput> {
         match &self.command {
@@ -73,7 +80,12 @@ impl Subgraph {
             Command::Fetch(command) => command.run(client_config),
             Command::Lint(command) => command.run(client_config),
             Command::List(command) => command.run(client_config),
-            Command::Publish(command) => command.run(client_config, git_context),
+            Command::Publish(command) => command.run(
+                client_config,
+                git_context,
+                routing_url_fetch_attempts,
+                quiet,
+            ),
         }
     }
 }
@@ -1,18 +1,46 @@
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::io::{self, IsTerminal};
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
+use camino::{Utf8Path, Utf8PathBuf};
 use clap::Parser;
+use reqwest::blocking::Client;
+use reqwest::header::{HeaderName, HeaderValue};
 use reqwest::Url;
+use rover_client::blocking::{GraphQLClient, StudioClient};
+use rover_client::operations::subgraph::fetch::{self, SubgraphFetchInput};
+use rover_client::operations::subgraph::introspect::{self, SubgraphIntrospectInput};
 use rover_client::operations::subgraph::routing_url::{self, SubgraphRoutingUrlInput};
+use rover_client::RoverClientError;
 use serde::Serialize;
+use tracing::Instrument;
 
-use crate::options::{GraphRefOpt, ProfileOpt, SchemaOpt, SubgraphOpt};
+use crate::command::output::TimedPublish;
+use crate::command::subgraph::manifest::PublishManifest;
+use crate::command::subgraph::status::{StatusFormat, StatusStream};
+use crate::error::{PublishWarning, StrictWarnings};
+use crate::options::{
+    validate_subgraph_name, GraphRefOpt, JsonOutput, ProfileOpt, SchemaOpt, SubgraphOpt,
+    SubgraphSchemaRef,
+};
 use crate::utils::client::StudioClientConfig;
+use crate::utils::concurrency;
+use crate::utils::parsers::parse_studio_header;
+use crate::utils::retry::{self, Retry};
+use crate::utils::sdl;
 use crate::{RoverError, RoverErrorSuggestion, RoverOutput, RoverResult};
 
-use rover_client::operations::subgraph::publish::{self, SubgraphPublishInput};
-use rover_client::shared::GitContext;
-use rover_std::Style;
+use houston::RoutingUrlValidation;
+use rover_client::operations::subgraph::publish::{
+    self, SubgraphPublishInput, SubgraphPublishResponse,
+};
+use rover_client::shared::{GitContext, GraphRef};
+use rover_std::style::{terminal_width, wrap};
+use rover_std::url::{is_routable, validate_routing_url, RoutingUrlWarning, SUPPORTED_SCHEMES};
+use rover_std::{prompt, Emoji, Fs, Spinner, Style};
 
 #[derive(Debug, Serialize, Parser)]
 pub struct Publish {
@@ -20,14 +48,51 @@ pub struct Publish {
     graph: GraphRefOpt,
 
     #[clap(flatten)]
-    subgraph: SubgraphOpt,
+    subgraph: Option<SubgraphOpt>,
 
     #[clap(flatten)]
     profile: ProfileOpt,
 
     #[clap(flatten)]
     #[serde(skip_serializing)]
-    schema: SchemaOpt,
+    schema: Option<SchemaOpt>,
+
+    /// Publish the schema that's already published for another subgraph,
+    /// given as `graph@variant:subgraph`, instead of reading one with
+    /// `--schema`. This is useful to promote a schema from one variant to another
+    #[arg(
+        long,
+        value_name = "GRAPH_REF:SUBGRAPH",
+        conflicts_with_all = ["schema", "schema_stdin_timeout", "manifest", "watch"]
+    )]
+    #[serde(skip_serializing)]
+    schema_ref: Option<SubgraphSchemaRef>,
+
+    /// A directory of `.graphql` files to publish together as one schema,
+    /// joined in the order of their names, instead of a single `--schema`.
+    /// A type or directive defined in more than one of them is an error
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["schema", "schema_ref", "schema_stdin_timeout", "manifest", "watch"]
+    )]
+    #[serde(skip_serializing)]
+    schema_from_build: Option<Utf8PathBuf>,
+
+    /// A YAML file mapping the names of subgraphs to publish to their
+    /// `routing_url` and `schema_path`, publishing each of them in turn
+    /// instead of a single subgraph
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["subgraph_name", "schema", "schema_stdin_timeout", "routing_url", "routing_url_from_introspection"]
+    )]
+    #[serde(skip_serializing)]
+    manifest: Option<Utf8PathBuf>,
+
+    /// The most subgraphs from `--manifest` to publish at once
+    #[arg(long, requires = "manifest", default_value = "4")]
+    max_concurrency: NonZeroUsize,
 
     /// Indicate whether to convert a non-federated graph into a subgraph
     #[arg(short, long)]
@@ -35,288 +100,3434 @@ pub struct Publish {
 
     /// Url of a running subgraph that a supergraph can route operations to
     /// (often a deployed subgraph). May be left empty ("") or a placeholder url
-    /// if not running a gateway or router in managed federation mode
+    /// if not running a gateway or router in managed federation mode.
+    /// Passing an empty url clears the subgraph's routing url, while leaving
+    /// this out keeps the one it already has
     #[arg(long)]
     #[serde(skip_serializing)]
     routing_url: Option<String>,
 
+    /// Url of a running subgraph to use as the routing url, which must respond
+    /// to a subgraph introspection query before anything is published
+    #[arg(long, value_name = "ENDPOINT", conflicts_with = "routing_url")]
+    #[serde(skip_serializing)]
+    routing_url_from_introspection: Option<Url>,
+
     /// Bypasses warnings and the prompt to confirm publish when the routing url
     /// is invalid in TTY environment. In a future major version, this flag will
     /// be required to publish in a non-TTY environment. For now it will warn
     /// and publish anyway.
     #[arg(long)]
     allow_invalid_routing_url: bool,
+
+    /// Fail instead of warning when the routing url's host is only reachable
+    /// locally, like `localhost` or `127.0.0.1`, even with `--yes`
+    #[arg(long, conflicts_with = "allow_invalid_routing_url")]
+    reject_local_routing_url: bool,
+
+    /// Automatically answer "yes" to any prompt to confirm the publish.
+    /// Unlike `--allow-invalid-routing-url`, warnings about the routing url
+    /// are still printed, and routing urls that can't be reached still fail
+    /// the publish outside of a TTY.
+    #[arg(long, short = 'y')]
+    yes: bool,
+
+    /// Summarize the publish and ask for confirmation before publishing it,
+    /// unless `--yes` is passed. Has no effect outside of a TTY.
+    #[arg(long, conflicts_with = "manifest")]
+    confirm: bool,
+
+    /// Validate the routing url, read the schema, and authenticate without
+    /// publishing anything, printing what would have been published instead
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Skip publishing if the subgraph's routing url is unchanged and its
+    /// published schema only differs from this one by comments or whitespace
+    #[arg(long)]
+    skip_if_unchanged: bool,
+
+    /// An extra header to send with every request to Apollo Studio, such as
+    /// one required by a gateway in front of it. May be passed more than once,
+    /// ex. -H "X-Gateway-Token: some key"
+    #[arg(value_name = "NAME:VALUE", long = "header", short = 'H', value_parser = parse_studio_header)]
+    #[serde(skip_serializing)]
+    headers: Vec<(HeaderName, HeaderValue)>,
+
+    /// Log the request and response bodies of the calls to Apollo Studio made
+    /// while publishing, at info level, with API keys redacted. Pass
+    /// `--log info` to see them
+    #[arg(long)]
+    #[serde(skip_serializing)]
+    trace_http: bool,
+
+    /// A file to save the result of the publish to as JSON, such as for an
+    /// audit trail. It's written in addition to the usual output
+    #[arg(long, value_name = "PATH")]
+    #[serde(skip_serializing)]
+    response_file: Option<Utf8PathBuf>,
+
+    /// Create any missing parent directories of `--response-file`
+    #[arg(long, requires = "response_file")]
+    create_parent_dirs: bool,
+
+    /// After publishing, keep watching the `--schema` file and publish it
+    /// again whenever it changes, until interrupted. Any prompt to confirm
+    /// the publish is answered "yes"
+    #[arg(long, conflicts_with_all = ["manifest", "dry_run", "confirm", "response_file"])]
+    watch: bool,
+
+    /// Don't send the git branch, commit, author, or remote url to Studio
+    /// with the publish, whatever is detected locally or in CI
+    #[arg(long)]
+    no_git_context: bool,
+
+    /// Publish the schema without checking it for syntax errors first,
+    /// leaving Studio to reject it if it can't be parsed
+    #[arg(long)]
+    skip_validation: bool,
+
+    /// Reformat the schema before publishing it, giving every field its own
+    /// line with a consistent indent. Descriptions, directives, and comments
+    /// are kept exactly as written
+    #[arg(long, conflicts_with = "skip_validation")]
+    normalize: bool,
+
+    /// How many times to retry fetching the routing url and publishing when
+    /// they fail for a reason that might go away on its own, like a dropped
+    /// connection or a server error. Defaults to 2, and 0 tries each request
+    /// once. Overrides `APOLLO_ROUTING_URL_FETCH_ATTEMPTS`
+    #[arg(long, value_name = "N")]
+    max_retries: Option<usize>,
+
+    /// Print a single line with the status of the publish, the subgraph, the
+    /// graph ref and how many composition errors there were, instead of the
+    /// usual output, for CI dashboards. Informational messages are left out
+    /// like with `--quiet`, and `--format json` is unaffected
+    #[arg(long, conflicts_with_all = ["manifest", "watch"])]
+    summary_only: bool,
+
+    /// After publishing, check that the routing url can be connected to by
+    /// sending it a `HEAD` request, warning if it can't. Hosts that are only
+    /// reachable locally, like `localhost`, aren't checked
+    #[arg(long)]
+    verify_reachable: bool,
+
+    /// How to report the progress of the publish on stderr. `json` writes a
+    /// JSON event per line when each phase of a publish starts and ends, and
+    /// with the result of each subgraph, in place of the usual messages. The
+    /// result of the command is still printed to stdout
+    #[arg(long, value_enum, default_value_t = StatusFormat::Text)]
+    status_format: StatusFormat,
+
+    /// Fail instead of warning about anything that would be included in the
+    /// output's warnings, like a routing url that only works locally or a
+    /// schema with no `@key` directives, so that CI fails when anything is off.
+    /// The error has the same messages the warnings would have had, and
+    /// `--verify-reachable` checks the routing url before publishing instead
+    #[arg(long, conflicts_with = "allow_invalid_routing_url")]
+    strict: bool,
+}
+
+/// what every subgraph publish in a single run of the command shares
+struct PublishSettings<'a> {
+    client_config: &'a StudioClientConfig,
+    git_context: &'a GitContext,
+    routing_url_fetch_attempts: usize,
+    /// the most times the publish mutation is sent
+    publish_attempts: usize,
+    quiet: bool,
+    /// whether warnings about the routing url can prompt, which they never do
+    /// while the subgraphs in a manifest are being published
+    interactive: bool,
+    /// whether a spinner is shown during network calls, which is only done
+    /// for a single publish to a terminal
+    progress: bool,
+    /// how strictly routing urls are checked, from the flags or the config
+    routing_url_validation: RoutingUrlValidation,
+    /// where progress events go with `--status-format json`
+    status: &'a StatusStream,
+}
+
+impl PublishSettings<'_> {
+    /// where to write warnings, which are informational unless a prompt is
+    /// going to ask about them
+    fn warnings(&self, auto_confirm: bool) -> Box<dyn io::Write> {
+        if self.quiet && (auto_confirm || !self.interactive) {
+            Box::new(io::sink())
+        } else {
+            Box::new(io::stderr())
+        }
+    }
+}
+
+/// How many times each request of a subgraph publish was sent, retries
+/// included, so that flaky networks show up in the output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PublishAttempts {
+    /// attempts at fetching the current routing url
+    pub routing_url_fetch: usize,
+    /// attempts at the publish mutation
+    pub publish: usize,
+}
+
+/// a subgraph publish that's ready to send its publish mutation, from
+/// [`Publish::prepare_publish`]
+struct PendingPublish {
+    subgraph: String,
+    client: StudioClient,
+    /// the span for the whole publish, which each stage enters
+    span: tracing::Span,
+    input: SubgraphPublishInput,
+    previous_routing_url: Option<String>,
+    routing_url: Option<String>,
+    attempts: PublishAttempts,
+    warnings: Vec<PublishWarning>,
 }
 
+/// how far [`Publish::prepare_publish`] got
+enum Prepared {
+    /// there's nothing to publish, like with `--dry-run`
+    Done(RoverOutput),
+    /// the publish mutation still has to be sent
+    Publish(Box<PendingPublish>),
+}
+
+/// where the schema for a publish comes from
+#[derive(Debug, Clone, Copy)]
+enum SchemaInput<'a> {
+    /// `--schema`, or a schema file listed in a manifest
+    Source(&'a SchemaOpt),
+    /// the schema published for another subgraph, from `--schema-ref`
+    Registry(&'a SubgraphSchemaRef),
+    /// the `.graphql` files in the directory passed with `--schema-from-build`
+    Build(&'a Utf8Path),
+}
+
+/// What to do about a routing url before publishing it, as decided by
+/// [`Publish::check_routing_url`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoutingUrlCheck {
+    /// the routing url can be published as it is
+    Ok,
+    /// the routing url can be published, but there's something worth knowing about it
+    Note(String),
+    /// the routing url can be published, but looks like a mistake
+    Warning(String),
+    /// the user has to be asked this before the routing url is published
+    NeedsConfirmation(String),
+    /// the routing url can't be published without `--allow-invalid-routing-url`
+    HardError(String),
+}
+
+/// how long to wait for more changes to the schema in watch mode before
+/// publishing it, so that a save that writes the file several times only
+/// publishes once
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// how long `--verify-reachable` waits for the routing url to answer, which is
+/// much less than `--client-timeout` since the publish has already happened
+const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(5);
+
 impl Publish {
+    /// whether prompts to confirm the publish are answered "yes" without asking
+    fn auto_confirm(&self) -> bool {
+        self.yes || self.watch
+    }
+
     pub fn run(
         &self,
         client_config: StudioClientConfig,
         git_context: GitContext,
+        routing_url_fetch_attempts: usize,
+        quiet: bool,
     ) -> RoverResult<RoverOutput> {
-        // if --allow-invalid-routing-url is not provided, we need to inspect
-        // the URL and possibly prompt the user to publish
-        if !self.allow_invalid_routing_url {
-            Self::handle_maybe_invalid_routing_url(
-                &self.routing_url,
-                &mut io::stderr(),
-                &mut io::stdin(),
-                io::stderr().is_terminal() && io::stdin().is_terminal(),
-            )?;
+        let client_config = client_config
+            .with_headers(self.headers.iter().cloned().collect())
+            .with_trace_http(self.trace_http);
+        let git_context = self.git_context(git_context);
+        self.profile.validate(&client_config.config)?;
+        let status = StatusStream::new(self.status_format);
+        let quiet = quiet || self.summary_only || status.is_enabled();
+        let settings = PublishSettings {
+            client_config: &client_config,
+            git_context: &git_context,
+            routing_url_fetch_attempts: self
+                .max_retries
+                .map_or(routing_url_fetch_attempts, |retries| retries + 1),
+            publish_attempts: self
+                .max_retries
+                .map_or(retry::DEFAULT_ATTEMPTS, |retries| retries + 1),
+            quiet,
+            interactive: io::stderr().is_terminal() && io::stdin().is_terminal(),
+            progress: !quiet,
+            routing_url_validation: self
+                .routing_url_validation(client_config.routing_url_validation()?),
+            status: &status,
+        };
+        let output = match &self.manifest {
+            Some(manifest) => self.publish_manifest(manifest, &settings),
+            None => self.publish_single_subgraph(&settings),
         }
+        .map_err(|error| match &self.subgraph {
+            Some(subgraph) if self.summary_only => {
+                let line =
+                    Self::summary_line(&subgraph.subgraph_name, &self.graph.graph_ref, Err(&error));
+                error.with_summary(line)
+            }
+            _ => error,
+        })?;
+        let saved = match &self.response_file {
+            Some(response_file) => self.write_response_file(&output, response_file)?,
+            None => true,
+        };
+        let output = match &self.subgraph {
+            Some(subgraph) if self.summary_only => RoverOutput::Summary {
+                line: Self::summary_line(
+                    &subgraph.subgraph_name,
+                    &self.graph.graph_ref,
+                    Ok(&output),
+                ),
+                output: Box::new(output),
+            },
+            _ => output,
+        };
+        if saved {
+            Ok(output)
+        } else {
+            Ok(RoverOutput::UnsavedResponse {
+                output: Box::new(output),
+            })
+        }
+    }
 
-        let client = client_config.get_authenticated_client(&self.profile)?;
-
-        if self.routing_url.is_none() {
-            let fetch_response = routing_url::run(
-                SubgraphRoutingUrlInput {
-                    graph_ref: self.graph.graph_ref.clone(),
-                    subgraph_name: self.subgraph.subgraph_name.clone(),
-                },
-                &client,
-            )?;
+    /// the single line printed with `--summary-only`, like
+    /// `composition failed | accounts | my-graph@current | 2 composition errors`
+    fn summary_line(
+        subgraph: &str,
+        graph_ref: &GraphRef,
+        output: Result<&RoverOutput, &RoverError>,
+    ) -> String {
+        let (status, composition_errors) = match output {
+            Ok(RoverOutput::SubgraphPublishResponse {
+                publish_response, ..
+            }) if !publish_response.build_errors.is_empty() => {
+                ("composition failed", publish_response.build_errors.len())
+            }
+            Ok(RoverOutput::SubgraphPublishDryRun { .. }) => ("dry run", 0),
+            Ok(RoverOutput::Skipped { .. }) => ("skipped", 0),
+            Ok(_) => ("success", 0),
+            Err(_) => ("failed", 0),
+        };
+        let mut line = format!(
+            "{} | {} | {}",
+            status,
+            subgraph,
+            Style::Link.paint(graph_ref.to_string())
+        );
+        match composition_errors {
+            0 => {}
+            1 => line.push_str(" | 1 composition error"),
+            count => line.push_str(&format!(" | {} composition errors", count)),
+        }
+        line
+    }
 
-            Self::handle_maybe_invalid_routing_url(
-                &Some(fetch_response),
-                &mut io::stderr(),
-                &mut io::stdin(),
-                io::stderr().is_terminal() && io::stdin().is_terminal(),
-            )?;
+    /// how strictly routing urls are checked: `--allow-invalid-routing-url` and
+    /// `--reject-local-routing-url` always win over the configured `default`
+    fn routing_url_validation(&self, default: RoutingUrlValidation) -> RoutingUrlValidation {
+        if self.allow_invalid_routing_url {
+            RoutingUrlValidation::Off
+        } else if self.reject_local_routing_url {
+            RoutingUrlValidation::Strict
+        } else {
+            default
         }
+    }
 
-        eprintln!(
-            "Publishing SDL to {} (subgraph: {}) using credentials from the {} profile.",
-            Style::Link.paint(self.graph.graph_ref.to_string()),
-            Style::Link.paint(&self.subgraph.subgraph_name),
-            Style::Command.paint(&self.profile.profile_name)
-        );
+    /// the git context to send with the publish, which is empty with `--no-git-context`
+    fn git_context(&self, detected: GitContext) -> GitContext {
+        if self.no_git_context {
+            tracing::info!("not sending the git context because of --no-git-context");
+            GitContext::empty()
+        } else {
+            detected
+        }
+    }
 
-        let schema = self
-            .schema
-            .read_file_descriptor("SDL", &mut std::io::stdin())?;
+    /// publishes the subgraph passed with `--name` and `--schema`
+    fn publish_single_subgraph(&self, settings: &PublishSettings) -> RoverResult<RoverOutput> {
+        // clap only lets these be left out when --manifest is passed
+        let schema = match (&self.schema, &self.schema_ref, &self.schema_from_build) {
+            (Some(schema), _, _) => Some(SchemaInput::Source(schema)),
+            (None, Some(schema_ref), _) => Some(SchemaInput::Registry(schema_ref)),
+            (None, None, Some(dir)) => Some(SchemaInput::Build(dir)),
+            (None, None, None) => None,
+        };
+        let (Some(subgraph), Some(schema)) = (&self.subgraph, schema) else {
+            return Err(anyhow!(
+                "`--name` and one of `--schema`, `--schema-ref` or `--schema-from-build` are required unless `--manifest` is passed."
+            )
+            .into());
+        };
+        subgraph.validate()?;
 
-        tracing::debug!("Publishing \n{}", &schema);
+        let provided_routing_url = match &self.routing_url_from_introspection {
+            Some(endpoint) => Some(
+                tracing::debug_span!("introspect_routing_url", %endpoint).in_scope(|| {
+                    Self::introspect_routing_url(
+                        endpoint,
+                        &settings.client_config.get_reqwest_client()?,
+                    )
+                })?,
+            ),
+            None => self.routing_url.clone(),
+        };
+        self.check_provided_routing_url(&provided_routing_url, settings)?;
 
-        let publish_response = publish::run(
-            SubgraphPublishInput {
-                graph_ref: self.graph.graph_ref.clone(),
-                subgraph: self.subgraph.subgraph_name.clone(),
-                url: self.routing_url.clone(),
+        if !self.watch {
+            return self.publish_subgraph(
+                &subgraph.subgraph_name,
+                provided_routing_url,
                 schema,
-                git_context,
-                convert_to_federated_graph: self.convert,
-            },
-            &client,
-        )?;
+                settings,
+            );
+        }
 
-        Ok(RoverOutput::SubgraphPublishResponse {
-            graph_ref: self.graph.graph_ref.clone(),
-            subgraph: self.subgraph.subgraph_name.clone(),
-            publish_response,
-        })
+        let Some(schema_path) = (match schema {
+            SchemaInput::Source(schema) => schema.file_path(),
+            SchemaInput::Registry(_) | SchemaInput::Build(_) => None,
+        }) else {
+            return Err(RoverError::new(anyhow!(
+                "`--watch` can only watch a schema that is read from a file."
+            ))
+            .with_suggestion(RoverErrorSuggestion::ProvideSchemaFile));
+        };
+        // the watch starts before the first publish so that no change is missed
+        let changes = Fs::watch_file_debounced(schema_path, WATCH_DEBOUNCE)?;
+        let checksum = Fs::sha256(schema_path)?;
+        let output = self.publish_subgraph(
+            &subgraph.subgraph_name,
+            provided_routing_url.clone(),
+            schema,
+            settings,
+        )?;
+        if !settings.quiet {
+            eprintln!("{}", Self::watch_status(&output));
+        }
+        self.republish_on_change(
+            &subgraph.subgraph_name,
+            &provided_routing_url,
+            schema_path,
+            checksum,
+            &changes,
+            settings,
+        );
+        Ok(RoverOutput::EmptySuccess)
     }
 
-    fn handle_maybe_invalid_routing_url(
-        maybe_invalid_routing_url: &Option<String>,
-        // For testing purposes, we pass in stub `Write`er and `Read`ers to
-        // simulate input and verify output.
-        writer: &mut impl io::Write,
-        reader: &mut impl io::Read,
-        // Simulate a CI environment (non-TTY) for testing
-        is_atty: bool,
-    ) -> RoverResult<()> {
-        // if a --routing-url is provided AND the URL is unparsable,
-        // we need to warn and prompt the user, else we can assume a publish
-        if let Some(routing_url) = maybe_invalid_routing_url {
-            match Url::parse(routing_url) {
-                Ok(parsed_url) => {
-                    tracing::debug!("Parsed URL: {}", parsed_url.to_string());
-                    let reason = format!("`{}` is not a valid routing URL. The `{}` protocol is not supported by the router. Valid protocols are `http` and `https`.", Style::Link.paint(routing_url), &parsed_url.scheme());
-                    if !["http", "https"].contains(&parsed_url.scheme()) {
-                        if is_atty {
-                            Self::prompt_for_publish(
-                                format!("{reason} Continuing the publish will make this subgraph unreachable by your supergraph. Would you still like to publish?").as_str(),
-                                reader,
-                                writer,
-                            )?;
-                        } else {
-                            Self::non_tty_hard_error(&reason)?;
-                        }
-                    } else if let Some(host) = parsed_url.host_str() {
-                        if ["localhost", "127.0.0.1"].contains(&host) {
-                            let reason = format!("The host `{}` is not routable via the public internet. Continuing the publish will make this subgraph reachable in local environments only.", host);
-                            if is_atty {
-                                Self::prompt_for_publish(
-                                    format!("{reason} Would you still like to publish?").as_str(),
-                                    reader,
-                                    writer,
-                                )?;
-                            } else {
-                                Self::non_tty_warn_about_local_url(&reason, writer)?;
-                            }
-                        }
-                    }
+    /// publishes the schema at `schema_path` again for every change in
+    /// `changes`, skipping changes that leave its contents as they were.
+    /// failed publishes are reported without ending the watch.
+    fn republish_on_change(
+        &self,
+        subgraph: &str,
+        routing_url: &Option<String>,
+        schema_path: &Utf8Path,
+        mut checksum: String,
+        changes: impl IntoIterator<Item = Utf8PathBuf>,
+        settings: &PublishSettings,
+    ) {
+        // each publish gets a one-line status instead of the usual output
+        let cycle_settings = PublishSettings {
+            quiet: true,
+            interactive: false,
+            progress: false,
+            ..*settings
+        };
+        let schema = SchemaOpt::from_file(schema_path.to_path_buf());
+        if !settings.quiet {
+            eprintln!(
+                "{}Watching {} for changes...",
+                Emoji::Watch,
+                Style::Path.paint(schema_path)
+            );
+        }
+        for _ in changes {
+            match Fs::sha256(schema_path) {
+                Ok(new_checksum) if new_checksum == checksum => {
+                    tracing::info!("{} is unchanged, not publishing it", schema_path);
+                    continue;
                 }
-                Err(parse_error) => {
-                    tracing::debug!("Parse error: {}", parse_error.to_string());
-                    let reason = format!(
-                        "`{}` is not a valid routing URL.",
-                        Style::Link.paint(routing_url)
-                    );
-                    if is_atty {
-                        Self::prompt_for_publish(
-                        format!("{} Continuing the publish will make this subgraph unreachable by your supergraph. Would you still like to publish?", &reason).as_str(),
-                            reader,
-                            writer,
-                        )?;
-                    } else {
-                        Self::non_tty_hard_error(&reason)?;
-                    }
+                Ok(new_checksum) => checksum = new_checksum,
+                // the file can briefly disappear while an editor saves it
+                Err(e) => {
+                    tracing::info!("could not read {}: {}", schema_path, e);
+                    continue;
+                }
+            }
+            match self.publish_subgraph(
+                subgraph,
+                routing_url.clone(),
+                SchemaInput::Source(&schema),
+                &cycle_settings,
+            ) {
+                Ok(output) if !settings.quiet => eprintln!("{}", Self::watch_status(&output)),
+                Ok(_) => {}
+                Err(error) => {
+                    let _ = error.print();
                 }
             }
         }
-        Ok(())
     }
 
-    pub fn prompt_for_publish(
-        message: &str,
-        reader: &mut impl io::Read,
-        writer: &mut impl io::Write,
-    ) -> RoverResult<Option<bool>> {
-        write!(writer, "{} [y/N] ", message)?;
-        let mut response = [0];
-        reader.read_exact(&mut response)?;
-        if std::str::from_utf8(&response).unwrap().to_lowercase() == *"y" {
-            Ok(Some(true))
-        } else {
-            Err(anyhow!("You cancelled a subgraph publish due to an invalid routing url.").into())
+    /// a one-line summary of a publish in watch mode
+    fn watch_status(output: &RoverOutput) -> String {
+        match output {
+            RoverOutput::SubgraphPublishResponse {
+                graph_ref,
+                subgraph,
+                publish_response,
+                ..
+            } if !publish_response.build_errors.is_empty() => format!(
+                "{}Published {} to {} with {}",
+                Emoji::Warn,
+                Style::Link.paint(subgraph),
+                Style::Link.paint(graph_ref.to_string()),
+                publish_response.build_errors.length_string()
+            ),
+            RoverOutput::SubgraphPublishResponse {
+                graph_ref,
+                subgraph,
+                ..
+            } => format!(
+                "{}Published {} to {}",
+                Emoji::Success,
+                Style::Link.paint(subgraph),
+                Style::Link.paint(graph_ref.to_string())
+            ),
+            _ => format!("{}Published", Emoji::Success),
         }
     }
 
-    pub fn non_tty_hard_error(reason: &str) -> RoverResult<()> {
-        Err(RoverError::new(anyhow!("{reason}"))
-            .with_suggestion(RoverErrorSuggestion::AllowInvalidRoutingUrlOrSpecifyValidUrl))
+    /// rejects a schema with nothing but whitespace in it, which Studio would
+    /// otherwise reject with a much less helpful error
+    fn check_schema_is_not_empty(
+        subgraph: &str,
+        schema: &str,
+        input: SchemaInput,
+    ) -> RoverResult<()> {
+        if !schema.trim().is_empty() {
+            return Ok(());
+        }
+        let suggestion = match input {
+            SchemaInput::Source(schema_opt) if schema_opt.reads_from_stdin() => {
+                "Make sure the command you are piping to Rover outputs the schema.".to_string()
+            }
+            SchemaInput::Source(schema_opt) => match schema_opt.file_path() {
+                Some(path) => format!(
+                    "Check that {} is the path to the schema, and that it's been saved.",
+                    Style::Path.paint(path)
+                ),
+                None => "Check that the URL passed to `--schema` serves the schema.".to_string(),
+            },
+            SchemaInput::Registry(_) => format!(
+                "Check that a schema has been published for the subgraph passed to {}.",
+                Style::Command.paint("`--schema-ref`")
+            ),
+            SchemaInput::Build(dir) => format!(
+                "Check that the `.graphql` files in {} contain the schema.",
+                Style::Path.paint(dir)
+            ),
+        };
+        Err(RoverError::new(anyhow!(
+            "The schema for the {} subgraph is empty, so it was not published.",
+            subgraph
+        ))
+        .with_suggestion(RoverErrorSuggestion::Adhoc(suggestion)))
     }
 
-    pub fn non_tty_warn_about_local_url(
-        reason: &str,
-        writer: &mut dyn io::Write,
-    ) -> RoverResult<()> {
-        writeln!(writer, "{} {reason}", Style::WarningPrefix.paint("WARN:"),)?;
-        Ok(())
+    /// checks that the schema can be parsed before it's sent to Studio,
+    /// pointing at the line and column of each syntax error
+    fn validate_schema(subgraph: &str, schema: &str) -> RoverResult<()> {
+        let errors = sdl::syntax_errors(schema);
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(Self::syntax_error(subgraph, schema, &errors))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::command::subgraph::Publish;
+    /// each error is shown with the lines around it, with a caret under where it was found
+    fn syntax_error(subgraph: &str, schema: &str, errors: &[sdl::SyntaxError]) -> RoverError {
+        let errors: Vec<String> = errors
+            .iter()
+            .map(|error| {
+                let snippet = rover_std::snippet(schema, error.line, error.column)
+                    .lines()
+                    .map(|line| format!("    {}", line))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("  {}\n{}", error, snippet)
+            })
+            .collect();
+        RoverError::new(anyhow!(
+            "The schema for the {} subgraph has syntax errors:\n{}",
+            subgraph,
+            errors.join("\n")
+        ))
+        .with_suggestion(RoverErrorSuggestion::Adhoc(format!(
+            "Fix the errors at the lines and columns above, or pass {} to leave checking the schema to Studio.",
+            Style::Command.paint("`--skip-validation`")
+        )))
+    }
 
-    #[test]
-    fn test_confirm_invalid_url_publish() {
-        let mut input = "y".as_bytes();
-        let mut output: Vec<u8> = Vec::new();
-        let result = Publish::handle_maybe_invalid_routing_url(
-            &Some("invalid-url".to_string()),
-            &mut output,
-            &mut input,
-            true,
-        );
+    /// warns about a schema that seems to have forgotten its `@key`
+    /// directives, which only breaks once the supergraph is composed
+    fn missing_keys_warning(subgraph: &str, schema: &str) -> Option<PublishWarning> {
+        let entities: Vec<String> = sdl::unkeyed_entities(schema)
+            .iter()
+            .map(|name| format!("`{}`", name))
+            .collect();
+        if entities.is_empty() {
+            return None;
+        }
+        Some(PublishWarning {
+            code: "MISSING_KEY_DIRECTIVES",
+            message: format!(
+                "The schema for the `{}` subgraph has no `@key` directives, but {} {} an `id` field like an entity would. Without a `@key`, other subgraphs can't reference {}, which can break composition.",
+                subgraph,
+                entities.join(", "),
+                if entities.len() == 1 { "has" } else { "have" },
+                if entities.len() == 1 { "it" } else { "them" },
+            ),
+        })
+    }
 
-        assert!(result.is_ok());
-        assert!(input.is_empty());
-        assert!(std::str::from_utf8(&output).unwrap().contains("is not a valid routing URL. Continuing the publish will make this subgraph unreachable by your supergraph. Would you still like to publish?"));
+    /// with `--strict`, fails with `warnings` instead of letting them be printed
+    /// and included in the output. the error keeps their messages, and
+    /// `--format json` has all of them in the error's details
+    fn fail_if_strict(
+        &self,
+        warnings: impl IntoIterator<Item = PublishWarning>,
+    ) -> RoverResult<()> {
+        let warnings: Vec<PublishWarning> = warnings.into_iter().collect();
+        if !self.strict || warnings.is_empty() {
+            return Ok(());
+        }
+        Err(
+            RoverError::new(StrictWarnings(warnings)).with_suggestion(RoverErrorSuggestion::Adhoc(
+                format!(
+                    "Fix the problems above, or publish without {} to only be warned about them.",
+                    Style::Command.paint("`--strict`")
+                ),
+            )),
+        )
     }
 
-    #[test]
-    fn test_deny_invalid_url_publish() {
-        let mut input = "n".as_bytes();
-        let mut output: Vec<u8> = Vec::new();
-        let result = Publish::handle_maybe_invalid_routing_url(
-            &Some("invalid-url".to_string()),
-            &mut output,
-            &mut input,
-            true,
+    /// saves the result of the publish as JSON to `--response-file`, returning
+    /// whether it was written. the publish has already happened by the time
+    /// this fails, so rather than failing too, a warning saying what happened is
+    /// printed and the output is still printed as usual
+    fn write_response_file(&self, output: &RoverOutput, path: &Utf8Path) -> RoverResult<bool> {
+        let response = JsonOutput::from(output.clone()).to_string();
+        let parent = path.parent().filter(|parent| !parent.as_str().is_empty());
+        let written = match parent {
+            Some(parent) if self.create_parent_dirs => Fs::create_dir_all(parent),
+            _ => Ok(()),
+        }
+        .and_then(|_| Fs::write_file_atomic(path, response));
+        let Err(e) = written else {
+            return Ok(true);
+        };
+
+        let graph_ref = &self.graph.graph_ref;
+        let (what_happened, suggestion) = match output {
+            RoverOutput::SubgraphPublishDryRun { .. } => (
+                format!("The dry run of the publish to {} finished", graph_ref),
+                "Make sure",
+            ),
+            RoverOutput::Skipped { .. } => (
+                format!("Nothing needed to be published to {}", graph_ref),
+                "Make sure",
+            ),
+            _ => (
+                format!("The publish to {} succeeded", graph_ref),
+                "There is no need to publish again. Make sure",
+            ),
+        };
+        let mut message = format!(
+            "{}, but its response could not be written to {}: {}. {} {} is a writable directory",
+            what_happened,
+            path,
+            e,
+            suggestion,
+            Style::Path.paint(parent.map_or(".", Utf8Path::as_str))
         );
+        if self.create_parent_dirs {
+            message.push('.');
+        } else {
+            message.push_str(&format!(
+                ", or pass {} to create it.",
+                Style::Command.paint("`--create-parent-dirs`")
+            ));
+        }
+        Self::write_warning(&message, &mut io::stderr())?;
+        Ok(false)
+    }
 
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("You cancelled a subgraph publish due to an invalid routing url."));
-        assert!(input.is_empty());
-        assert!(std::str::from_utf8(&output).unwrap().contains("is not a valid routing URL. Continuing the publish will make this subgraph unreachable by your supergraph. Would you still like to publish?"));
+    /// if --allow-invalid-routing-url is not provided, we need to inspect
+    /// the URL and possibly prompt the user to publish
+    fn check_provided_routing_url(
+        &self,
+        provided_routing_url: &Option<String>,
+        settings: &PublishSettings,
+    ) -> RoverResult<()> {
+        // with `--strict`, the warning fails the publish once every warning
+        // about it is known, instead of being prompted about here
+        if self.strict && Self::routing_url_warning(provided_routing_url).is_some() {
+            return Ok(());
+        }
+        if settings.routing_url_validation == RoutingUrlValidation::Off {
+            return Ok(());
+        }
+        Self::handle_maybe_invalid_routing_url(
+            provided_routing_url,
+            &mut settings.warnings(self.auto_confirm()),
+            &mut prompt::stdin(),
+            settings.interactive,
+            self.auto_confirm(),
+            settings.routing_url_validation == RoutingUrlValidation::Strict,
+        )
     }
 
-    #[test]
-    fn test_invalid_scheme() {
-        let mut input = "y".as_bytes();
-        let mut output: Vec<u8> = Vec::new();
-        let result = Publish::handle_maybe_invalid_routing_url(
-            &Some("ftp://invalid-scheme".to_string()),
-            &mut output,
-            &mut input,
-            true,
-        );
+    /// publishes the subgraphs in a manifest, up to `--max-concurrency` at a
+    /// time, stopping once any of them fails. every subgraph is checked, and
+    /// anything that needs asking is asked, before any of them is published
+    fn publish_manifest(
+        &self,
+        manifest: &Utf8Path,
+        settings: &PublishSettings,
+    ) -> RoverResult<RoverOutput> {
+        let manifest = PublishManifest::load(manifest)?;
+        for subgraph in &manifest.subgraphs {
+            validate_subgraph_name(&subgraph.name)?;
+        }
+        // any prompts about routing urls are answered up front, so that
+        // concurrent publishes never prompt over each other
+        for subgraph in &manifest.subgraphs {
+            self.check_provided_routing_url(&subgraph.routing_url, settings)?;
+        }
+        let settings = PublishSettings {
+            interactive: false,
+            progress: false,
+            ..*settings
+        };
 
-        assert!(result.is_ok());
-        assert!(input.is_empty());
-        assert!(std::str::from_utf8(&output).unwrap().contains(
-            "is not a valid routing URL. The `ftp` protocol is not supported by the router. Valid protocols are `http` and `https`."
-        ));
-    }
+        let async_client = settings.client_config.get_async_reqwest_client()?;
+        let mut prepared = Vec::with_capacity(manifest.subgraphs.len());
+        let mut failure = None;
+        for subgraph in &manifest.subgraphs {
+            let started = Instant::now();
+            let result = self
+                .prepare_publish(
+                    &subgraph.name,
+                    subgraph.routing_url.clone(),
+                    SchemaInput::Source(&SchemaOpt::from_file(subgraph.schema_path.clone())),
+                    &settings,
+                )
+                .and_then(|prepared| match prepared {
+                    Prepared::Publish(pending) => {
+                        // only the publish mutations are sent without blocking,
+                        // so whether the graph is federated is checked up front
+                        pending
+                            .span
+                            .in_scope(|| {
+                                Retry::new(settings.publish_attempts).run(
+                                    || publish::check_convert(&pending.input, &pending.client),
+                                    retry::is_transient,
+                                )
+                            })
+                            .map_err(Self::with_network_suggestion)?;
+                        let pending = *pending;
+                        Ok(Prepared::Publish(Box::new(PendingPublish {
+                            client: pending.client.with_async_client(async_client.clone()),
+                            ..pending
+                        })))
+                    }
+                    done => Ok(done),
+                });
+            match result {
+                Ok(prepared_publish) => {
+                    if let Prepared::Done(output) = &prepared_publish {
+                        settings.status.result(&subgraph.name, Ok(output));
+                    }
+                    prepared.push((prepared_publish, started.elapsed()));
+                }
+                Err(error) => {
+                    settings.status.result(&subgraph.name, Err(&error));
+                    failure = Some(error);
+                    break;
+                }
+            }
+        }
 
-    #[test]
-    fn test_localhost_tty() {
-        let mut input = "y".as_bytes();
-        let mut output: Vec<u8> = Vec::new();
-        let result = Publish::handle_maybe_invalid_routing_url(
-            &Some("http://localhost:8000".to_string()),
-            &mut output,
-            &mut input,
-            true,
-        );
+        let pending: Vec<&PendingPublish> = prepared
+            .iter()
+            .filter_map(|(prepared, _)| match prepared {
+                Prepared::Publish(pending) => Some(pending.as_ref()),
+                Prepared::Done(_) => None,
+            })
+            .collect();
+        let mut sent = if failure.is_none() {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            // every publish goes to the same host
+            let max_concurrency = self
+                .max_concurrency
+                .min(settings.client_config.max_requests_per_host());
+            runtime.block_on(concurrency::run_bounded(
+                &pending,
+                max_concurrency.get(),
+                |pending| async {
+                    let started = Instant::now();
+                    let (result, attempts) = self.send_publish_async(pending, &settings).await;
+                    (result, attempts, started.elapsed())
+                },
+                |(result, ..)| result.is_err(),
+            ))
+        } else {
+            Vec::new()
+        }
+        .into_iter();
 
-        assert!(result.is_ok());
-        assert!(input.is_empty());
-        assert!(std::str::from_utf8(&output).unwrap().contains(
-            "The host `localhost` is not routable via the public internet. Continuing the publish will make this subgraph reachable in local environments only."
-        ));
-    }
+        let mut results = Vec::with_capacity(manifest.subgraphs.len());
+        for (prepared, elapsed) in prepared {
+            let result = match prepared {
+                Prepared::Done(publish) => Some(Ok(TimedPublish { publish, elapsed })),
+                Prepared::Publish(mut pending) => match sent.next().flatten() {
+                    Some((result, attempts, sending)) => {
+                        let started = Instant::now();
+                        pending.attempts.publish = attempts;
+                        let subgraph = pending.subgraph.clone();
+                        let result = result.and_then(|response| {
+                            self.finish_publish(*pending, response, &settings)
+                        });
+                        settings.status.result(&subgraph, result.as_ref());
+                        Some(result.map(|publish| TimedPublish {
+                            publish,
+                            elapsed: elapsed + sending + started.elapsed(),
+                        }))
+                    }
+                    None => None,
+                },
+            };
+            results.push(result);
+        }
+        if let Some(error) = failure {
+            results.push(Some(Err(error)));
+        }
 
-    #[test]
-    fn test_localhost_no_tty() {
-        let mut input: &[u8] = &[];
-        let mut output: Vec<u8> = Vec::new();
-        let result = Publish::handle_maybe_invalid_routing_url(
-            &Some("http://localhost:8000".to_string()),
-            &mut output,
-            &mut input,
-            false,
-        );
+        let mut publishes = Vec::with_capacity(results.len());
+        let mut failure = None;
+        let mut published = Vec::new();
+        let mut skipped = Vec::new();
+        for (subgraph, result) in manifest
+            .subgraphs
+            .iter()
+            .zip(results.into_iter().chain(std::iter::repeat_with(|| None)))
+        {
+            let name = format!("`{}`", subgraph.name);
+            match result {
+                Some(Ok(publish)) => {
+                    publishes.push(publish);
+                    published.push(name);
+                }
+                Some(Err(error)) if failure.is_none() => failure = Some((name, error)),
+                Some(Err(error)) => {
+                    tracing::debug!("the {} subgraph also failed to publish: {}", name, error)
+                }
+                None => skipped.push(name),
+            }
+        }
 
-        assert!(result.is_ok());
-        assert!(input.is_empty());
-        assert!(std::str::from_utf8(&output).unwrap().contains(
-            "The host `localhost` is not routable via the public internet. Continuing the publish will make this subgraph reachable in local environments only."
-        ));
+        match failure {
+            Some((name, mut error)) => {
+                let mut summary = if published.is_empty() {
+                    format!(
+                        "No subgraphs were published before the {} subgraph failed.",
+                        name
+                    )
+                } else {
+                    format!(
+                        "These subgraphs were published before the {} subgraph failed: {}.",
+                        name,
+                        published.join(", ")
+                    )
+                };
+                if !skipped.is_empty() {
+                    summary.push_str(&format!(
+                        " These subgraphs were not published: {}.",
+                        skipped.join(", ")
+                    ));
+                }
+                error.set_suggestion(RoverErrorSuggestion::Adhoc(summary));
+                Err(error)
+            }
+            None => Ok(RoverOutput::SubgraphPublishBatch {
+                graph_ref: self.graph.graph_ref.clone(),
+                publishes,
+            }),
+        }
     }
 
-    #[test]
-    fn test_invalid_url_no_tty() {
-        let mut input: &[u8] = &[];
-        let mut output: Vec<u8> = Vec::new();
-        let result = Publish::handle_maybe_invalid_routing_url(
-            &Some("invalid-url".to_string()),
-            &mut output,
-            &mut input,
-            false,
+    /// publishes a single subgraph, reporting how it went with `--status-format json`
+    fn publish_subgraph(
+        &self,
+        subgraph: &str,
+        provided_routing_url: Option<String>,
+        schema: SchemaInput,
+        settings: &PublishSettings,
+    ) -> RoverResult<RoverOutput> {
+        let result = self
+            .prepare_publish(subgraph, provided_routing_url, schema, settings)
+            .and_then(|prepared| match prepared {
+                Prepared::Done(output) => Ok(output),
+                Prepared::Publish(mut pending) => {
+                    let response = self.send_publish(&mut pending, settings)?;
+                    self.finish_publish(*pending, response, settings)
+                }
+            });
+        settings.status.result(subgraph, result.as_ref());
+        result
+    }
+
+    /// everything about publishing `subgraph` that comes before sending the
+    /// publish mutation, including any prompts
+    fn prepare_publish(
+        &self,
+        subgraph: &str,
+        mut provided_routing_url: Option<String>,
+        schema: SchemaInput,
+        settings: &PublishSettings,
+    ) -> RoverResult<Prepared> {
+        let PublishSettings {
+            client_config,
+            git_context,
+            routing_url_fetch_attempts,
+            quiet,
+            interactive,
+            progress,
+            routing_url_validation,
+            status,
+            ..
+        } = *settings;
+        let mut attempts = PublishAttempts::default();
+
+        // each phase of the publish gets its own span, so that `--log debug`
+        // shows how long each one took when the spans close
+        let publish_span = tracing::debug_span!(
+            "subgraph_publish",
+            subgraph,
+            routing_url_source = tracing::field::Empty,
+            schema_bytes = tracing::field::Empty,
         );
+        let _publish = publish_span.enter();
+        if provided_routing_url.is_some() {
+            let source = if self.routing_url_from_introspection.is_some() {
+                "introspected"
+            } else {
+                "supplied"
+            };
+            publish_span.record("routing_url_source", source);
+        }
 
-        assert!(result.is_err());
-        assert!(input.is_empty());
+        let client = status.phase(subgraph, "authenticate", || {
+            tracing::debug_span!("authenticate", profile = %self.profile.profile_name)
+                .in_scope(|| client_config.get_authenticated_client(&self.profile))
+        })?;
+
+        let routing_url_input = SubgraphRoutingUrlInput {
+            graph_ref: self.graph.graph_ref.clone(),
+            subgraph_name: subgraph.to_string(),
+        };
+        let fetch_span = tracing::debug_span!("fetch_routing_url");
+        let shows_routing_url_diff = !(self.dry_run || quiet || !interactive);
+        let previous_routing_url = if provided_routing_url.is_none() {
+            publish_span.record("routing_url_source", "fetched");
+            let spinner = Spinner::start("Fetching the routing URL...", progress);
+            let fetch_response = status.phase(subgraph, "fetch_routing_url", || {
+                fetch_span.in_scope(|| {
+                    Retry::new(routing_url_fetch_attempts).run(
+                        || {
+                            attempts.routing_url_fetch += 1;
+                            routing_url::run(routing_url_input.clone(), &client)
+                        },
+                        retry::is_transient,
+                    )
+                })
+            });
+            spinner.stop();
+            match fetch_response {
+                // a subgraph that's never been published, or was published without
+                // one, has no routing url to keep, so rather than failing, ask for
+                // one when there's someone to ask
+                Err(error)
+                    if Self::has_no_routing_url(&error) && interactive && !self.auto_confirm() =>
+                {
+                    publish_span.record("routing_url_source", "prompted");
+                    provided_routing_url = Self::prompt_for_routing_url(
+                        subgraph,
+                        &mut prompt::stdin(),
+                        &mut io::stderr(),
+                        routing_url_validation == RoutingUrlValidation::Strict,
+                    )?;
+                    None
+                }
+                fetch_response => {
+                    let fetch_response = fetch_response.map_err(Self::with_network_suggestion)?;
+                    let fetched = Some(fetch_response.clone());
+                    if routing_url_validation != RoutingUrlValidation::Off
+                        && !(self.strict && Self::routing_url_warning(&fetched).is_some())
+                    {
+                        Self::handle_maybe_invalid_routing_url(
+                            &fetched,
+                            &mut settings.warnings(self.auto_confirm()),
+                            &mut prompt::stdin(),
+                            interactive,
+                            self.auto_confirm(),
+                            routing_url_validation == RoutingUrlValidation::Strict,
+                        )?;
+                    }
+                    Some(fetch_response)
+                }
+            }
+        } else if shows_routing_url_diff || self.skip_if_unchanged {
+            // the current routing url is only needed to report a change, or to
+            // tell whether anything changed, so failing to fetch it (e.g. for a
+            // brand new subgraph) is not an error, unless the API key was
+            // rejected, which the publish would be too
+            attempts.routing_url_fetch += 1;
+            match status.phase(subgraph, "fetch_routing_url", || {
+                fetch_span.in_scope(|| routing_url::run(routing_url_input, &client))
+            }) {
+                Ok(routing_url) => Some(routing_url),
+                Err(e @ RoverClientError::AuthenticationFailed { .. }) => return Err(e.into()),
+                Err(e) => {
+                    tracing::debug!("could not fetch the current routing url: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let routing_url = provided_routing_url
+            .clone()
+            .or(previous_routing_url.clone());
+
+        if !shows_routing_url_diff {
+            if previous_routing_url != routing_url && previous_routing_url.is_some() {
+                tracing::info!(
+                    ?previous_routing_url,
+                    ?routing_url,
+                    "the routing url for {} will change",
+                    subgraph
+                );
+            }
+        } else {
+            Self::write_routing_url_diff(
+                subgraph,
+                &previous_routing_url,
+                &routing_url,
+                &mut io::stderr(),
+            )?;
+        }
+
+        if !quiet {
+            eprintln!(
+                "{} SDL to {} (subgraph: {}) using credentials from the {} profile.",
+                if self.dry_run {
+                    "Dry run: not publishing"
+                } else {
+                    "Publishing"
+                },
+                Style::Link.paint(self.graph.graph_ref.to_string()),
+                Style::Link.paint(subgraph),
+                Style::Dimmed.paint(&self.profile.profile_name)
+            );
+        }
+
+        let input = schema;
+        let schema = status.phase(subgraph, "read_schema", || {
+            tracing::debug_span!("read_schema").in_scope(|| match input {
+                // stdin is read through the same reader as the prompts, so
+                // that neither takes input meant for the other
+                SchemaInput::Source(schema) => schema.read_file_descriptor_with(
+                    "SDL",
+                    &mut prompt::stdin_with_timeout(None),
+                    || Ok(client_config.get_reqwest_client()?),
+                ),
+                SchemaInput::Registry(schema_ref) => Self::fetch_schema_ref(schema_ref, &client),
+                SchemaInput::Build(dir) => Self::read_schema_dir(dir),
+            })
+        })?;
+        publish_span.record("schema_bytes", schema.len());
+        Self::check_schema_is_not_empty(subgraph, &schema, input)?;
+        if !self.skip_validation {
+            status.phase(subgraph, "validate_schema", || {
+                tracing::debug_span!("validate_schema")
+                    .in_scope(|| Self::validate_schema(subgraph, &schema))
+            })?;
+        }
+        let schema = if self.normalize {
+            sdl::format(&schema).map_err(|errors| Self::syntax_error(subgraph, &schema, &errors))?
+        } else {
+            schema
+        };
+        let missing_keys = Self::missing_keys_warning(subgraph, &schema);
+        // the routing url's warnings were printed when it was checked
+        let mut warnings: Vec<PublishWarning> = Self::routing_url_warning(&routing_url)
+            .into_iter()
+            .chain(missing_keys.clone())
+            .collect();
+        // with `--strict`, an unreachable routing url has to stop the publish,
+        // so it's checked before publishing rather than after
+        if self.strict {
+            warnings.extend(self.check_reachable(&routing_url, false, client_config, progress)?);
+        }
+        self.fail_if_strict(warnings.clone())?;
+        if let Some(warning) = missing_keys.as_ref().filter(|_| !quiet && !self.yes) {
+            Self::write_warning(&warning.message, &mut io::stderr())?;
+        }
+
+        tracing::trace!("Publishing \n{}", &schema);
+
+        if self.skip_if_unchanged
+            && previous_routing_url == routing_url
+            && self.is_schema_unchanged(subgraph, &schema, &client)
+        {
+            return Ok(Prepared::Done(RoverOutput::Skipped {
+                reason: Some(format!(
+                    "The schema and routing url of the {} subgraph in {} are unchanged, so it was not published.",
+                    Style::Link.paint(subgraph),
+                    Style::Link.paint(self.graph.graph_ref.to_string())
+                )),
+                subgraph: Some(subgraph.to_string()),
+                graph_ref: Some(self.graph.graph_ref.clone()),
+            }));
+        }
+
+        if self.dry_run {
+            return Ok(Prepared::Done(RoverOutput::SubgraphPublishDryRun {
+                graph_ref: self.graph.graph_ref.clone(),
+                subgraph: subgraph.to_string(),
+                routing_url,
+                schema_bytes: schema.len(),
+            }));
+        }
+
+        if self.confirm && !self.yes && interactive {
+            Self::confirm_publish(
+                &self.graph.graph_ref,
+                subgraph,
+                &routing_url,
+                schema.len(),
+                &mut prompt::stdin(),
+                &mut io::stderr(),
+            )?;
+        }
+
+        let input = SubgraphPublishInput {
+            graph_ref: self.graph.graph_ref.clone(),
+            subgraph: subgraph.to_string(),
+            url: provided_routing_url,
+            schema,
+            git_context: git_context.clone(),
+            convert_to_federated_graph: self.convert,
+        };
+        Ok(Prepared::Publish(Box::new(PendingPublish {
+            subgraph: subgraph.to_string(),
+            client,
+            span: publish_span.clone(),
+            input,
+            previous_routing_url,
+            routing_url,
+            attempts,
+            warnings,
+        })))
+    }
+
+    /// sends the publish mutation for `pending`, retrying it while it fails
+    /// for reasons that might go away on their own
+    fn send_publish(
+        &self,
+        pending: &mut PendingPublish,
+        settings: &PublishSettings,
+    ) -> RoverResult<SubgraphPublishResponse> {
+        let PendingPublish {
+            subgraph,
+            client,
+            span,
+            input,
+            attempts,
+            ..
+        } = pending;
+        let _publish = span.enter();
+        let spinner = Spinner::start(format!("Publishing {subgraph}..."), settings.progress);
+        let publish_response = settings
+            .status
+            .phase(subgraph, "publish_mutation", || {
+                tracing::debug_span!("publish_mutation").in_scope(|| {
+                    Retry::new(settings.publish_attempts).run_with_retry_after(
+                        || {
+                            attempts.publish += 1;
+                            publish::run(input.clone(), client)
+                        },
+                        retry::is_transient,
+                        |error| {
+                            Self::rate_limit_delay(error, subgraph, settings.quiet, Some(&spinner))
+                        },
+                    )
+                })
+            })
+            .map_err(Self::with_network_suggestion);
+        spinner.stop();
+        publish_response
+    }
+
+    /// sends the publish mutation for `pending` like [`Publish::send_publish`],
+    /// without blocking so that other subgraphs can be published at the same
+    /// time, along with how many times it was sent
+    async fn send_publish_async(
+        &self,
+        pending: &PendingPublish,
+        settings: &PublishSettings<'_>,
+    ) -> (RoverResult<SubgraphPublishResponse>, usize) {
+        let attempts = Cell::new(0);
+        let publish_response = settings
+            .status
+            .phase_async(
+                &pending.subgraph,
+                "publish_mutation",
+                Retry::new(settings.publish_attempts).run_with_retry_after_async(
+                    || {
+                        attempts.set(attempts.get() + 1);
+                        publish::run_async(pending.input.clone(), &pending.client)
+                    },
+                    retry::is_transient,
+                    |error| Self::rate_limit_delay(error, &pending.subgraph, settings.quiet, None),
+                ),
+            )
+            .instrument(tracing::debug_span!(parent: &pending.span, "publish_mutation"))
+            .await
+            .map_err(Self::with_network_suggestion);
+        (publish_response, attempts.get())
+    }
+
+    /// how long to wait before publishing `subgraph` again after `error`,
+    /// saying so if it's because Studio is rate limiting requests
+    fn rate_limit_delay(
+        error: &RoverClientError,
+        subgraph: &str,
+        quiet: bool,
+        spinner: Option<&Spinner>,
+    ) -> Option<Duration> {
+        let retry_after = retry::retry_after(error);
+        if !quiet && matches!(error, RoverClientError::RateLimited { .. }) {
+            let warn = || {
+                eprintln!(
+                    "{} Studio is rate limiting requests, waiting {} before publishing {} again.",
+                    Style::WarningPrefix.paint("WARN:"),
+                    retry_after.map_or_else(
+                        || "a moment".to_string(),
+                        |retry_after| format!("{}s", retry_after.as_secs())
+                    ),
+                    Style::Link.paint(subgraph)
+                )
+            };
+            match spinner {
+                Some(spinner) => spinner.suspend(warn),
+                None => warn(),
+            }
+        }
+        retry_after
+    }
+
+    /// everything about publishing a subgraph that comes after its publish
+    /// mutation was answered with `publish_response`
+    fn finish_publish(
+        &self,
+        pending: PendingPublish,
+        publish_response: SubgraphPublishResponse,
+        settings: &PublishSettings,
+    ) -> RoverResult<RoverOutput> {
+        let PendingPublish {
+            subgraph,
+            span,
+            previous_routing_url,
+            routing_url,
+            attempts,
+            mut warnings,
+            ..
+        } = pending;
+        let _publish = span.enter();
+        if !self.strict {
+            if let Some(warning) = self.check_reachable(
+                &routing_url,
+                true,
+                settings.client_config,
+                settings.progress,
+            )? {
+                if !settings.quiet {
+                    Self::write_warning(&warning.message, &mut io::stderr())?;
+                }
+                warnings.push(warning);
+            }
+        }
+
+        Ok(RoverOutput::SubgraphPublishResponse {
+            graph_ref: self.graph.graph_ref.clone(),
+            subgraph,
+            previous_routing_url,
+            routing_url,
+            publish_response,
+            attempts,
+            warnings,
+        })
+    }
+
+    /// whether fetching the routing url failed because the subgraph doesn't have one
+    fn has_no_routing_url(error: &RoverClientError) -> bool {
+        match error {
+            RoverClientError::MissingRoutingUrlError { .. } => true,
+            RoverClientError::MalformedResponse { null_field } => {
+                null_field == "graph.variant.subgraph.url"
+            }
+            _ => false,
+        }
+    }
+
+    /// fetches the schema published for the subgraph that `--schema-ref` points at
+    fn fetch_schema_ref(
+        schema_ref: &SubgraphSchemaRef,
+        client: &StudioClient,
+    ) -> RoverResult<String> {
+        tracing::info!("fetching the schema to publish from {}", schema_ref);
+        let input = SubgraphFetchInput {
+            graph_ref: schema_ref.graph_ref.clone(),
+            subgraph_name: schema_ref.subgraph_name.clone(),
+        };
+        let fetched = fetch::run(input, client).map_err(Self::with_network_suggestion)?;
+        Ok(fetched.sdl.contents)
+    }
+
+    /// joins the `.graphql` files in `dir` into one schema in the order of their
+    /// names, failing if any of them define the same type or directive, which
+    /// Studio would only reject after the upload
+    fn read_schema_dir(dir: &Utf8Path) -> RoverResult<String> {
+        let files = Fs::list_dir(dir, Some("graphql"))?;
+        if files.is_empty() {
+            return Err(anyhow!("There are no `.graphql` files in {} to publish.", dir).into());
+        }
+        let mut defined_in = HashMap::new();
+        let mut fragments = Vec::with_capacity(files.len());
+        for file in &files {
+            let fragment = Fs::read_file(file)?;
+            for name in sdl::defined_names(&fragment) {
+                if let Some(first) = defined_in.insert(name.clone(), file) {
+                    return Err(RoverError::new(anyhow!(
+                        "`{}` is defined in both {} and {}.",
+                        name,
+                        first,
+                        file
+                    ))
+                    .with_suggestion(RoverErrorSuggestion::Adhoc(format!(
+                        "Define it in only one of the files, using {} to add to a type from another file.",
+                        Style::Command.paint("`extend`")
+                    ))));
+                }
+            }
+            fragments.push(fragment);
+        }
+        tracing::info!("publishing the {} schema files in {}", files.len(), dir);
+        Ok(fragments.join("\n"))
+    }
+
+    /// whether the subgraph's published schema is equivalent to `schema`.
+    /// failing to fetch the published schema, like for a brand new subgraph,
+    /// counts as a change.
+    fn is_schema_unchanged(&self, subgraph: &str, schema: &str, client: &StudioClient) -> bool {
+        let input = SubgraphFetchInput {
+            graph_ref: self.graph.graph_ref.clone(),
+            subgraph_name: subgraph.to_string(),
+        };
+        match tracing::debug_span!("fetch_current_schema").in_scope(|| fetch::run(input, client)) {
+            Ok(current) => sdl::is_equivalent(&current.sdl.contents, schema),
+            Err(e) => {
+                tracing::debug!("could not fetch the current schema of {}: {}", subgraph, e);
+                false
+            }
+        }
+    }
+
+    /// points connection failures (DNS, refused connections, proxies) at the
+    /// network rather than at Studio, leaving every other error as it was
+    fn with_network_suggestion(error: RoverClientError) -> RoverError {
+        let is_connection_error = matches!(
+            &error,
+            RoverClientError::SendRequest { source, .. } if source.is_connect()
+        );
+        let mut error = RoverError::from(error);
+        if is_connection_error {
+            error.set_suggestion(RoverErrorSuggestion::CheckNetworkConnectivity);
+        }
+        error
+    }
+
+    /// checks that a subgraph is running at `endpoint` by introspecting it,
+    /// returning the endpoint to use as the routing url
+    fn introspect_routing_url(endpoint: &Url, client: &Client) -> RoverResult<String> {
+        let graphql_client = GraphQLClient::new(endpoint.as_str(), client.clone());
+        let input = SubgraphIntrospectInput {
+            headers: HashMap::new(),
+        };
+        match introspect::run(input, &graphql_client, false) {
+            Ok(_) => Ok(endpoint.to_string()),
+            Err(e) => {
+                tracing::debug!("introspection of {} failed: {}", endpoint, e);
+                let is_unreachable = matches!(
+                    &e,
+                    RoverClientError::SendRequest { source, .. }
+                        if source.is_connect() || source.is_timeout()
+                );
+                let suggestion = if is_unreachable {
+                    RoverErrorSuggestion::CheckServerConnection
+                } else {
+                    RoverErrorSuggestion::Adhoc(format!(
+                        "Make sure {} is a running subgraph, or pass its routing URL with {} instead.",
+                        Style::Link.paint(endpoint.as_str()),
+                        Style::Command.paint("`--routing-url`")
+                    ))
+                };
+                Err(RoverError::new(anyhow!(
+                    "Could not use {} as the routing URL because it did not respond to a subgraph introspection query.",
+                    endpoint
+                ))
+                .with_suggestion(suggestion))
+            }
+        }
+    }
+
+    /// with `--verify-reachable`, checks that `routing_url` can be connected
+    /// to, returning a warning if it can't. `published` is whether the
+    /// subgraph has been published yet, which the warning says
+    fn check_reachable(
+        &self,
+        routing_url: &Option<String>,
+        published: bool,
+        client_config: &StudioClientConfig,
+        progress: bool,
+    ) -> RoverResult<Option<PublishWarning>> {
+        let Some(routing_url) = routing_url.as_ref().filter(|_| self.verify_reachable) else {
+            return Ok(None);
+        };
+        let reqwest_client = client_config.get_reqwest_client()?;
+        let spinner = Spinner::start("Checking that the routing URL is reachable...", progress);
+        let unreachable = tracing::debug_span!("verify_reachable")
+            .in_scope(|| Self::verify_reachable(routing_url, published, &reqwest_client));
+        spinner.stop();
+        Ok(unreachable)
+    }
+
+    /// routing urls that aren't HTTP, and ones whose host is only reachable
+    /// locally, are never checked
+    fn verify_reachable(
+        routing_url: &str,
+        published: bool,
+        client: &Client,
+    ) -> Option<PublishWarning> {
+        let url = Url::parse(routing_url)
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))?;
+        if !is_routable(&url) {
+            tracing::debug!(
+                "not checking that the local-only routing url {} is reachable",
+                routing_url
+            );
+            return None;
+        }
+        Self::reachability_warning(routing_url, url, published, client, REACHABILITY_TIMEOUT)
+    }
+
+    /// sends a `HEAD` request to `url`. any response at all within `timeout`
+    /// means it's reachable, since a subgraph doesn't have to answer anything but GraphQL
+    fn reachability_warning(
+        routing_url: &str,
+        url: Url,
+        published: bool,
+        client: &Client,
+        timeout: Duration,
+    ) -> Option<PublishWarning> {
+        let error = client.head(url).timeout(timeout).send().err()?;
+        tracing::debug!("could not reach {}: {}", routing_url, error);
+        let message = if published {
+            format!(
+                "The subgraph was published, but its routing URL `{}` could not be reached.",
+                routing_url
+            )
+        } else {
+            format!(
+                "The routing URL `{}` could not be reached, so the subgraph was not published.",
+                routing_url
+            )
+        };
+        Some(PublishWarning {
+            code: "UNREACHABLE_ROUTING_URL",
+            message: format!(
+                "{} Make sure the subgraph is running where your router can connect to it.",
+                message
+            ),
+        })
+    }
+
+    /// writes a before/after diff of the routing url if it is changing,
+    /// returning whether anything was written
+    fn write_routing_url_diff(
+        subgraph: &str,
+        previous_routing_url: &Option<String>,
+        routing_url: &Option<String>,
+        writer: &mut impl io::Write,
+    ) -> io::Result<bool> {
+        match (previous_routing_url, routing_url) {
+            (Some(previous_routing_url), Some(routing_url))
+                if previous_routing_url != routing_url =>
+            {
+                writeln!(
+                    writer,
+                    "The routing URL for the {} subgraph will change:",
+                    Style::Link.paint(subgraph)
+                )?;
+                writeln!(
+                    writer,
+                    "{}",
+                    Style::Failure.paint(format!(
+                        "- {}",
+                        Self::display_routing_url(previous_routing_url)
+                    ))
+                )?;
+                writeln!(
+                    writer,
+                    "{}",
+                    Style::Success.paint(format!("+ {}", Self::display_routing_url(routing_url)))
+                )?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// summarizes a publish on one line and asks whether to go ahead with it,
+    /// returning an error if the answer is no
+    fn confirm_publish(
+        graph_ref: &GraphRef,
+        subgraph: &str,
+        routing_url: &Option<String>,
+        schema_bytes: usize,
+        reader: &mut impl io::BufRead,
+        writer: &mut impl io::Write,
+    ) -> RoverResult<()> {
+        let summary = format!(
+            "Publish {} of SDL to {} (subgraph: {}, routing URL: {})?",
+            Style::Dimmed.paint(format!("{} bytes", schema_bytes)),
+            Style::Link.paint(graph_ref.to_string()),
+            Style::Link.paint(subgraph),
+            Style::Link.paint(
+                routing_url
+                    .as_deref()
+                    .map_or("(none)", Self::display_routing_url)
+            )
+        );
+        if prompt::confirm_with_io(&wrap(&summary, terminal_width()), false, reader, writer)? {
+            Ok(())
+        } else {
+            Err(anyhow!("You cancelled the publish of the {} subgraph.", subgraph).into())
+        }
+    }
+
+    /// asks for the routing url of a subgraph that doesn't have one yet,
+    /// checking it like one passed with `--routing-url`. a blank answer
+    /// publishes the subgraph without a routing url
+    fn prompt_for_routing_url(
+        subgraph: &str,
+        reader: &mut impl io::BufRead,
+        writer: &mut impl io::Write,
+        reject_local: bool,
+    ) -> RoverResult<Option<String>> {
+        let message = format!(
+            "The {} subgraph doesn't have a routing URL yet. Enter the URL your router should send requests to, or leave it blank to publish without one:",
+            Style::Link.paint(subgraph)
+        );
+        let routing_url = Some(prompt::input_with_default_with_io(
+            &wrap(&message, terminal_width()),
+            "",
+            reader,
+            writer,
+        )?)
+        .filter(|routing_url| !routing_url.is_empty());
+        Self::handle_maybe_invalid_routing_url(
+            &routing_url,
+            writer,
+            reader,
+            true,
+            false,
+            reject_local,
+        )?;
+        Ok(routing_url)
+    }
+
+    /// an empty routing url means the subgraph has none
+    fn display_routing_url(routing_url: &str) -> &str {
+        if routing_url.is_empty() {
+            "(none)"
+        } else {
+            routing_url
+        }
+    }
+
+    /// checks a routing url before it's published, deciding whether the
+    /// publish can go ahead without any IO so that callers can present the
+    /// outcome however they like
+    pub fn check_routing_url(
+        maybe_invalid_routing_url: &Option<String>,
+        // whether the user can be prompted, which they can't in CI
+        is_atty: bool,
+        // answer "yes" to the prompt, printing its warning instead. urls that
+        // block publishes without a TTY still do, like without `auto_confirm`
+        auto_confirm: bool,
+        // fail on local-only hosts, even with `auto_confirm`
+        reject_local: bool,
+    ) -> RoutingUrlCheck {
+        // an explicitly empty URL clears the routing url, so there is nothing to check
+        let Some(routing_url) = maybe_invalid_routing_url
+            .as_ref()
+            .filter(|routing_url| !routing_url.is_empty())
+        else {
+            return RoutingUrlCheck::Ok;
+        };
+        let warning = match validate_routing_url(routing_url) {
+            Ok(validated_url) => {
+                tracing::debug!("Parsed URL: {}", validated_url);
+                if validated_url.is_unix_socket() {
+                    return RoutingUrlCheck::Note(format!("{} `{}` is a Unix domain socket. Only a router running on the same machine as this subgraph will be able to reach it.", Style::HintPrefix.paint("NOTE:"), Style::Link.paint(routing_url)));
+                }
+                return RoutingUrlCheck::Ok;
+            }
+            Err(warning) => warning,
+        };
+        if let (RoutingUrlWarning::NonRoutableHost { host }, true) = (&warning, reject_local) {
+            return RoutingUrlCheck::HardError(format!("The host `{}` is not routable via the public internet, and `--reject-local-routing-url` doesn't allow publishing local-only routing URLs.", host));
+        }
+        let (reason, question, blocks) = Self::describe_routing_url_warning(routing_url, &warning);
+        if is_atty && !auto_confirm {
+            RoutingUrlCheck::NeedsConfirmation(format!("{reason} {question}"))
+        } else if blocks && !is_atty {
+            RoutingUrlCheck::HardError(reason)
+        } else {
+            RoutingUrlCheck::Warning(reason)
+        }
+    }
+
+    /// what to tell the user about `warning` for `routing_url`, the question
+    /// to ask them before publishing it anyway, and whether it blocks
+    /// publishes that can't ask. unreachable routing urls block, while ones
+    /// that might just be a mistake only warn
+    fn describe_routing_url_warning(
+        routing_url: &str,
+        warning: &RoutingUrlWarning,
+    ) -> (String, &'static str, bool) {
+        match warning {
+            RoutingUrlWarning::Unparsable {
+                reason: parse_error,
+            } => {
+                tracing::debug!("Parse error: {}", parse_error);
+                (
+                    format!(
+                        "`{}` is not a valid routing URL.",
+                        Style::Link.paint(routing_url)
+                    ),
+                    "Continuing the publish will make this subgraph unreachable by your supergraph. Would you still like to publish?",
+                    true,
+                )
+            }
+            RoutingUrlWarning::UnsupportedScheme { scheme } => (
+                format!("`{}` is not a valid routing URL. The `{}` protocol is not supported by the router. Valid protocols are {}.", Style::Link.paint(routing_url), &scheme, Self::supported_schemes()),
+                "Continuing the publish will make this subgraph unreachable by your supergraph. Would you still like to publish?",
+                true,
+            ),
+            RoutingUrlWarning::SchemePortMismatch {
+                scheme,
+                port,
+                expected_scheme,
+            } => (
+                format!("`{}` uses the `{}` protocol with port {}, which is usually used with `{}`. Double-check that the protocol and port are correct.", Style::Link.paint(routing_url), scheme, port, expected_scheme),
+                "Would you still like to publish?",
+                false,
+            ),
+            RoutingUrlWarning::InternalHostPort { host, port } => (
+                format!("`{}` uses port {} on `{}`, which looks like a host that's only reachable inside a private network. Double-check that this isn't a development URL.", Style::Link.paint(routing_url), port, host),
+                "Would you still like to publish?",
+                false,
+            ),
+            RoutingUrlWarning::LocalFile { path } => (
+                format!("`{}` points at the local file `{}`. Only routers composing offline on this machine will be able to use it.", Style::Link.paint(routing_url), path),
+                "Would you still like to publish?",
+                false,
+            ),
+            RoutingUrlWarning::NonRoutableHost { host } => (
+                format!("The host `{}` is not routable via the public internet. Continuing the publish will make this subgraph reachable in local environments only.", host),
+                "Would you still like to publish?",
+                false,
+            ),
+        }
+    }
+
+    /// a stable code for each kind of routing url warning, for `--format json`
+    fn routing_url_warning_code(warning: &RoutingUrlWarning) -> &'static str {
+        match warning {
+            RoutingUrlWarning::Unparsable { .. } => "INVALID_ROUTING_URL",
+            RoutingUrlWarning::UnsupportedScheme { .. } => "UNSUPPORTED_ROUTING_URL_SCHEME",
+            RoutingUrlWarning::SchemePortMismatch { .. } => "ROUTING_URL_SCHEME_PORT_MISMATCH",
+            RoutingUrlWarning::InternalHostPort { .. } => "INTERNAL_ROUTING_URL_PORT",
+            RoutingUrlWarning::LocalFile { .. } => "FILE_ROUTING_URL",
+            RoutingUrlWarning::NonRoutableHost { .. } => "LOCAL_ROUTING_URL",
+        }
+    }
+
+    /// the warning about `routing_url` that's included in the output, if any
+    fn routing_url_warning(routing_url: &Option<String>) -> Option<PublishWarning> {
+        let routing_url = routing_url.as_deref().filter(|url| !url.is_empty())?;
+        let warning = validate_routing_url(routing_url).err()?;
+        let (reason, _, _) = Self::describe_routing_url_warning(routing_url, &warning);
+        Some(PublishWarning {
+            code: Self::routing_url_warning_code(&warning),
+            message: Style::strip(&reason),
+        })
+    }
+
+    /// checks a routing url with [`Publish::check_routing_url`], then prints
+    /// its notes and warnings, prompts for confirmation or fails as it says
+    fn handle_maybe_invalid_routing_url(
+        maybe_invalid_routing_url: &Option<String>,
+        // For testing purposes, we pass in stub `Write`er and `Read`ers to
+        // simulate input and verify output.
+        writer: &mut impl io::Write,
+        reader: &mut impl io::BufRead,
+        // Simulate a CI environment (non-TTY) for testing
+        is_atty: bool,
+        // Print warnings but never block on them, as if every prompt was confirmed
+        auto_confirm: bool,
+        // Fail on local-only hosts instead of warning about them
+        reject_local: bool,
+    ) -> RoverResult<()> {
+        match Self::check_routing_url(
+            maybe_invalid_routing_url,
+            is_atty,
+            auto_confirm,
+            reject_local,
+        ) {
+            RoutingUrlCheck::Ok => Ok(()),
+            RoutingUrlCheck::Note(note) => {
+                writeln!(writer, "{}", wrap(&note, terminal_width()))?;
+                Ok(())
+            }
+            RoutingUrlCheck::Warning(reason) => Self::warn_about_routing_url(&reason, writer),
+            RoutingUrlCheck::NeedsConfirmation(message) => {
+                Self::prompt_for_publish(&message, reader, writer)?;
+                Ok(())
+            }
+            RoutingUrlCheck::HardError(reason) => Self::non_tty_hard_error(&reason),
+        }
+    }
+
+    fn supported_schemes() -> String {
+        let schemes: Vec<String> = SUPPORTED_SCHEMES
+            .iter()
+            .map(|scheme| format!("`{scheme}`"))
+            .collect();
+        match schemes.split_last() {
+            Some((last, [])) => last.to_string(),
+            Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+            None => String::new(),
+        }
+    }
+
+    pub fn prompt_for_publish(
+        message: &str,
+        reader: &mut impl io::BufRead,
+        writer: &mut impl io::Write,
+    ) -> RoverResult<Option<bool>> {
+        let message = wrap(message, terminal_width());
+        if prompt::confirm_with_io(&message, false, reader, writer)? {
+            Ok(Some(true))
+        } else {
+            Err(anyhow!("You cancelled a subgraph publish due to an invalid routing url.").into())
+        }
+    }
+
+    pub fn non_tty_hard_error(reason: &str) -> RoverResult<()> {
+        Err(RoverError::new(anyhow!("{reason}"))
+            .with_suggestion(RoverErrorSuggestion::AllowInvalidRoutingUrlOrSpecifyValidUrl))
+    }
+
+    pub fn warn_about_routing_url(reason: &str, writer: &mut dyn io::Write) -> RoverResult<()> {
+        Self::write_warning(reason, writer)
+    }
+
+    fn write_warning(reason: &str, writer: &mut dyn io::Write) -> RoverResult<()> {
+        let warning = format!(
+            "{} {}",
+            Style::WarningLinePrefix.paint("WARN:"),
+            Style::Warning.paint(reason)
+        );
+        writeln!(writer, "{}", wrap(&warning, terminal_width()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{self, BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::num::NonZeroUsize;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use assert_fs::prelude::*;
+    use camino::Utf8PathBuf;
+    use clap::Parser;
+    use houston::{Config, RoutingUrlValidation};
+    use httpmock::{
+        Method::{HEAD, POST},
+        MockServer,
+    };
+    use reqwest::{blocking::Client, Url};
+    use rover_client::shared::{GitContext, GitContextSource};
+    use rover_client::{EndpointKind, RoverClientError};
+    use rover_std::test_env::{with_env_vars, InterruptedInput};
+    use serde_json::json;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::Layer;
+
+    use crate::command::subgraph::publish::{
+        PublishAttempts, PublishSettings, RoutingUrlCheck, REACHABILITY_TIMEOUT,
+    };
+    use crate::command::subgraph::status::{StatusFormat, StatusStream};
+    use crate::command::subgraph::Publish;
+    use crate::error::{RoverErrorCode, INTERRUPTED_EXIT_CODE};
+    use crate::utils::client::{ClientBuilder, StudioClientConfig};
+    use crate::utils::retry;
+    use crate::{RoverError, RoverErrorSuggestion, RoverOutput, RoverResult};
+
+    /// the written output with line wrapping undone
+    fn unwrapped(output: &[u8]) -> String {
+        std::str::from_utf8(output)
+            .unwrap()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_omitted_routing_url_is_not_checked() {
+        assert_eq!(
+            Publish::check_routing_url(&None, false, false, false),
+            RoutingUrlCheck::Ok
+        );
+    }
+
+    #[test]
+    fn test_empty_routing_url_clears_without_warning() {
+        assert_eq!(
+            Publish::check_routing_url(&Some("".to_string()), false, false, false),
+            RoutingUrlCheck::Ok
+        );
+    }
+
+    #[test]
+    fn test_whitespace_routing_url_is_still_invalid() {
+        assert!(matches!(
+            Publish::check_routing_url(&Some(" ".to_string()), false, false, false),
+            RoutingUrlCheck::HardError(_)
+        ));
+    }
+
+    #[test]
+    fn test_routing_url_diff_shows_a_cleared_routing_url() {
+        let mut output: Vec<u8> = Vec::new();
+        let wrote = Publish::write_routing_url_diff(
+            "accounts",
+            &Some("https://accounts.example.com/graphql".to_string()),
+            &Some("".to_string()),
+            &mut output,
+        )
+        .unwrap();
+
+        assert!(wrote);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("- https://accounts.example.com/graphql"));
+        assert!(output.contains("+ (none)"));
+    }
+
+    #[test]
+    fn test_confirm_invalid_url_publish() {
+        let mut input = "yes\n".as_bytes();
+        let mut output: Vec<u8> = Vec::new();
+        let result = Publish::handle_maybe_invalid_routing_url(
+            &Some("invalid-url".to_string()),
+            &mut output,
+            &mut input,
+            true,
+            false,
+            false,
+        );
+
+        assert!(result.is_ok());
+        assert!(input.is_empty());
+        assert!(unwrapped(&output).contains("is not a valid routing URL. Continuing the publish will make this subgraph unreachable by your supergraph. Would you still like to publish?"));
+    }
+
+    #[test]
+    fn test_deny_invalid_url_publish() {
+        let mut input = "nope\n".as_bytes();
+        let mut output: Vec<u8> = Vec::new();
+        let result = Publish::handle_maybe_invalid_routing_url(
+            &Some("invalid-url".to_string()),
+            &mut output,
+            &mut input,
+            true,
+            false,
+            false,
+        );
+
+        assert!(result.is_err());
         assert!(result
             .unwrap_err()
             .to_string()
-            .contains("is not a valid routing URL."));
+            .contains("You cancelled a subgraph publish due to an invalid routing url."));
+        assert!(input.is_empty());
+        assert!(unwrapped(&output).contains("is not a valid routing URL. Continuing the publish will make this subgraph unreachable by your supergraph. Would you still like to publish?"));
+    }
+
+    fn confirm_publish_with_input(input: &str) -> (RoverResult<()>, String) {
+        let mut input = input.as_bytes();
+        let mut output: Vec<u8> = Vec::new();
+        let result = Publish::confirm_publish(
+            &"my-graph@current".parse().unwrap(),
+            "accounts",
+            &Some("https://accounts.example.com/graphql".to_string()),
+            28,
+            &mut input,
+            &mut output,
+        );
+        (result, unwrapped(&output))
+    }
+
+    #[test]
+    fn test_confirm_publish_summary_accepted() {
+        let (result, output) = confirm_publish_with_input("y\n");
+
+        assert!(result.is_ok());
+        assert_eq!(
+            output,
+            "Publish 28 bytes of SDL to my-graph@current (subgraph: accounts, routing URL: https://accounts.example.com/graphql)? [y/N]"
+        );
+    }
+
+    #[test]
+    fn test_confirm_publish_summary_dims_the_byte_count() {
+        let (_, output) = with_env_vars(&[("CLICOLOR_FORCE", "1")], || {
+            confirm_publish_with_input("y\n")
+        });
+        assert!(output.contains("\u{1b}[2m28 bytes\u{1b}[0m"));
+
+        let (_, output) = with_env_vars(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")], || {
+            confirm_publish_with_input("y\n")
+        });
+        assert!(!output.contains('\u{1b}'));
+        assert!(output.contains("Publish 28 bytes of SDL"));
+    }
+
+    #[test]
+    fn test_confirm_publish_summary_declined() {
+        for answer in ["n\n", "\n"] {
+            let (result, _) = confirm_publish_with_input(answer);
+
+            assert!(result
+                .unwrap_err()
+                .to_string()
+                .contains("You cancelled the publish of the accounts subgraph."));
+        }
+    }
+
+    #[test]
+    fn test_interrupted_confirmation_cancels_cleanly() {
+        let error = Publish::confirm_publish(
+            &"my-graph@current".parse().unwrap(),
+            "accounts",
+            &None,
+            28,
+            &mut InterruptedInput,
+            &mut io::sink(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.exit_code(), INTERRUPTED_EXIT_CODE);
+        assert_eq!(error.message(), "The operation was cancelled.");
+        assert!(!error.to_string().contains("Caused by"), "{}", error);
+    }
+
+    #[test]
+    fn test_confirm_is_case_and_whitespace_insensitive() {
+        for answer in ["Y \n", "YES\n", "  yEs\r\n"] {
+            let mut input = answer.as_bytes();
+            let mut output: Vec<u8> = Vec::new();
+            let result = Publish::handle_maybe_invalid_routing_url(
+                &Some("invalid-url".to_string()),
+                &mut output,
+                &mut input,
+                true,
+                false,
+                false,
+            );
+
+            assert!(result.is_ok());
+            assert!(input.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_empty_answer_defaults_to_no() {
+        for answer in ["\n", ""] {
+            let mut input = answer.as_bytes();
+            let mut output: Vec<u8> = Vec::new();
+            let result = Publish::handle_maybe_invalid_routing_url(
+                &Some("invalid-url".to_string()),
+                &mut output,
+                &mut input,
+                true,
+                false,
+                false,
+            );
+
+            assert!(result.is_err());
+            assert!(result
+                .unwrap_err()
+                .to_string()
+                .contains("You cancelled a subgraph publish due to an invalid routing url."));
+        }
+    }
+
+    #[test]
+    fn test_invalid_scheme() {
+        let check = Publish::check_routing_url(
+            &Some("ftp://invalid-scheme".to_string()),
+            true,
+            false,
+            false,
+        );
+
+        let RoutingUrlCheck::NeedsConfirmation(message) = check else {
+            panic!("expected a confirmation, got {check:?}");
+        };
+        assert!(message.contains(
+            "is not a valid routing URL. The `ftp` protocol is not supported by the router. Valid protocols are `http`, `https`, `ws` and `wss`."
+        ));
+        // a Unix domain socket is accepted, but only with a note that it's local
+        assert!(!message.contains("`unix`"));
+    }
+
+    #[test]
+    fn test_invalid_scheme_no_tty() {
+        let check = Publish::check_routing_url(
+            &Some("ftp://invalid-scheme".to_string()),
+            false,
+            false,
+            false,
+        );
+
+        let RoutingUrlCheck::HardError(reason) = check else {
+            panic!("expected an error, got {check:?}");
+        };
+        assert!(reason.contains(
+            "The `ftp` protocol is not supported by the router. Valid protocols are `http`, `https`, `ws` and `wss`."
+        ));
+    }
+
+    #[test]
+    fn test_websocket_schemes() {
+        for url in ["ws://host", "wss://host"] {
+            assert_eq!(
+                Publish::check_routing_url(&Some(url.to_string()), true, false, false),
+                RoutingUrlCheck::Ok
+            );
+        }
+    }
+
+    #[test]
+    fn test_unix_socket_note() {
+        let check = Publish::check_routing_url(
+            &Some("unix:///tmp/subgraph.sock".to_string()),
+            false,
+            false,
+            false,
+        );
+
+        let RoutingUrlCheck::Note(note) = check else {
+            panic!("expected a note, got {check:?}");
+        };
+        assert!(note.contains("is a Unix domain socket."));
+    }
+
+    #[test]
+    fn test_localhost_tty() {
+        let check = Publish::check_routing_url(
+            &Some("http://localhost:8000".to_string()),
+            true,
+            false,
+            false,
+        );
+
+        let RoutingUrlCheck::NeedsConfirmation(message) = check else {
+            panic!("expected a confirmation, got {check:?}");
+        };
+        assert!(message.contains(
+            "The host `localhost` is not routable via the public internet. Continuing the publish will make this subgraph reachable in local environments only. Would you still like to publish?"
+        ));
+    }
+
+    #[test]
+    fn test_localhost_no_tty() {
+        let check = Publish::check_routing_url(
+            &Some("http://localhost:8000".to_string()),
+            false,
+            false,
+            false,
+        );
+
+        assert_eq!(
+            check,
+            RoutingUrlCheck::Warning(
+                "The host `localhost` is not routable via the public internet. Continuing the publish will make this subgraph reachable in local environments only.".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_routing_url_warnings_have_stable_codes() {
+        let warning = Publish::routing_url_warning(&Some("http://localhost:8000".to_string()))
+            .expect("localhost should be warned about");
+        assert_eq!(warning.code, "LOCAL_ROUTING_URL");
+        assert!(warning
+            .message
+            .starts_with("The host `localhost` is not routable via the public internet."));
+
+        let warning = Publish::routing_url_warning(&Some("ftp://accounts.example.com".to_string()))
+            .expect("unsupported schemes should be warned about");
+        assert_eq!(warning.code, "UNSUPPORTED_ROUTING_URL_SCHEME");
+
+        assert_eq!(
+            Publish::routing_url_warning(&Some("https://accounts.example.com".to_string())),
+            None
+        );
+        assert_eq!(Publish::routing_url_warning(&None), None);
+    }
+
+    #[test]
+    fn test_local_file_tty() {
+        let check = Publish::check_routing_url(
+            &Some("file:///etc/subgraphs/accounts.graphql".to_string()),
+            true,
+            false,
+            false,
+        );
+
+        let RoutingUrlCheck::NeedsConfirmation(message) = check else {
+            panic!("expected a confirmation, got {check:?}");
+        };
+        assert!(message.contains(
+            "points at the local file `/etc/subgraphs/accounts.graphql`. Only routers composing offline on this machine will be able to use it. Would you still like to publish?"
+        ));
+    }
+
+    #[test]
+    fn test_local_file_no_tty() {
+        let mut input = "".as_bytes();
+        let mut output: Vec<u8> = Vec::new();
+        let result = Publish::handle_maybe_invalid_routing_url(
+            &Some("file:///etc/subgraphs/accounts.graphql".to_string()),
+            &mut output,
+            &mut input,
+            false,
+            false,
+            false,
+        );
+
+        assert!(result.is_ok());
+        let output = unwrapped(&output);
+        assert!(output.contains("WARN:"));
+        assert!(output.contains("points at the local file `/etc/subgraphs/accounts.graphql`."));
+        assert!(!output.contains("Would you still like to publish?"));
+    }
+
+    #[test]
+    fn test_reject_local_routing_url() {
+        for url in [
+            "http://localhost:8000",
+            "http://127.0.0.1:4000",
+            "http://[::1]",
+        ] {
+            for (is_atty, auto_confirm) in [(true, false), (false, false), (true, true)] {
+                let check =
+                    Publish::check_routing_url(&Some(url.to_string()), is_atty, auto_confirm, true);
+                let RoutingUrlCheck::HardError(reason) = check else {
+                    panic!("expected an error for {url}, got {check:?}");
+                };
+                assert!(reason.contains("`--reject-local-routing-url` doesn't allow"));
+            }
+        }
+
+        let err = Publish::handle_maybe_invalid_routing_url(
+            &Some("http://localhost:8000".to_string()),
+            &mut Vec::new(),
+            &mut "y\n".as_bytes(),
+            true,
+            false,
+            true,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.suggestions(),
+            [RoverErrorSuggestion::AllowInvalidRoutingUrlOrSpecifyValidUrl]
+        ));
+    }
+
+    #[test]
+    fn test_reject_local_routing_url_leaves_other_urls_alone() {
+        assert_eq!(
+            Publish::check_routing_url(
+                &Some("https://accounts.example.com/graphql".to_string()),
+                false,
+                false,
+                true,
+            ),
+            RoutingUrlCheck::Ok
+        );
+        assert!(matches!(
+            Publish::check_routing_url(
+                &Some("http://api.example.com:443".to_string()),
+                false,
+                false,
+                true,
+            ),
+            RoutingUrlCheck::Warning(_)
+        ));
+    }
+
+    #[test]
+    fn test_routing_url_validation_flags_override_the_config() {
+        let publish = |flags: &[&str]| {
+            let mut args = vec![
+                "publish",
+                "my-graph@current",
+                "--name",
+                "accounts",
+                "--schema",
+                "schema.graphql",
+            ];
+            args.extend_from_slice(flags);
+            Publish::parse_from(args)
+        };
+        let all_modes = [
+            RoutingUrlValidation::Strict,
+            RoutingUrlValidation::Warn,
+            RoutingUrlValidation::Off,
+        ];
+
+        for configured in all_modes {
+            assert_eq!(publish(&[]).routing_url_validation(configured), configured);
+            assert_eq!(
+                publish(&["--allow-invalid-routing-url"]).routing_url_validation(configured),
+                RoutingUrlValidation::Off
+            );
+            assert_eq!(
+                publish(&["--reject-local-routing-url"]).routing_url_validation(configured),
+                RoutingUrlValidation::Strict
+            );
+        }
+    }
+
+    #[test]
+    fn test_loopback_ips_tty() {
+        for (url, host) in [
+            ("http://[::1]:4000", "[::1]"),
+            ("http://127.0.0.2", "127.0.0.2"),
+            ("http://0.0.0.0:80", "0.0.0.0"),
+        ] {
+            let check = Publish::check_routing_url(&Some(url.to_string()), true, false, false);
+
+            let RoutingUrlCheck::NeedsConfirmation(message) = check else {
+                panic!("expected a confirmation for {url}, got {check:?}");
+            };
+            assert!(message.contains(&format!(
+                "The host `{host}` is not routable via the public internet."
+            )));
+        }
+    }
+
+    #[test]
+    fn test_loopback_ips_no_tty() {
+        for (url, host) in [
+            ("http://[::1]:4000", "[::1]"),
+            ("http://127.0.0.2", "127.0.0.2"),
+            ("http://0.0.0.0:80", "0.0.0.0"),
+        ] {
+            let check = Publish::check_routing_url(&Some(url.to_string()), false, false, false);
+
+            let RoutingUrlCheck::Warning(reason) = check else {
+                panic!("expected a warning for {url}, got {check:?}");
+            };
+            assert!(reason.contains(&format!(
+                "The host `{host}` is not routable via the public internet."
+            )));
+        }
+    }
+
+    #[test]
+    fn test_auto_confirm_only_warns() {
+        for (url, is_atty) in [
+            ("invalid-url", true),
+            ("ftp://invalid-scheme", true),
+            ("http://localhost:8000", true),
+            ("http://localhost:8000", false),
+            ("http://api.example.com:443", true),
+            ("http://api.example.com:443", false),
+        ] {
+            let check = Publish::check_routing_url(&Some(url.to_string()), is_atty, true, false);
+            assert!(
+                matches!(check, RoutingUrlCheck::Warning(_)),
+                "{url} gave {check:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_auto_confirm_still_blocks_unreachable_urls_without_a_tty() {
+        for url in ["invalid-url", "ftp://invalid-scheme"] {
+            let check = Publish::check_routing_url(&Some(url.to_string()), false, true, false);
+            assert!(
+                matches!(check, RoutingUrlCheck::HardError(_)),
+                "{url} gave {check:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_auto_confirm_warns_without_reading_input() {
+        for (url, is_atty) in [
+            ("invalid-url", true),
+            ("ftp://invalid-scheme", true),
+            ("http://localhost:8000", true),
+            ("http://localhost:8000", false),
+        ] {
+            let mut input = "n".as_bytes();
+            let mut output: Vec<u8> = Vec::new();
+            let result = Publish::handle_maybe_invalid_routing_url(
+                &Some(url.to_string()),
+                &mut output,
+                &mut input,
+                is_atty,
+                true,
+                false,
+            );
+
+            assert!(result.is_ok());
+            assert_eq!(input, "n".as_bytes());
+            let output = unwrapped(&output);
+            assert!(output.contains("WARN:"));
+            assert!(!output.contains("Would you still like to publish?"));
+        }
+    }
+
+    #[test]
+    fn test_routing_url_from_introspection() {
+        let server = MockServer::start();
+        let introspection = server.mock(|when, then| {
+            when.method(POST).path("/graphql");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "data": { "_service": { "sdl": "type Query { hello: String }" } }
+                }));
+        });
+        let endpoint = Url::parse(&server.url("/graphql")).unwrap();
+
+        let routing_url = Publish::introspect_routing_url(&endpoint, &Client::new()).unwrap();
+
+        assert_eq!(routing_url, endpoint.to_string());
+        introspection.assert();
+    }
+
+    #[test]
+    fn test_routing_url_from_introspection_of_non_graphql_endpoint() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path("/graphql");
+            then.status(200)
+                .header("content-type", "text/html")
+                .body("<html><body>Hello!</body></html>");
+        });
+        let endpoint = Url::parse(&server.url("/graphql")).unwrap();
+
+        let err = Publish::introspect_routing_url(&endpoint, &Client::new()).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("did not respond to a subgraph introspection query"));
+        assert!(matches!(
+            err.suggestions(),
+            [RoverErrorSuggestion::Adhoc(_)]
+        ));
+    }
+
+    #[test]
+    fn test_connection_errors_suggest_checking_the_network() {
+        // nothing listens on port 1, so connecting fails right away
+        let source = reqwest::blocking::get("http://127.0.0.1:1").unwrap_err();
+        let err = Publish::with_network_suggestion(RoverClientError::SendRequest {
+            source,
+            endpoint_kind: EndpointKind::ApolloStudio,
+        });
+        assert!(err
+            .suggestions()
+            .iter()
+            .any(|s| matches!(s, RoverErrorSuggestion::CheckNetworkConnectivity)));
+
+        let err = Publish::with_network_suggestion(RoverClientError::InvalidKey);
+        assert!(matches!(
+            err.suggestions(),
+            [RoverErrorSuggestion::CheckKey]
+        ));
+    }
+
+    #[test]
+    fn test_scheme_port_mismatch_prompts_in_tty() {
+        let check = Publish::check_routing_url(
+            &Some("http://api.example.com:443".to_string()),
+            true,
+            false,
+            false,
+        );
+
+        let RoutingUrlCheck::NeedsConfirmation(message) = check else {
+            panic!("expected a confirmation, got {check:?}");
+        };
+        assert!(message.contains(
+            "uses the `http` protocol with port 443, which is usually used with `https`."
+        ));
+    }
+
+    #[test]
+    fn test_scheme_port_mismatch_warns_in_non_tty() {
+        let check = Publish::check_routing_url(
+            &Some("https://api.example.com:80".to_string()),
+            false,
+            false,
+            false,
+        );
+
+        let RoutingUrlCheck::Warning(reason) = check else {
+            panic!("expected a warning, got {check:?}");
+        };
+        assert!(reason.contains("uses the `https` protocol with port 80"));
+    }
+
+    #[test]
+    fn test_internal_host_ports_prompt_in_tty_and_warn_in_non_tty() {
+        let routing_url = Some("http://accounts:4001/graphql".to_string());
+
+        let check = Publish::check_routing_url(&routing_url, true, false, false);
+        let RoutingUrlCheck::NeedsConfirmation(message) = check else {
+            panic!("expected a confirmation, got {check:?}");
+        };
+        assert!(message.contains("uses port 4001 on `accounts`"));
+
+        let check = Publish::check_routing_url(&routing_url, false, false, false);
+        let RoutingUrlCheck::Warning(reason) = check else {
+            panic!("expected a warning, got {check:?}");
+        };
+        assert!(reason.contains("only reachable inside a private network"));
+    }
+
+    #[test]
+    fn test_verify_reachable_accepts_any_response() {
+        let server = MockServer::start();
+        let head = server.mock(|when, then| {
+            when.method(HEAD).path("/graphql");
+            then.status(405);
+        });
+        let routing_url = server.url("/graphql");
+
+        let warning = Publish::reachability_warning(
+            &routing_url,
+            Url::parse(&routing_url).unwrap(),
+            true,
+            &Client::new(),
+            REACHABILITY_TIMEOUT,
+        );
+        assert_eq!(warning, None);
+        head.assert();
+    }
+
+    #[test]
+    fn test_verify_reachable_warns_when_unreachable() {
+        // a port that was just free, so nothing is listening on it
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let routing_url = format!("http://127.0.0.1:{port}/graphql");
+
+        let warning = Publish::reachability_warning(
+            &routing_url,
+            Url::parse(&routing_url).unwrap(),
+            true,
+            &Client::new(),
+            REACHABILITY_TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(warning.code, "UNREACHABLE_ROUTING_URL");
+        assert!(warning.message.contains(&routing_url));
+        assert!(warning.message.starts_with("The subgraph was published"));
+    }
+
+    #[test]
+    fn test_verify_reachable_gives_up_on_routing_urls_that_never_answer() {
+        // accepts connections, but never answers on them
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let routing_url = format!("http://{}/graphql", listener.local_addr().unwrap());
+
+        let started = Instant::now();
+        let warning = Publish::reachability_warning(
+            &routing_url,
+            Url::parse(&routing_url).unwrap(),
+            true,
+            &Client::new(),
+            Duration::from_millis(100),
+        )
+        .unwrap();
+        assert_eq!(warning.code, "UNREACHABLE_ROUTING_URL");
+        assert!(started.elapsed() < Duration::from_secs(5));
+        drop(listener);
+    }
+
+    #[test]
+    fn test_verify_reachable_skips_local_and_non_http_routing_urls() {
+        let server = MockServer::start();
+        let head = server.mock(|when, then| {
+            when.method(HEAD);
+            then.status(200);
+        });
+        for routing_url in [
+            server.url("/graphql"),
+            format!("http://localhost:{}/graphql", server.port()),
+            format!("http://accounts.localhost:{}/graphql", server.port()),
+            "http://10.0.0.12/graphql".to_string(),
+            "unix:///tmp/subgraph.sock".to_string(),
+            "invalid-url".to_string(),
+        ] {
+            assert_eq!(
+                Publish::verify_reachable(&routing_url, true, &Client::new()),
+                None,
+                "{routing_url}"
+            );
+        }
+        head.assert_hits(0);
+    }
+
+    fn prompt_for_routing_url_with_input(input: &str) -> (RoverResult<Option<String>>, String) {
+        let mut reader = input.as_bytes();
+        let mut writer: Vec<u8> = Vec::new();
+        let routing_url =
+            Publish::prompt_for_routing_url("accounts", &mut reader, &mut writer, false);
+        (routing_url, unwrapped(&writer))
+    }
+
+    #[test]
+    fn test_subgraphs_without_a_routing_url_are_prompted_for_one() {
+        assert!(Publish::has_no_routing_url(
+            &RoverClientError::MissingRoutingUrlError {
+                subgraph_name: "accounts".to_string(),
+                graph_ref: "my-graph@current".parse().unwrap(),
+            }
+        ));
+        assert!(Publish::has_no_routing_url(
+            &RoverClientError::MalformedResponse {
+                null_field: "graph.variant.subgraph.url".to_string(),
+            }
+        ));
+        assert!(!Publish::has_no_routing_url(
+            &RoverClientError::MalformedResponse {
+                null_field: "graph".to_string(),
+            }
+        ));
+        assert!(!Publish::has_no_routing_url(&RoverClientError::InvalidKey));
+    }
+
+    #[test]
+    fn test_prompt_for_routing_url_accepts_a_url() {
+        let (routing_url, output) =
+            prompt_for_routing_url_with_input(" https://accounts.example.com/graphql \n");
+        assert_eq!(
+            routing_url.unwrap().as_deref(),
+            Some("https://accounts.example.com/graphql")
+        );
+        assert!(output.contains("The accounts subgraph doesn't have a routing URL yet."));
+    }
+
+    #[test]
+    fn test_prompt_for_routing_url_publishes_without_one_on_blank() {
+        let (routing_url, _) = prompt_for_routing_url_with_input("\n");
+        assert_eq!(routing_url.unwrap(), None);
+    }
+
+    #[test]
+    fn test_prompt_for_routing_url_checks_the_url() {
+        let (routing_url, output) = prompt_for_routing_url_with_input("http://localhost:4001\nn\n");
+        assert!(routing_url.is_err());
+        assert!(output.contains("The host `localhost` is not routable"));
+
+        let (routing_url, _) = prompt_for_routing_url_with_input("http://localhost:4001\ny\n");
+        assert_eq!(
+            routing_url.unwrap().as_deref(),
+            Some("http://localhost:4001")
+        );
+    }
+
+    #[test]
+    fn test_routing_url_diff_only_when_changed() {
+        let previous = Some("https://old.example.com".to_string());
+        let new = Some("https://new.example.com".to_string());
+
+        let mut output: Vec<u8> = Vec::new();
+        assert!(Publish::write_routing_url_diff("accounts", &previous, &new, &mut output).unwrap());
+        let output = unwrapped(&output);
+        assert!(output.contains("- https://old.example.com"));
+        assert!(output.contains("+ https://new.example.com"));
+
+        for (previous, new) in [
+            (previous.clone(), previous.clone()),
+            (None, new.clone()),
+            (previous, None),
+        ] {
+            let mut output: Vec<u8> = Vec::new();
+            assert!(
+                !Publish::write_routing_url_diff("accounts", &previous, &new, &mut output).unwrap()
+            );
+            assert!(output.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_invalid_url_no_tty() {
+        let check =
+            Publish::check_routing_url(&Some("invalid-url".to_string()), false, false, false);
+
+        let RoutingUrlCheck::HardError(reason) = check else {
+            panic!("expected an error, got {check:?}");
+        };
+        assert!(reason.contains("is not a valid routing URL."));
+    }
+
+    #[test]
+    fn test_invalid_url_no_tty_fails_the_publish() {
+        let mut input: &[u8] = &[];
+        let mut output: Vec<u8> = Vec::new();
+        let result = Publish::handle_maybe_invalid_routing_url(
+            &Some("invalid-url".to_string()),
+            &mut output,
+            &mut input,
+            false,
+            false,
+            false,
+        );
+
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("is not a valid routing URL."));
+        assert!(matches!(
+            err.suggestions(),
+            [RoverErrorSuggestion::AllowInvalidRoutingUrlOrSpecifyValidUrl]
+        ));
+        assert!(output.is_empty());
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordedSpan {
+        name: &'static str,
+        fields: HashMap<&'static str, String>,
+    }
+
+    impl Visit for RecordedSpan {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields.insert(field.name(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.fields.insert(field.name(), format!("{:?}", value));
+        }
+    }
+
+    /// a tracing layer that keeps every span it sees, along with its fields,
+    /// and the message of every info event
+    #[derive(Clone, Default)]
+    struct SpanRecorder {
+        spans: Arc<Mutex<Vec<RecordedSpan>>>,
+        messages: Arc<Mutex<Vec<String>>>,
+        // span ids are reused once a span closes, so these point at the latest span for each id
+        indices: Arc<Mutex<HashMap<Id, usize>>>,
+    }
+
+    impl SpanRecorder {
+        fn span(&self, name: &str) -> Option<HashMap<&'static str, String>> {
+            self.spans
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|span| span.name == name)
+                .map(|span| span.fields.clone())
+        }
+
+        fn messages(&self) -> Vec<String> {
+            self.messages.lock().unwrap().clone()
+        }
+    }
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanRecorder {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: Context<'_, S>) {
+            let mut span = RecordedSpan {
+                name: attrs.metadata().name(),
+                ..Default::default()
+            };
+            attrs.record(&mut span);
+            let mut spans = self.spans.lock().unwrap();
+            self.indices.lock().unwrap().insert(id.clone(), spans.len());
+            spans.push(span);
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            if let Some(index) = self.indices.lock().unwrap().get(id) {
+                values.record(&mut self.spans.lock().unwrap()[*index]);
+            }
+        }
+
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            if *event.metadata().level() == Level::INFO {
+                let mut fields = RecordedSpan::default();
+                event.record(&mut fields);
+                if let Some(message) = fields.fields.remove("message") {
+                    self.messages.lock().unwrap().push(message);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_publish_phases_are_traced() {
+        let studio = MockServer::start();
+        mock_successful_publish(&studio);
+
+        let recorder = SpanRecorder::default();
+        let subscriber = tracing_subscriber::registry().with(recorder.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            publish_with_studio(studio.url("/graphql")).unwrap();
+        });
+
+        let publish_span = recorder.span("subgraph_publish").unwrap();
+        assert_eq!(publish_span["subgraph"], "accounts");
+        assert_eq!(publish_span["routing_url_source"], "fetched");
+        assert_eq!(publish_span["schema_bytes"], "28");
+        for phase in [
+            "authenticate",
+            "fetch_routing_url",
+            "read_schema",
+            "publish_mutation",
+        ] {
+            assert!(recorder.span(phase).is_some(), "no {} span", phase);
+        }
+        assert!(recorder.span("introspect_routing_url").is_none());
+    }
+
+    fn trace_http_messages(trace_http: bool) -> Vec<String> {
+        const API_KEY: &str = "user:gh.fake:a1b2c3d4e5f6g7h8";
+        let studio = MockServer::start();
+        studio.mock(|when, then| {
+            when.body_contains("SubgraphRoutingUrlQuery");
+            then.status(200).json_body(json!({
+                "data": {
+                    "variant": {
+                        "__typename": "GraphVariant",
+                        "subgraph": { "url": "https://accounts.example.com/graphql" }
+                    }
+                }
+            }));
+        });
+        let mut published = published_body();
+        published["data"]["graph"]["publishSubgraph"]["launchCliCopy"] =
+            json!(format!("published with {}", API_KEY));
+        studio.mock(|when, then| {
+            when.body_contains("SubgraphPublishMutation");
+            then.status(200).json_body(published);
+        });
+        let args: &[&str] = if trace_http { &["--trace-http"] } else { &[] };
+
+        let recorder = SpanRecorder::default();
+        let subscriber = tracing_subscriber::registry().with(recorder.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            publish_to_studio(studio.url("/graphql"), API_KEY, GitContext::empty(), args).unwrap();
+        });
+        let messages = recorder.messages();
+        for message in &messages {
+            assert!(!message.contains(API_KEY), "{} has the API key", message);
+        }
+        messages
+    }
+
+    #[test]
+    fn test_trace_http_logs_redacted_requests_and_responses() {
+        let messages = trace_http_messages(true);
+
+        let requests: Vec<_> = messages
+            .iter()
+            .filter(|message| message.starts_with("request to "))
+            .collect();
+        assert_eq!(requests.len(), 2, "{:?}", messages);
+        assert!(requests[0].contains("SubgraphRoutingUrlQuery"));
+        assert!(requests[1].contains("SubgraphPublishMutation"));
+        assert!(requests[1].contains("type Query { hello: String }"));
+        let responses: Vec<_> = messages
+            .iter()
+            .filter(|message| message.starts_with("response from "))
+            .collect();
+        assert_eq!(responses.len(), 2, "{:?}", messages);
+        assert!(responses[0].contains("200 OK"));
+        assert!(responses[1].contains("published with user:gh.fake:****"));
+    }
+
+    #[test]
+    fn test_http_calls_are_not_traced_without_trace_http() {
+        let messages = trace_http_messages(false);
+
+        assert!(!messages
+            .iter()
+            .any(|message| message.starts_with("request to ")
+                || message.starts_with("response from ")));
+    }
+
+    /// publishes the accounts subgraph to `studio`, with `--routing-url` if
+    /// one is given, returning the error it fails with
+    fn publish_with_rejected_key(studio: &MockServer, routing_url: Option<&str>) -> RoverError {
+        let mut args = vec!["--max-retries", "2"];
+        if let Some(routing_url) = routing_url {
+            args.extend(["--routing-url", routing_url]);
+        }
+        publish_with_studio_args(studio.url("/graphql"), &args).unwrap_err()
+    }
+
+    fn assert_suggests_reauthenticating(err: &RoverError) {
+        assert!(
+            err.message()
+                .contains("Apollo Studio rejected your API key"),
+            "{}",
+            err
+        );
+        assert!(matches!(
+            err.suggestions(),
+            [RoverErrorSuggestion::ReauthenticateKey { .. }]
+        ));
+        assert_eq!(err.code(), Some(RoverErrorCode::E013));
+    }
+
+    #[test]
+    fn test_rejected_key_while_fetching_the_routing_url() {
+        let studio = MockServer::start();
+        let routing_url = studio.mock(|when, then| {
+            when.body_contains("SubgraphRoutingUrlQuery");
+            then.status(401).body("Unauthorized");
+        });
+        let publish = studio.mock(|when, then| {
+            when.body_contains("SubgraphPublishMutation");
+            then.status(200);
+        });
+
+        let err = publish_with_rejected_key(&studio, None);
+
+        assert_suggests_reauthenticating(&err);
+        assert!(err.message().contains("401 Unauthorized"));
+        // a rejected key isn't retried like a network error is
+        routing_url.assert_hits(1);
+        publish.assert_hits(0);
+
+        // when one is given, the routing url is only fetched to show how it
+        // changed, which a publish outside a terminal never does
+        publish_with_rejected_key(&studio, Some("https://accounts.example.com/graphql"));
+        routing_url.assert_hits(1);
+    }
+
+    #[test]
+    fn test_rejected_key_while_publishing() {
+        let studio = MockServer::start();
+        studio.mock(|when, then| {
+            when.body_contains("SubgraphRoutingUrlQuery");
+            then.status(200).json_body(json!({
+                "data": {
+                    "variant": {
+                        "__typename": "GraphVariant",
+                        "subgraph": { "url": "https://accounts.example.com/graphql" }
+                    }
+                }
+            }));
+        });
+        studio.mock(|when, then| {
+            when.body_contains("SubgraphPublishMutation");
+            then.status(200).json_body(json!({
+                "data": null,
+                "errors": [{
+                    "message": "This API key has expired.",
+                    "extensions": { "code": "UNAUTHENTICATED" }
+                }]
+            }));
+        });
+
+        let err = publish_with_rejected_key(&studio, None);
+
+        assert_suggests_reauthenticating(&err);
+        assert!(err.message().contains("This API key has expired."));
+    }
+
+    /// a Studio stand-in that answers publish mutations with `responses` in
+    /// order, since the same request needs different answers when retried.
+    /// returns its url and how many publish mutations it has received.
+    fn scripted_studio(responses: Vec<String>) -> (String, Arc<Mutex<usize>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/graphql", listener.local_addr().unwrap());
+        let publishes = Arc::new(Mutex::new(0));
+        let received = Arc::clone(&publishes);
+        thread::spawn(move || {
+            let mut responses = responses.into_iter();
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let body = read_request_body(&mut stream);
+                let response = if body.contains("SubgraphRoutingUrlQuery") {
+                    http_response(
+                        "200 OK",
+                        "",
+                        &json!({
+                            "data": {
+                                "variant": {
+                                    "__typename": "GraphVariant",
+                                    "subgraph": { "url": "https://accounts.example.com/graphql" }
+                                }
+                            }
+                        })
+                        .to_string(),
+                    )
+                } else {
+                    *received.lock().unwrap() += 1;
+                    match responses.next() {
+                        Some(response) => response,
+                        None => return,
+                    }
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, publishes)
+    }
+
+    fn read_request_body(stream: &mut TcpStream) -> String {
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        String::from_utf8(body).unwrap()
+    }
+
+    fn http_response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+            status,
+            body.len(),
+            headers,
+            body
+        )
+    }
+
+    /// mocks a routing url lookup and a publish that succeeds on `studio`,
+    /// returning the mock of the publish
+    fn mock_successful_publish(studio: &MockServer) -> httpmock::Mock<'_> {
+        studio.mock(|when, then| {
+            when.body_contains("SubgraphRoutingUrlQuery");
+            then.status(200).json_body(json!({
+                "data": {
+                    "variant": {
+                        "__typename": "GraphVariant",
+                        "subgraph": { "url": "https://accounts.example.com/graphql" }
+                    }
+                }
+            }));
+        });
+        studio.mock(|when, then| {
+            when.body_contains("SubgraphPublishMutation");
+            then.status(200).json_body(published_body());
+        })
+    }
+
+    /// the body of a successful publishSubgraph response
+    fn published_body() -> serde_json::Value {
+        json!({
+            "data": {
+                "graph": {
+                    "publishSubgraph": {
+                        "compositionConfig": { "schemaHash": "5gf564" },
+                        "errors": [],
+                        "didUpdateGateway": true,
+                        "serviceWasCreated": false,
+                        "launchCliCopy": null,
+                        "launchUrl": null
+                    }
+                }
+            }
+        })
+    }
+
+    fn publish_with_studio(url: String) -> RoverResult<RoverOutput> {
+        publish_with_studio_args(url, &[])
+    }
+
+    /// publishes to the Studio at `url` with `args` added
+    fn publish_with_studio_args(url: String, args: &[&str]) -> RoverResult<RoverOutput> {
+        publish_to_studio(url, "user:fake:key", GitContext::empty(), args)
+    }
+
+    /// a config for the Studio at `url` that authenticates with `api_key`,
+    /// keeping Rover's own config in `config_home`
+    fn studio_client_config(
+        url: String,
+        config_home: &assert_fs::TempDir,
+        api_key: &str,
+    ) -> StudioClientConfig {
+        let home = Utf8PathBuf::try_from(config_home.path().to_path_buf()).unwrap();
+        StudioClientConfig::new(
+            Some(url),
+            Config::new(Some(&home), Some(api_key.to_string())).unwrap(),
+            false,
+            ClientBuilder::default(),
+        )
+    }
+
+    /// publishes the accounts subgraph to the Studio at `url` with `args`
+    /// added, authenticating with `api_key` and sending `git_context`
+    fn publish_to_studio(
+        url: String,
+        api_key: &str,
+        git_context: GitContext,
+        args: &[&str],
+    ) -> RoverResult<RoverOutput> {
+        let config_home = assert_fs::TempDir::new().unwrap();
+        let schema = config_home.child("schema.graphql");
+        schema.write_str("type Query { hello: String }").unwrap();
+        let client_config = studio_client_config(url, &config_home, api_key);
+        let mut publish_args = vec![
+            "publish",
+            "my-graph@current",
+            "--name",
+            "accounts",
+            "--convert",
+            "--schema",
+            schema.path().to_str().unwrap(),
+        ];
+        publish_args.extend_from_slice(args);
+        Publish::parse_from(publish_args).run(client_config, git_context, 1, true)
+    }
+
+    /// a Studio that takes a while to answer each publish, returning its url,
+    /// how many publishes it received and the most it was answering at once
+    fn slow_studio() -> (String, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/graphql", listener.local_addr().unwrap());
+        let publishes = Arc::new(AtomicUsize::new(0));
+        let most_in_flight = Arc::new(AtomicUsize::new(0));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let (received, most) = (Arc::clone(&publishes), Arc::clone(&most_in_flight));
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let (received, most, in_flight) = (
+                    Arc::clone(&received),
+                    Arc::clone(&most),
+                    Arc::clone(&in_flight),
+                );
+                thread::spawn(move || {
+                    let body = read_request_body(&mut stream);
+                    let body = if body.contains("SubgraphRoutingUrlQuery") {
+                        json!({
+                            "data": { "variant": { "__typename": "GraphVariant", "subgraph": null } }
+                        })
+                    } else {
+                        received.fetch_add(1, Ordering::SeqCst);
+                        let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        most.fetch_max(now, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(200));
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        published_body()
+                    };
+                    let response = http_response("200 OK", "", &body.to_string());
+                    stream.write_all(response.as_bytes()).unwrap();
+                });
+            }
+        });
+        (url, publishes, most_in_flight)
+    }
+
+    const MANIFEST_SUBGRAPHS: [&str; 4] = ["accounts", "products", "reviews", "inventory"];
+
+    /// publishes a manifest of [`MANIFEST_SUBGRAPHS`] to a [`slow_studio`] with
+    /// `--max-concurrency max_concurrency`, returning the output and how many
+    /// publishes were received and in flight at most
+    fn publish_manifest_to_slow_studio(
+        max_concurrency: &str,
+        client_builder: ClientBuilder,
+    ) -> (RoverOutput, usize, usize) {
+        let (url, publishes, most_in_flight) = slow_studio();
+        let config_home = assert_fs::TempDir::new().unwrap();
+        let subgraphs = MANIFEST_SUBGRAPHS;
+        let mut manifest = String::new();
+        for subgraph in subgraphs {
+            config_home
+                .child(format!("{}.graphql", subgraph))
+                .write_str("type Query { hello: String }")
+                .unwrap();
+            manifest.push_str(&format!(
+                "{subgraph}:\n  routing_url: https://{subgraph}.example.com/graphql\n  schema_path: {subgraph}.graphql\n"
+            ));
+        }
+        let manifest_path = config_home.child("subgraphs.yaml");
+        manifest_path.write_str(&manifest).unwrap();
+        let home = Utf8PathBuf::try_from(config_home.path().to_path_buf()).unwrap();
+        let client_config = StudioClientConfig::new(
+            Some(url),
+            Config::new(Some(&home), Some("user:fake:key".to_string())).unwrap(),
+            false,
+            client_builder,
+        );
+
+        let output = Publish::parse_from([
+            "publish",
+            "my-graph@current",
+            "--convert",
+            "--max-concurrency",
+            max_concurrency,
+            "--manifest",
+            manifest_path.path().to_str().unwrap(),
+        ])
+        .run(client_config, GitContext::empty(), 1, true)
+        .unwrap();
+        (
+            output,
+            publishes.load(Ordering::SeqCst),
+            most_in_flight.load(Ordering::SeqCst),
+        )
+    }
+
+    #[test]
+    fn test_manifest_publishes_are_sent_concurrently() {
+        let (output, publishes, most_in_flight) =
+            publish_manifest_to_slow_studio("2", ClientBuilder::default());
+
+        assert_eq!(publishes, MANIFEST_SUBGRAPHS.len());
+        assert_eq!(most_in_flight, 2);
+        let RoverOutput::SubgraphPublishBatch { publishes, .. } = output else {
+            panic!("expected a batch of publishes, got {:?}", output);
+        };
+        let published: Vec<_> = publishes
+            .iter()
+            .map(|timed| match &timed.publish {
+                RoverOutput::SubgraphPublishResponse { subgraph, .. } => subgraph.as_str(),
+                publish => panic!("expected a publish, got {:?}", publish),
+            })
+            .collect();
+        assert_eq!(published, MANIFEST_SUBGRAPHS);
+    }
+
+    #[test]
+    fn test_manifest_publishes_keep_to_the_max_requests_per_host() {
+        let (_, publishes, most_in_flight) = publish_manifest_to_slow_studio(
+            "4",
+            ClientBuilder::default().with_max_requests_per_host(NonZeroUsize::new(2).unwrap()),
+        );
+
+        assert_eq!(publishes, MANIFEST_SUBGRAPHS.len());
+        assert_eq!(most_in_flight, 2);
+    }
+
+    #[test]
+    fn test_no_git_context_sends_an_empty_git_context() {
+        let detected = GitContext {
+            branch: Some("main".to_string()),
+            author: Some("Anna Developer <anna@example.com>".to_string()),
+            commit: Some("f84b32caddddb5e8ec1d5fc126bec2b1e8a1b3a3".to_string()),
+            remote_url: Some("https://github.com/example/accounts".to_string()),
+            branch_source: Some(GitContextSource::Git),
+            commit_source: Some(GitContextSource::Git),
+        };
+        let args = [
+            "publish",
+            "my-graph@current",
+            "--name",
+            "accounts",
+            "--schema",
+            "schema.graphql",
+        ];
+
+        let publish = Publish::parse_from(args);
+        assert_eq!(publish.git_context(detected.clone()), detected);
+        assert_eq!(
+            serde_json::to_value(&publish).unwrap()["no_git_context"],
+            false
+        );
+
+        let publish = Publish::parse_from(args.iter().chain(&["--no-git-context"]));
+        assert_eq!(publish.git_context(detected), GitContext::empty());
+        assert_eq!(
+            serde_json::to_value(&publish).unwrap()["no_git_context"],
+            true
+        );
+    }
+
+    #[test]
+    fn test_no_git_context_is_not_sent_to_studio() {
+        let studio = MockServer::start();
+        let with_git_context = studio.mock(|when, then| {
+            when.body_contains("f84b32caddddb5e8ec1d5fc126bec2b1e8a1b3a3");
+            then.status(400);
+        });
+        let publishes = mock_successful_publish(&studio);
+        let git_context = GitContext {
+            branch: Some("main".to_string()),
+            author: None,
+            commit: Some("f84b32caddddb5e8ec1d5fc126bec2b1e8a1b3a3".to_string()),
+            remote_url: None,
+            branch_source: Some(GitContextSource::Git),
+            commit_source: Some(GitContextSource::Git),
+        };
+
+        publish_to_studio(
+            studio.url("/graphql"),
+            "user:fake:key",
+            git_context,
+            &["--no-git-context"],
+        )
+        .unwrap();
+
+        publishes.assert();
+        with_git_context.assert_hits(0);
+    }
+
+    #[test]
+    fn test_watch_republishes_each_change_to_the_schema() {
+        let studio = MockServer::start();
+        let publishes = mock_successful_publish(&studio);
+        let config_home = assert_fs::TempDir::new().unwrap();
+        let schema = config_home.child("schema.graphql");
+        schema.write_str("type Query { hello: String }").unwrap();
+        let schema_path = Utf8PathBuf::try_from(schema.path().to_path_buf()).unwrap();
+        let client_config =
+            studio_client_config(studio.url("/graphql"), &config_home, "user:fake:key");
+        let git_context = GitContext::empty();
+        let settings = PublishSettings {
+            client_config: &client_config,
+            git_context: &git_context,
+            routing_url_fetch_attempts: 1,
+            publish_attempts: 1,
+            quiet: true,
+            interactive: false,
+            progress: false,
+            routing_url_validation: RoutingUrlValidation::Warn,
+            status: &StatusStream::new(StatusFormat::Text),
+        };
+        let publish = Publish::parse_from([
+            "publish",
+            "my-graph@current",
+            "--name",
+            "accounts",
+            "--convert",
+            "--watch",
+            "--schema",
+            schema_path.as_str(),
+        ]);
+
+        // stands in for the file watcher, saving the next change to the schema
+        // once the last one has been handled and ending the watch after the last
+        let changes = [
+            "type Query { hello: String! }",
+            "type Query { hello: String! }",
+            "type Query { hello: String, goodbye: String }",
+        ]
+        .into_iter()
+        .map(|sdl| {
+            schema.write_str(sdl).unwrap();
+            schema_path.clone()
+        });
+        publish.republish_on_change(
+            "accounts",
+            &None,
+            &schema_path,
+            rover_std::Fs::sha256(&schema_path).unwrap(),
+            changes,
+            &settings,
+        );
+
+        // the second write didn't change anything, so it isn't published
+        publishes.assert_hits(2);
+    }
+
+    #[test]
+    fn test_publish_waits_when_rate_limited() {
+        let (url, publishes) = scripted_studio(vec![
+            http_response("429 Too Many Requests", "Retry-After: 1\r\n", ""),
+            published_response(),
+        ]);
+
+        let started = Instant::now();
+        let output = publish_with_studio(url).unwrap();
+
+        assert!(matches!(
+            output,
+            RoverOutput::SubgraphPublishResponse { .. }
+        ));
+        assert_eq!(*publishes.lock().unwrap(), 2);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_publish_gives_up_on_persistent_server_errors() {
+        let unavailable = http_response("503 Service Unavailable", "", "");
+        let (url, publishes) = scripted_studio(vec![unavailable; retry::DEFAULT_ATTEMPTS + 1]);
+
+        let error = publish_with_studio(url).unwrap_err();
+
+        assert!(error.to_string().contains("503"), "{}", error);
+        assert_eq!(*publishes.lock().unwrap(), retry::DEFAULT_ATTEMPTS);
+    }
+
+    fn published_response() -> String {
+        http_response("200 OK", "", &published_body().to_string())
+    }
+
+    fn publish_attempts(output: RoverOutput) -> PublishAttempts {
+        match output {
+            RoverOutput::SubgraphPublishResponse { attempts, .. } => attempts,
+            output => panic!("expected a publish response, got {:?}", output),
+        }
+    }
+
+    #[test]
+    fn test_publish_counts_a_single_attempt_when_the_first_succeeds() {
+        let (url, publishes) = scripted_studio(vec![published_response()]);
+
+        let output = publish_with_studio_args(url, &["--max-retries", "2"]).unwrap();
+
+        assert_eq!(
+            publish_attempts(output),
+            PublishAttempts {
+                routing_url_fetch: 1,
+                publish: 1
+            }
+        );
+        assert_eq!(*publishes.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_publish_counts_the_attempts_after_a_retry() {
+        let (url, publishes) = scripted_studio(vec![
+            http_response("503 Service Unavailable", "", ""),
+            published_response(),
+        ]);
+
+        let output = publish_with_studio_args(url, &["--max-retries", "1"]).unwrap();
+
+        assert_eq!(publish_attempts(output).publish, 2);
+        assert_eq!(*publishes.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_publish_stops_after_max_retries() {
+        let unavailable = http_response("503 Service Unavailable", "", "");
+        let (url, publishes) = scripted_studio(vec![unavailable; 4]);
+
+        let error = publish_with_studio_args(url, &["--max-retries", "1"]).unwrap_err();
+
+        assert!(error.to_string().contains("503"), "{}", error);
+        assert_eq!(*publishes.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_routing_url_is_fetched_once_without_retries() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/graphql", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(0));
+        let received = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                read_request_body(&mut stream);
+                *received.lock().unwrap() += 1;
+                let unavailable = http_response("503 Service Unavailable", "", "");
+                stream.write_all(unavailable.as_bytes()).unwrap();
+            }
+        });
+
+        assert!(publish_with_studio_args(url, &["--max-retries", "0"]).is_err());
+        assert_eq!(*requests.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_publish_is_attempted_once_without_retries() {
+        let (url, publishes) = scripted_studio(vec![
+            http_response("503 Service Unavailable", "", ""),
+            published_response(),
+        ]);
+
+        assert!(publish_with_studio_args(url, &["--max-retries", "0"]).is_err());
+        assert_eq!(*publishes.lock().unwrap(), 1);
     }
 }
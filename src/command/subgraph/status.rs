@@ -0,0 +1,223 @@
+use std::future::Future;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use chrono::{SecondsFormat, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{RoverError, RoverOutput};
+
+/// How `rover subgraph publish --status-format` reports its progress
+#[derive(Default, ValueEnum, Debug, Serialize, Clone, Copy, Eq, PartialEq)]
+pub enum StatusFormat {
+    /// the usual messages for people
+    #[default]
+    Text,
+    /// a JSON event per line on stderr, for tools to follow along
+    Json,
+}
+
+/// Newline-delimited JSON events on the progress of a publish, written with
+/// `--status-format json`. Every event has a `timestamp`, a `phase`, a
+/// `status` and the `subgraph` it's about:
+///
+/// ```json
+/// {"timestamp":"2023-09-21T12:00:00.000Z","phase":"authenticate","status":"started","subgraph":"accounts"}
+/// {"timestamp":"2023-09-21T12:00:00.120Z","phase":"authenticate","status":"completed","subgraph":"accounts"}
+/// {"timestamp":"2023-09-21T12:00:01.000Z","phase":"result","status":"success","subgraph":"accounts"}
+/// ```
+///
+/// phases are `started` and then `completed` or `failed`, and each subgraph
+/// ends with a `result` phase, whose status is how its publish went.
+pub(crate) struct StatusStream {
+    writer: Option<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl StatusStream {
+    /// events are only written to stderr for `StatusFormat::Json`
+    pub(crate) fn new(format: StatusFormat) -> Self {
+        match format {
+            StatusFormat::Text => Self { writer: None },
+            StatusFormat::Json => Self::with_writer(Box::new(io::stderr())),
+        }
+    }
+
+    pub(crate) fn with_writer(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Some(Mutex::new(writer)),
+        }
+    }
+
+    /// whether events are being written, which replaces the usual messages
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// runs `f` between a `started` event for `phase` and a `completed`
+    /// one, or a `failed` one if it returns an error
+    pub(crate) fn phase<T, E>(
+        &self,
+        subgraph: &str,
+        phase: &str,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        self.emit(subgraph, phase, "started");
+        let result = f();
+        self.end_phase(subgraph, phase, result.is_ok());
+        result
+    }
+
+    /// runs `future` between events like [`StatusStream::phase`]
+    pub(crate) async fn phase_async<T, E>(
+        &self,
+        subgraph: &str,
+        phase: &str,
+        future: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        self.emit(subgraph, phase, "started");
+        let result = future.await;
+        self.end_phase(subgraph, phase, result.is_ok());
+        result
+    }
+
+    fn end_phase(&self, subgraph: &str, phase: &str, succeeded: bool) {
+        self.emit(
+            subgraph,
+            phase,
+            if succeeded { "completed" } else { "failed" },
+        );
+    }
+
+    /// the `result` event for a publish of `subgraph`
+    pub(crate) fn result(&self, subgraph: &str, result: Result<&RoverOutput, &RoverError>) {
+        let status = match result {
+            Ok(RoverOutput::SubgraphPublishResponse {
+                publish_response, ..
+            }) if !publish_response.build_errors.is_empty() => "composition_failed",
+            Ok(RoverOutput::SubgraphPublishDryRun { .. }) => "dry_run",
+            Ok(RoverOutput::Skipped { .. }) => "skipped",
+            Ok(_) => "success",
+            Err(_) => "failed",
+        };
+        self.emit(subgraph, "result", status);
+    }
+
+    fn emit(&self, subgraph: &str, phase: &str, status: &str) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+        let event = json!({
+            "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            "phase": phase,
+            "status": status,
+            "subgraph": subgraph,
+        });
+        // subgraphs in a manifest are published at once, so each line is
+        // written while holding the lock to keep them from interleaving
+        let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(writer, "{}", event).and_then(|_| writer.flush()) {
+            tracing::debug!("could not write a status event: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::Value;
+
+    use super::StatusStream;
+    use crate::{RoverError, RoverOutput, RoverResult};
+
+    #[derive(Clone, Default)]
+    struct Events(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Events {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Events {
+        fn lines(&self) -> Vec<Value> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    #[test]
+    fn it_wraps_a_phase_in_started_and_completed_events() {
+        let events = Events::default();
+        let status = StatusStream::with_writer(Box::new(events.clone()));
+
+        let ok: Result<_, ()> = status.phase("accounts", "read_schema", || Ok(1));
+        assert_eq!(ok, Ok(1));
+        let failed: Result<(), _> = status.phase("accounts", "publish_mutation", || Err(()));
+        assert!(failed.is_err());
+
+        let lines = events.lines();
+        let statuses: Vec<(&str, &str)> = lines
+            .iter()
+            .map(|event| {
+                (
+                    event["phase"].as_str().unwrap(),
+                    event["status"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("read_schema", "started"),
+                ("read_schema", "completed"),
+                ("publish_mutation", "started"),
+                ("publish_mutation", "failed"),
+            ]
+        );
+        for event in &lines {
+            assert_eq!(event["subgraph"], "accounts");
+            assert!(event["timestamp"].as_str().unwrap().ends_with('Z'));
+        }
+    }
+
+    #[test]
+    fn it_reports_the_result_of_a_publish() {
+        let events = Events::default();
+        let status = StatusStream::with_writer(Box::new(events.clone()));
+
+        let skipped: RoverResult<RoverOutput> = Ok(RoverOutput::Skipped {
+            reason: None,
+            subgraph: Some("accounts".to_string()),
+            graph_ref: None,
+        });
+        status.result("accounts", skipped.as_ref());
+        status.result("products", Err(&RoverError::new(anyhow::anyhow!("oops"))));
+
+        let lines = events.lines();
+        assert_eq!(lines[0]["phase"], "result");
+        assert_eq!(lines[0]["status"], "skipped");
+        assert_eq!(lines[1]["subgraph"], "products");
+        assert_eq!(lines[1]["status"], "failed");
+    }
+
+    #[test]
+    fn it_writes_nothing_for_text() {
+        let status = StatusStream::new(super::StatusFormat::Text);
+        assert!(!status.is_enabled());
+        assert_eq!(
+            status.phase("accounts", "authenticate", || Ok::<_, ()>(())),
+            Ok(())
+        );
+    }
+}
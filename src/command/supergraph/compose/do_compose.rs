@@ -218,7 +218,8 @@ mod tests {
             &FileDescriptorType::File(config_path),
             get_studio_config(),
             &ProfileOpt {
-                profile_name: "profile".to_string()
+                profile_name: "profile".to_string(),
+                config_dir: None,
             }
         )
         .is_err())
@@ -248,7 +249,8 @@ mod tests {
             &FileDescriptorType::File(config_path),
             get_studio_config(),
             &ProfileOpt {
-                profile_name: "profile".to_string()
+                profile_name: "profile".to_string(),
+                config_dir: None,
             }
         )
         .is_ok())
@@ -282,6 +284,7 @@ mod tests {
             get_studio_config(),
             &ProfileOpt {
                 profile_name: "profile".to_string(),
+                config_dir: None,
             },
         )
         .unwrap()
@@ -37,6 +37,14 @@ pub struct RoverErrorMetadata {
 /// and creating `Suggestion`s and `Code`s where applicable
 impl From<&mut anyhow::Error> for RoverErrorMetadata {
     fn from(error: &mut anyhow::Error) -> Self {
+        // cancelling isn't a failure that needs explaining, so only the message is printed
+        if super::is_interrupted(error) {
+            return RoverErrorMetadata {
+                skip_printing_cause: true,
+                ..Default::default()
+            };
+        }
+
         let mut skip_printing_cause = false;
         if let Some(rover_client_error) = error.downcast_ref::<RoverClientError>() {
             let (suggestion, code) = match rover_client_error {
@@ -116,6 +124,23 @@ impl From<&mut anyhow::Error> for RoverErrorMetadata {
                         (None, Some(RoverErrorCode::E004))
                     }
                 }
+                RoverClientError::OperationTimedOut { .. } => (
+                    Some(RoverErrorSuggestion::IncreaseTimeout),
+                    Some(RoverErrorCode::E031),
+                ),
+                RoverClientError::RateLimited { retry_after, .. } => {
+                    let wait = match retry_after {
+                        Some(retry_after) => format!("{} seconds", retry_after.as_secs()),
+                        None => "a little while".to_string(),
+                    };
+                    (
+                        Some(RoverErrorSuggestion::Adhoc(format!(
+                            "Wait {} and try again.",
+                            wait
+                        ))),
+                        Some(RoverErrorCode::E012),
+                    )
+                }
                 RoverClientError::MalformedResponse { null_field: _ } => (
                     Some(RoverErrorSuggestion::SubmitIssue),
                     Some(RoverErrorCode::E005),
@@ -232,6 +257,12 @@ impl From<&mut anyhow::Error> for RoverErrorMetadata {
 
                     (Some(suggestion_key), Some(RoverErrorCode::E013))
                 }
+                RoverClientError::AuthenticationFailed { .. } => (
+                    Some(RoverErrorSuggestion::ReauthenticateKey {
+                        key_from_env: std::env::var(RoverEnvKey::Key.to_string()).is_ok(),
+                    }),
+                    Some(RoverErrorCode::E013),
+                ),
                 RoverClientError::MalformedKey => (
                     Some(RoverErrorSuggestion::ProperKey),
                     Some(RoverErrorCode::E014),
@@ -372,6 +403,14 @@ impl From<&mut anyhow::Error> for RoverErrorMetadata {
                     Some(RoverErrorSuggestion::SubmitIssue),
                     Some(RoverErrorCode::E026),
                 ),
+                HoustonProblem::InvalidSettings { path, .. } => (
+                    Some(RoverErrorSuggestion::Adhoc(format!(
+                        "Fix the settings in {}, or remove the file to use the defaults.",
+                        path
+                    ))),
+                    None,
+                ),
+                HoustonProblem::EmptyApiKeyFile(_) => (None, None),
                 HoustonProblem::AdhocError(_) => (None, None),
                 HoustonProblem::RoverStdError(_) => (None, None),
             };
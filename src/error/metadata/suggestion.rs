@@ -30,6 +30,11 @@ pub enum RoverErrorSuggestion {
     Adhoc(String),
     CheckKey,
     TryUnsetKey,
+    /// the API key was rejected, so a new one needs to be saved, or set in
+    /// `$APOLLO_KEY` if that's where it came from
+    ReauthenticateKey {
+        key_from_env: bool,
+    },
     ValidComposeFile,
     ValidComposeRoutingUrl,
     ProperKey,
@@ -59,6 +64,7 @@ pub enum RoverErrorSuggestion {
     },
     FixLintFailure,
     IncreaseClientTimeout,
+    IncreaseTimeout,
     IncreaseChecksTimeout {
         url: Option<String>,
     },
@@ -84,6 +90,9 @@ pub enum RoverErrorSuggestion {
         graph_ref: String,
     },
     AllowInvalidRoutingUrlOrSpecifyValidUrl,
+    CheckNetworkConnectivity,
+    ProvideSchemaFile,
+    FixSubgraphName,
 }
 
 impl Display for RoverErrorSuggestion {
@@ -186,6 +195,20 @@ TryUnsetKey => {
                     Style::Command.paint("`--profile default`")
                 )
             }
+ReauthenticateKey { key_from_env: true } => {
+                format!(
+                    "Set {} to a valid API key, or unset it and run {} to save a new one.",
+                    Style::Command.paint(format!("`${}`", RoverEnvKey::Key)),
+                    Style::Command.paint("`rover config auth`")
+                )
+            }
+ReauthenticateKey { key_from_env: false } => {
+                format!(
+                    "Run {} to save a new API key, adding {} for a profile other than the default one.",
+                    Style::Command.paint("`rover config auth`"),
+                    Style::Command.paint("`--profile <name>`")
+                )
+            }
 ProperKey => {
                 format!("Try running {} for more details on Apollo's API keys.", Style::Command.paint("`rover docs open api-keys`"))
             }
@@ -234,6 +257,7 @@ FixDownstreamCheckFailure { target_url } => format!("The changes in the schema y
 FixOtherCheckTaskFailure { target_url } => format!("See {} to view the failure reason for the check.", Style::Link.paint(target_url)),
 FixLintFailure => "The schema you submitted contains lint violations. Please address the violations and resubmit the schema.".to_string(),
 IncreaseClientTimeout => "You can try increasing the timeout value by passing a higher value to the --client-timeout option.".to_string(),
+IncreaseTimeout => "Try again, or allow more time by passing a higher value to the --timeout option.".to_string(),
 IncreaseChecksTimeout {url} => format!("You can try increasing the timeout value by setting APOLLO_CHECKS_TIMEOUT_SECONDS to a higher value in your env. The default value is 300 seconds. You can also view the live check progress by visiting {}.", Style::Link.paint(url.clone().unwrap_or_else(|| "https://studio.apollographql.com".to_string()))),
 FixChecksInput { graph_ref } => format!("Graph {} has no published schema or is not a composition variant. Please publish a schema or use a different variant.", Style::Link.paint(graph_ref.to_string())),
 UpgradePlan => "Rover has likely reached rate limits while running graph or subgraph checks. Please try again later or contact your graph admin about upgrading your billing plan.".to_string(),
@@ -253,7 +277,10 @@ UpgradePlan => "Rover has likely reached rate limits while running graph or subg
             PublishSubgraphWithRoutingUrl { graph_ref, subgraph_name } => {
                 format!("Try publishing the subgraph with a routing URL like so `rover subgraph publish {graph_ref} --name {subgraph_name} --routing-url <url>`")
             },
-            AllowInvalidRoutingUrlOrSpecifyValidUrl => format!("Try publishing the subgraph with a valid routing URL. If you are sure you want to publish an invalid routing URL, re-run this command with the {} option.", Style::Command.paint("`--allow-invalid-routing-url`"))
+            AllowInvalidRoutingUrlOrSpecifyValidUrl => format!("Try publishing the subgraph with a valid routing URL. If you are sure you want to publish an invalid routing URL, re-run this command with the {} option.", Style::Command.paint("`--allow-invalid-routing-url`")),
+            CheckNetworkConnectivity => format!("Check that this machine is connected to the internet and can resolve the host. If you connect through a proxy, make sure it is set with the {} or {} environment variable.", Style::Command.paint("HTTPS_PROXY"), Style::Command.paint("HTTP_PROXY")),
+            ProvideSchemaFile => format!("If nothing is meant to be piped to Rover, pass the schema's path with {} instead.", Style::Command.paint("`--schema <file>`")),
+            FixSubgraphName => "Rover only publishes subgraph names that start with a letter, contain only letters, numbers, underscores (`_`), and hyphens (`-`), and are at most 64 characters long.".to_string(),
         };
         write!(formatter, "{}", &suggestion)
     }
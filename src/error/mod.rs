@@ -1,12 +1,16 @@
 mod metadata;
+mod warning;
 
 pub use metadata::{RoverErrorCode, RoverErrorMetadata, RoverErrorSuggestion};
+pub use rover_std::prompt::INTERRUPTED_EXIT_CODE;
+pub use warning::PublishWarning;
+pub(crate) use warning::StrictWarnings;
 
 pub type RoverResult<T> = std::result::Result<T, RoverError>;
 
 use calm_io::{stderr, stdoutln};
 use rover_client::RoverClientError;
-use rover_std::Style;
+use rover_std::{prompt, RoverStdError, Style};
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 use serde_json::{json, Value};
@@ -14,6 +18,7 @@ use serde_json::{json, Value};
 use std::borrow::BorrowMut;
 use std::error::Error;
 use std::fmt::{self, Debug, Display};
+use std::io;
 
 use apollo_federation_types::build::BuildErrors;
 
@@ -29,6 +34,10 @@ pub struct RoverError {
 
     #[serde(flatten)]
     metadata: RoverErrorMetadata,
+
+    /// a line printed to stdout before the error, like the one from `--summary-only`
+    #[serde(skip)]
+    summary: Option<String>,
 }
 
 fn serialize_anyhow<S>(error: &anyhow::Error, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -38,20 +47,30 @@ where
     let top_level_struct = "error";
     let message_field_name = "message";
     let details_struct = "details";
+    // messages can include styled text like SDL snippets, which doesn't belong in JSON
+    let message = Style::strip(&error.to_string());
 
     if let Some(rover_client_error) = error.downcast_ref::<RoverClientError>() {
         if let Some(rover_client_error_source) = rover_client_error.source() {
             if let Some(build_errors) = rover_client_error_source.downcast_ref::<BuildErrors>() {
                 let mut top_level_data = serializer.serialize_struct(top_level_struct, 2)?;
-                top_level_data.serialize_field(message_field_name, &error.to_string())?;
+                top_level_data.serialize_field(message_field_name, &message)?;
                 top_level_data.serialize_field(details_struct, &build_errors)?;
                 return top_level_data.end();
             }
         }
     }
 
+    // warnings that failed a publish with `--strict` keep their codes
+    if let Some(StrictWarnings(warnings)) = error.downcast_ref::<StrictWarnings>() {
+        let mut top_level_data = serializer.serialize_struct(top_level_struct, 2)?;
+        top_level_data.serialize_field(message_field_name, &message)?;
+        top_level_data.serialize_field(details_struct, warnings)?;
+        return top_level_data.end();
+    }
+
     let mut data = serializer.serialize_struct(top_level_struct, 1)?;
-    data.serialize_field(message_field_name, &error.to_string())?;
+    data.serialize_field(message_field_name, &message)?;
     data.end()
 }
 
@@ -63,7 +82,11 @@ impl RoverError {
         let mut error = error.into();
         let metadata = RoverErrorMetadata::from(error.borrow_mut());
 
-        Self { error, metadata }
+        Self {
+            error,
+            metadata,
+            summary: None,
+        }
     }
 
     pub fn set_suggestion(&mut self, suggestion: RoverErrorSuggestion) {
@@ -75,6 +98,14 @@ impl RoverError {
         self
     }
 
+    /// prints `summary` to stdout along with the error, which still goes to stderr
+    pub fn with_summary(self, summary: String) -> Self {
+        Self {
+            summary: Some(summary),
+            ..self
+        }
+    }
+
     pub fn suggestions(&self) -> &[RoverErrorSuggestion] {
         &self.metadata.suggestions
     }
@@ -87,6 +118,15 @@ impl RoverError {
         self.metadata.code.clone()
     }
 
+    /// the code Rover exits with because of this error
+    pub fn exit_code(&self) -> i32 {
+        if is_interrupted(&self.error) {
+            INTERRUPTED_EXIT_CODE
+        } else {
+            1
+        }
+    }
+
     pub fn print(&self) -> RoverResult<()> {
         match self.error.downcast_ref::<RoverClientError>() {
             Some(RoverClientError::CheckWorkflowFailure {
@@ -98,6 +138,9 @@ impl RoverError {
             }
             _ => (),
         }
+        if let Some(summary) = &self.summary {
+            stdoutln!("{}", summary)?;
+        }
 
         stderr!("{}", self)?;
         Ok(())
@@ -151,6 +194,17 @@ impl Display for RoverError {
     }
 }
 
+/// whether an error came from the user cancelling a prompt, e.g. with Ctrl-C
+fn is_interrupted(error: &anyhow::Error) -> bool {
+    if let Some(io_error) = error.downcast_ref::<io::Error>() {
+        return prompt::is_interrupted(io_error);
+    }
+    matches!(
+        error.downcast_ref::<RoverStdError>(),
+        Some(RoverStdError::Interrupted)
+    )
+}
+
 impl<E: Into<anyhow::Error>> From<E> for RoverError {
     fn from(error: E) -> Self {
         Self::new(error)
@@ -0,0 +1,34 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use serde::Serialize;
+
+/// Something about a publish that's worth checking, like a routing url that
+/// only works locally. They're printed as they come up, and included in the
+/// output so that `--format json` has them too
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PublishWarning {
+    /// what kind of warning this is, which doesn't change between versions
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Display for PublishWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The warnings that failed a publish with `--strict`, which are listed in
+/// the error's details for `--format json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StrictWarnings(pub(crate) Vec<PublishWarning>);
+
+impl Display for StrictWarnings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self.0.iter().map(|w| w.message.as_str()).collect();
+        write!(f, "{}", messages.join("\n"))
+    }
+}
+
+impl Error for StrictWarnings {}
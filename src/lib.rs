@@ -5,5 +5,5 @@ mod options;
 pub mod utils;
 
 pub use command::RoverOutput;
-pub use error::{RoverError, RoverErrorCode, RoverErrorSuggestion, RoverResult};
+pub use error::{PublishWarning, RoverError, RoverErrorCode, RoverErrorSuggestion, RoverResult};
 pub use utils::pkg::*;
@@ -1,13 +1,71 @@
+use anyhow::Context;
+use camino::Utf8PathBuf;
 use clap::Parser;
+use houston::{Config, Profile};
+use rover_std::Fs;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
+use crate::{RoverError, RoverErrorSuggestion, RoverResult};
+
 #[derive(Debug, Clone, Serialize, Deserialize, Parser)]
 pub struct ProfileOpt {
     /// Name of configuration profile to use
     #[arg(long = "profile", default_value = "default")]
     #[serde(skip_serializing)]
     pub profile_name: String,
+
+    /// Directory to read configuration profiles and credentials from,
+    /// instead of the default one
+    #[arg(long = "config-dir", value_name = "PATH")]
+    #[serde(skip_serializing)]
+    pub config_dir: Option<Utf8PathBuf>,
+}
+
+impl ProfileOpt {
+    /// `config`, reading profiles from `--config-dir` if it was passed.
+    /// unlike the default config directory, it's never created.
+    pub fn config(&self, config: &Config) -> anyhow::Result<Config> {
+        let Some(config_dir) = &self.config_dir else {
+            return Ok(config.clone());
+        };
+        Fs::get_dir_entries(config_dir).with_context(|| {
+            format!(
+                "Could not read configuration profiles from {}. It must be an existing, readable directory",
+                config_dir
+            )
+        })?;
+        Ok(Config {
+            home: config_dir.clone(),
+            ..config.clone()
+        })
+    }
+
+    /// checks that the profile exists before any requests are made, naming
+    /// the ones that do if it doesn't. an API key from the environment is
+    /// used instead of any profile, and having no profiles at all is left
+    /// to the usual error when loading credentials.
+    pub fn validate(&self, config: &Config) -> RoverResult<()> {
+        let config = self.config(config)?;
+        if config.override_api_key.is_some() {
+            return Ok(());
+        }
+        let mut profiles = Profile::list(&config)?;
+        if profiles.is_empty() || profiles.contains(&self.profile_name) {
+            return Ok(());
+        }
+        profiles.sort();
+        Err(RoverError::new(anyhow::anyhow!(
+            "There is no profile named `{}`. The available profiles are: {}.",
+            self.profile_name,
+            profiles
+                .iter()
+                .map(|profile| format!("`{}`", profile))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .with_suggestion(RoverErrorSuggestion::ListProfiles))
+    }
 }
 
 impl Display for ProfileOpt {
@@ -15,3 +73,113 @@ impl Display for ProfileOpt {
         write!(f, "{}", &self.profile_name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8PathBuf;
+
+    use super::*;
+
+    fn profile(name: &str) -> ProfileOpt {
+        ProfileOpt {
+            profile_name: name.to_string(),
+            config_dir: None,
+        }
+    }
+
+    /// a config home containing `profiles`
+    fn config_with_profiles(
+        home: &assert_fs::TempDir,
+        profiles: &[&str],
+        override_api_key: Option<&str>,
+    ) -> Config {
+        let home = Utf8PathBuf::try_from(home.path().to_path_buf()).unwrap();
+        let config = Config::new(Some(&home), None).unwrap();
+        for name in profiles {
+            Profile::set_api_key(name, &config, "user:fake:key").unwrap();
+        }
+        Config::new(Some(&home), override_api_key.map(str::to_string)).unwrap()
+    }
+
+    #[test]
+    fn it_accepts_an_existing_profile() {
+        let home = assert_fs::TempDir::new().unwrap();
+        let config = config_with_profiles(&home, &["default", "staging"], None);
+
+        assert!(profile("staging").validate(&config).is_ok());
+    }
+
+    #[test]
+    fn it_names_the_available_profiles_when_the_profile_is_missing() {
+        let home = assert_fs::TempDir::new().unwrap();
+        let config = config_with_profiles(&home, &["staging", "default"], None);
+
+        let error = profile("prod").validate(&config).unwrap_err();
+
+        assert_eq!(
+            error.message(),
+            "There is no profile named `prod`. The available profiles are: `default`, `staging`."
+        );
+        assert!(matches!(
+            error.suggestions(),
+            [RoverErrorSuggestion::ListProfiles]
+        ));
+    }
+
+    #[test]
+    fn it_leaves_a_missing_default_profile_to_the_usual_error() {
+        let home = assert_fs::TempDir::new().unwrap();
+        let config = config_with_profiles(&home, &[], None);
+
+        assert!(profile("default").validate(&config).is_ok());
+    }
+
+    #[test]
+    fn it_reads_profiles_from_the_config_dir() {
+        let default_home = assert_fs::TempDir::new().unwrap();
+        let config = config_with_profiles(&default_home, &["default"], None);
+        let config_dir = assert_fs::TempDir::new().unwrap();
+        config_with_profiles(&config_dir, &["ci"], None);
+        let profile = ProfileOpt {
+            profile_name: "ci".to_string(),
+            config_dir: Some(Utf8PathBuf::try_from(config_dir.path().to_path_buf()).unwrap()),
+        };
+
+        assert!(profile.validate(&config).is_ok());
+        assert_eq!(
+            profile.config(&config).unwrap().home,
+            config_dir.path().to_str().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_errors_on_a_missing_config_dir() {
+        let home = assert_fs::TempDir::new().unwrap();
+        let config = config_with_profiles(&home, &["default"], None);
+        let missing = Utf8PathBuf::try_from(home.path().join("missing")).unwrap();
+        let profile = ProfileOpt {
+            profile_name: "default".to_string(),
+            config_dir: Some(missing.clone()),
+        };
+
+        let error = profile.config(&config).unwrap_err();
+
+        assert!(
+            error.to_string().contains(&format!(
+                "Could not read configuration profiles from {}",
+                missing
+            )),
+            "{}",
+            error
+        );
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn it_skips_validation_when_the_api_key_is_overridden() {
+        let home = assert_fs::TempDir::new().unwrap();
+        let config = config_with_profiles(&home, &["default"], Some("user:fake:key"));
+
+        assert!(profile("prod").validate(&config).is_ok());
+    }
+}
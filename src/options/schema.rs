@@ -1,14 +1,112 @@
+use anyhow::{anyhow, Context};
+use camino::{Utf8Path, Utf8PathBuf};
 use clap::Parser;
+use reqwest::{blocking::Client, Url};
+use rover_client::{EndpointKind, RoverClientError};
+use rover_std::prompt::{self, TimeoutReader};
 
-use crate::{utils::parsers::FileDescriptorType, RoverResult};
+use crate::{utils::parsers::FileDescriptorType, RoverError, RoverErrorSuggestion, RoverResult};
 
-use std::io::Read;
+use std::{
+    fmt,
+    io::{self, BufRead, IsTerminal, Read},
+    str::FromStr,
+    time::Duration,
+};
 
 #[derive(Debug, Parser)]
 pub struct SchemaOpt {
-    /// The schema file to check. You can pass `-` to use stdin instead of a file.
+    /// The schema file to check. You can pass `-` to use stdin instead of a file,
+    /// or an `http(s)://` URL to fetch the schema from. A file named `-` can
+    /// be passed as `./-`.
     #[arg(long, short = 's')]
-    schema: FileDescriptorType,
+    schema: SchemaSource,
+
+    /// Give up if nothing is piped to stdin within this many seconds when
+    /// reading the schema from `-`. Has no effect when stdin is a terminal.
+    #[arg(long, value_name = "SECONDS")]
+    schema_stdin_timeout: Option<u64>,
+}
+
+/// Where to read a schema from: a file, stdin, or a URL
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SchemaSource {
+    FileDescriptor(FileDescriptorType),
+    Url(Url),
+}
+
+impl SchemaSource {
+    /// reads the schema, only calling `get_client` if it's fetched from a URL
+    fn read(
+        &self,
+        file_description: &str,
+        stdin: &mut impl Read,
+        get_client: impl FnOnce() -> RoverResult<Client>,
+    ) -> RoverResult<String> {
+        match self {
+            Self::FileDescriptor(file_descriptor) => {
+                file_descriptor.read_file_descriptor(file_description, stdin)
+            }
+            Self::Url(url) => Self::fetch(url, file_description, &get_client()?),
+        }
+    }
+
+    /// fetches the contents of a URL, following redirects and respecting the
+    /// client's timeout
+    fn fetch(url: &Url, file_description: &str, client: &Client) -> RoverResult<String> {
+        tracing::info!("fetching {} from {}", file_description, url);
+        let to_rover_error = |source| {
+            RoverError::from(RoverClientError::SendRequest {
+                source,
+                endpoint_kind: EndpointKind::Customer,
+            })
+        };
+        let response = client.get(url.clone()).send().map_err(to_rover_error)?;
+        let status = response.status();
+        if !status.is_success() {
+            let mut err = RoverError::new(anyhow!(
+                "Could not fetch {} from {}. The server responded with status code {}.",
+                file_description,
+                url,
+                status
+            ));
+            err.set_suggestion(RoverErrorSuggestion::CheckServerConnection);
+            return Err(err);
+        }
+        let contents = response.text().map_err(to_rover_error)?;
+        if contents.trim().is_empty() {
+            let mut err = RoverError::new(anyhow!("The {} you passed was empty", file_description));
+            err.set_suggestion(RoverErrorSuggestion::Adhoc(format!(
+                "'{}' responded successfully, but with an empty body.",
+                url
+            )));
+            Err(err)
+        } else {
+            Ok(contents)
+        }
+    }
+}
+
+impl FromStr for SchemaSource {
+    type Err = io::Error;
+
+    fn from_str(input: &str) -> std::result::Result<Self, Self::Err> {
+        // anything that isn't an http(s) URL is treated as a path,
+        // which keeps Windows paths like `C:\schema.graphql` working
+        match Url::parse(input) {
+            Ok(url) if ["http", "https"].contains(&url.scheme()) => Ok(Self::Url(url)),
+            _ => FileDescriptorType::from_str(input).map(Self::FileDescriptor),
+        }
+    }
+}
+
+impl fmt::Display for SchemaSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileDescriptor(file_descriptor) => write!(f, "{}", file_descriptor),
+            Self::Url(url) => write!(f, "{}", url),
+        }
+    }
 }
 
 pub struct FileWithMetadata {
@@ -17,28 +115,263 @@ pub struct FileWithMetadata {
 }
 
 impl SchemaOpt {
+    /// reads the schema from a file, as if it was passed to `--schema`
+    pub(crate) fn from_file(path: Utf8PathBuf) -> Self {
+        Self {
+            schema: SchemaSource::FileDescriptor(FileDescriptorType::File(path)),
+            schema_stdin_timeout: None,
+        }
+    }
+
+    /// whether the schema is read from stdin, with `--schema -`
+    pub(crate) fn reads_from_stdin(&self) -> bool {
+        matches!(
+            self.schema,
+            SchemaSource::FileDescriptor(FileDescriptorType::Stdin)
+        )
+    }
+
+    /// the file the schema is read from, unless it's read from stdin or a URL
+    pub(crate) fn file_path(&self) -> Option<&Utf8Path> {
+        match &self.schema {
+            SchemaSource::FileDescriptor(FileDescriptorType::File(path)) => Some(path),
+            _ => None,
+        }
+    }
+
     pub(crate) fn read_file_descriptor(
         &self,
         file_description: &str,
         stdin: &mut impl Read,
+        client: &Client,
     ) -> RoverResult<String> {
-        self.schema.read_file_descriptor(file_description, stdin)
+        self.read(file_description, stdin, || Ok(client.clone()))
+    }
+
+    /// like [`SchemaOpt::read_file_descriptor`], but only calls `get_client`
+    /// if the schema is fetched from a URL, so that reading a file or stdin
+    /// never has to build a client
+    pub(crate) fn read_file_descriptor_with(
+        &self,
+        file_description: &str,
+        stdin: &mut impl Read,
+        get_client: impl FnOnce() -> RoverResult<Client>,
+    ) -> RoverResult<String> {
+        self.read(file_description, stdin, get_client)
+    }
+
+    fn read(
+        &self,
+        file_description: &str,
+        stdin: &mut impl Read,
+        get_client: impl FnOnce() -> RoverResult<Client>,
+    ) -> RoverResult<String> {
+        match (&self.schema, self.schema_stdin_timeout) {
+            (SchemaSource::FileDescriptor(FileDescriptorType::Stdin), Some(seconds))
+                if !io::stdin().is_terminal() =>
+            {
+                let piped = read_with_timeout(
+                    &mut prompt::stdin_with_timeout(None),
+                    Duration::from_secs(seconds),
+                    file_description,
+                )?;
+                // reuse the usual stdin handling, e.g. for empty input
+                self.schema
+                    .read(file_description, &mut piped.as_slice(), get_client)
+            }
+            _ => self.schema.read(file_description, stdin, get_client),
+        }
     }
 
     pub(crate) fn read_file_descriptor_with_metadata(
         &self,
         file_description: &str,
         stdin: &mut impl Read,
+        client: &Client,
     ) -> RoverResult<FileWithMetadata> {
-        match self.schema.read_file_descriptor(file_description, stdin) {
+        match self.read(file_description, stdin, || Ok(client.clone())) {
             Ok(proposed_schema) => Ok(FileWithMetadata {
                 schema: proposed_schema,
-                file_path: match &self.schema {
-                    FileDescriptorType::Stdin => "stdin".to_owned(),
-                    FileDescriptorType::File(file_path) => file_path.to_string(),
-                },
+                file_path: self.schema.to_string(),
             }),
             Err(e) => Err(e),
         }
     }
 }
+
+/// reads all of `stdin`, giving up if it hasn't produced any data (or closed)
+/// before `timeout` has elapsed
+fn read_with_timeout(
+    stdin: &mut TimeoutReader,
+    timeout: Duration,
+    file_description: &str,
+) -> RoverResult<Vec<u8>> {
+    stdin.set_timeout(Some(timeout));
+    let arrived = stdin.fill_buf().map(|_| ());
+    // once input has started arriving, the rest of it is waited for
+    stdin.set_timeout(None);
+    match arrived {
+        Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+            return Err(RoverError::new(anyhow!(
+                "No {} was received on stdin within {} seconds.",
+                file_description,
+                timeout.as_secs_f32()
+            ))
+            .with_suggestion(RoverErrorSuggestion::ProvideSchemaFile))
+        }
+        arrived => {
+            arrived.with_context(|| format!("Failed to read {} from stdin", file_description))?
+        }
+    }
+    let mut contents = Vec::new();
+    stdin
+        .read_to_end(&mut contents)
+        .with_context(|| format!("Failed to read {} from stdin", file_description))?;
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{self, Read},
+        str::FromStr,
+        thread,
+        time::Duration,
+    };
+
+    use httpmock::{Method::GET, MockServer};
+    use reqwest::blocking::Client;
+
+    use rover_std::prompt::TimeoutReader;
+
+    use super::{read_with_timeout, SchemaSource};
+    use crate::{utils::parsers::FileDescriptorType, RoverErrorSuggestion};
+
+    /// stdin for a CI job that never writes anything or closes it
+    struct NeverClosingStdin;
+
+    impl Read for NeverClosingStdin {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            loop {
+                thread::park();
+            }
+        }
+    }
+
+    #[test]
+    fn it_times_out_waiting_on_silent_stdin() {
+        let mut stdin = TimeoutReader::new(io::BufReader::new(NeverClosingStdin), Duration::ZERO);
+        let err = read_with_timeout(&mut stdin, Duration::from_millis(100), "SDL").unwrap_err();
+
+        assert!(err.to_string().contains("No SDL was received on stdin"));
+        assert!(matches!(
+            err.suggestions(),
+            [RoverErrorSuggestion::ProvideSchemaFile]
+        ));
+    }
+
+    #[test]
+    fn it_reads_piped_stdin_before_the_timeout() {
+        let mut stdin = TimeoutReader::new(
+            io::Cursor::new("type Query { hello: String }"),
+            Duration::ZERO,
+        );
+        let piped = read_with_timeout(&mut stdin, Duration::from_secs(10), "SDL").unwrap();
+
+        assert_eq!(piped, b"type Query { hello: String }");
+    }
+
+    #[test]
+    fn it_parses_http_urls() {
+        assert!(matches!(
+            SchemaSource::from_str("https://example.com/schema.graphql").unwrap(),
+            SchemaSource::Url(_)
+        ));
+        assert!(matches!(
+            SchemaSource::from_str("./schema.graphql").unwrap(),
+            SchemaSource::FileDescriptor(FileDescriptorType::File(_))
+        ));
+        assert!(matches!(
+            SchemaSource::from_str("-").unwrap(),
+            SchemaSource::FileDescriptor(FileDescriptorType::Stdin)
+        ));
+        assert!(matches!(
+            SchemaSource::from_str("C:\\schema.graphql").unwrap(),
+            SchemaSource::FileDescriptor(FileDescriptorType::File(_))
+        ));
+    }
+
+    #[test]
+    fn it_fetches_a_schema_from_a_url() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/schema.graphql");
+            then.status(200).body("type Query { hello: String }");
+        });
+        let source = SchemaSource::from_str(&server.url("/schema.graphql")).unwrap();
+
+        let schema = source
+            .read("SDL", &mut "".as_bytes(), || Ok(Client::new()))
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(schema, "type Query { hello: String }");
+    }
+
+    #[test]
+    fn it_only_gets_a_client_for_urls() {
+        let no_client = || Err(anyhow::anyhow!("no client should be needed").into());
+        let stdin = SchemaSource::from_str("-").unwrap();
+
+        let schema = stdin
+            .read(
+                "SDL",
+                &mut "type Query { hello: String }".as_bytes(),
+                no_client,
+            )
+            .unwrap();
+
+        assert_eq!(schema, "type Query { hello: String }");
+        let url = SchemaSource::from_str("https://example.com/schema.graphql").unwrap();
+        let err = url.read("SDL", &mut "".as_bytes(), no_client).unwrap_err();
+        assert!(err.to_string().contains("no client should be needed"));
+    }
+
+    #[test]
+    fn it_errors_on_a_non_success_status() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/schema.graphql");
+            then.status(404);
+        });
+        let source = SchemaSource::from_str(&server.url("/schema.graphql")).unwrap();
+
+        let err = source
+            .read("SDL", &mut "".as_bytes(), || Ok(Client::new()))
+            .unwrap_err();
+
+        assert!(err.to_string().contains("404"));
+    }
+
+    #[test]
+    fn it_errors_on_a_timeout() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/schema.graphql");
+            then.status(200)
+                .delay(Duration::from_secs(2))
+                .body("type Query { hello: String }");
+        });
+        let source = SchemaSource::from_str(&server.url("/schema.graphql")).unwrap();
+        let client = Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let err = source
+            .read("SDL", &mut "".as_bytes(), || Ok(client))
+            .unwrap_err();
+
+        assert!(err.to_string().contains("timed out"));
+    }
+}
@@ -1,9 +1,14 @@
+use std::fmt;
 use std::io::{self, IsTerminal};
+use std::str::FromStr;
 
 use camino::Utf8PathBuf;
 use clap::{self, Parser};
+use rover_client::shared::GraphRef;
 use serde::{Deserialize, Serialize};
 
+use crate::{RoverError, RoverErrorSuggestion, RoverResult};
+
 #[cfg(feature = "composition-js")]
 use anyhow::{Context, Result};
 
@@ -30,6 +35,83 @@ pub struct SubgraphOpt {
     pub subgraph_name: String,
 }
 
+/// the longest subgraph name Rover publishes
+const MAX_SUBGRAPH_NAME_LENGTH: usize = 64;
+
+impl SubgraphOpt {
+    /// checks the subgraph name against Rover's own naming rules, so a bad
+    /// name fails before any requests are made. the registry does its own
+    /// checks when the subgraph is published
+    pub fn validate(&self) -> RoverResult<()> {
+        validate_subgraph_name(&self.subgraph_name)
+    }
+}
+
+/// subgraph names must start with a letter and can only contain letters,
+/// numbers, underscores, and hyphens
+pub fn validate_subgraph_name(name: &str) -> RoverResult<()> {
+    let problem = if name.trim().is_empty() {
+        Some("The subgraph name cannot be empty.".to_string())
+    } else if let Some(illegal) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '-'))
+    {
+        Some(format!(
+            "`{}` is not a valid subgraph name because it contains {:?}.",
+            name, illegal
+        ))
+    } else if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        Some(format!(
+            "`{}` is not a valid subgraph name because it does not start with a letter.",
+            name
+        ))
+    } else if name.len() > MAX_SUBGRAPH_NAME_LENGTH {
+        Some(format!(
+            "`{}` is not a valid subgraph name because it is longer than {} characters.",
+            name, MAX_SUBGRAPH_NAME_LENGTH
+        ))
+    } else {
+        None
+    };
+    match problem {
+        Some(problem) => Err(RoverError::new(anyhow::anyhow!("{}", problem))
+            .with_suggestion(RoverErrorSuggestion::FixSubgraphName)),
+        None => Ok(()),
+    }
+}
+
+/// A subgraph in the graph registry, written as `graph@variant:subgraph`,
+/// whose published schema can be used in place of a schema file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubgraphSchemaRef {
+    pub graph_ref: GraphRef,
+    pub subgraph_name: String,
+}
+
+impl FromStr for SubgraphSchemaRef {
+    type Err = String;
+
+    fn from_str(schema_ref: &str) -> std::result::Result<Self, Self::Err> {
+        // variants can contain colons, but subgraph names can't
+        let (graph_ref, subgraph_name) = schema_ref.rsplit_once(':').ok_or_else(|| {
+            format!("`{schema_ref}` is not a schema ref. Schema refs look like `graph@variant:subgraph`.")
+        })?;
+        let graph_ref = GraphRef::from_str(graph_ref)
+            .map_err(|_| format!("`{graph_ref}` in `{schema_ref}` is not a valid graph ref."))?;
+        validate_subgraph_name(subgraph_name).map_err(|e| e.message())?;
+        Ok(Self {
+            graph_ref,
+            subgraph_name: subgraph_name.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for SubgraphSchemaRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.graph_ref, self.subgraph_name)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Parser)]
 pub struct OptionalSubgraphOpts {
     /// The name of the subgraph.
@@ -169,3 +251,90 @@ impl OptionalSubgraphOpts {
             .and_then(|x| x.file_name().map(|x| x.to_string_lossy().to_lowercase()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_schema_refs() {
+        let schema_ref: SubgraphSchemaRef = "my-graph@staging:accounts".parse().unwrap();
+        assert_eq!(
+            schema_ref,
+            SubgraphSchemaRef {
+                graph_ref: GraphRef {
+                    name: "my-graph".to_string(),
+                    variant: "staging".to_string(),
+                },
+                subgraph_name: "accounts".to_string(),
+            }
+        );
+        assert_eq!(schema_ref.to_string(), "my-graph@staging:accounts");
+
+        let schema_ref: SubgraphSchemaRef = "my-graph@feature:v2:accounts".parse().unwrap();
+        assert_eq!(schema_ref.graph_ref.variant, "feature:v2");
+        assert_eq!(schema_ref.subgraph_name, "accounts");
+    }
+
+    #[test]
+    fn it_rejects_malformed_schema_refs() {
+        for (schema_ref, problem) in [
+            (
+                "my-graph@staging",
+                "Schema refs look like `graph@variant:subgraph`.",
+            ),
+            ("1graph@staging:accounts", "is not a valid graph ref."),
+            ("my-graph@staging:", "The subgraph name cannot be empty."),
+            ("my-graph@staging:acc.ounts", "is not a valid subgraph name"),
+        ] {
+            let error = SubgraphSchemaRef::from_str(schema_ref).unwrap_err();
+            assert!(error.contains(problem), "{schema_ref}: {error}");
+        }
+    }
+
+    #[test]
+    fn it_accepts_valid_subgraph_names() {
+        for name in ["accounts", "Accounts", "product-catalog_v2"] {
+            assert!(validate_subgraph_name(name).is_ok(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn it_rejects_empty_subgraph_names() {
+        for name in ["", "   ", "\t"] {
+            let error = validate_subgraph_name(name).unwrap_err();
+            assert_eq!(error.message(), "The subgraph name cannot be empty.");
+            assert!(matches!(
+                error.suggestions(),
+                [RoverErrorSuggestion::FixSubgraphName]
+            ));
+        }
+    }
+
+    #[test]
+    fn it_rejects_subgraph_names_with_illegal_characters() {
+        for (name, illegal) in [
+            ("my accounts", "' '"),
+            ("accounts.v2", "'.'"),
+            ("accounts/", "'/'"),
+            ("comptes-é", "'é'"),
+        ] {
+            let error = validate_subgraph_name(name).unwrap_err();
+            assert!(error.message().contains(illegal), "{}", error.message());
+        }
+    }
+
+    #[test]
+    fn it_rejects_subgraph_names_that_do_not_start_with_a_letter() {
+        for name in ["2accounts", "-accounts", "_accounts"] {
+            let error = validate_subgraph_name(name).unwrap_err();
+            assert!(error.message().contains("does not start with a letter"));
+        }
+    }
+
+    #[test]
+    fn it_rejects_subgraph_names_that_are_too_long() {
+        assert!(validate_subgraph_name(&"a".repeat(MAX_SUBGRAPH_NAME_LENGTH)).is_ok());
+        assert!(validate_subgraph_name(&"a".repeat(MAX_SUBGRAPH_NAME_LENGTH + 1)).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::cell::Cell;
+use std::future::Future;
+
+use futures::stream::{self, StreamExt};
+
+/// Runs `work` on each item, with at most `max_concurrency` items in flight
+/// at once, returning the results in the same order as `items`.
+///
+/// Once a result satisfies `should_stop`, no more items are started, though
+/// the ones already in flight are allowed to finish. Items that were never
+/// started have no result.
+pub async fn run_bounded<'a, T, R, F, Fut, S>(
+    items: &'a [T],
+    max_concurrency: usize,
+    work: F,
+    should_stop: S,
+) -> Vec<Option<R>>
+where
+    F: Fn(&'a T) -> Fut,
+    Fut: Future<Output = R>,
+    S: Fn(&R) -> bool,
+{
+    let stopped = Cell::new(false);
+    let (stopped, work, should_stop) = (&stopped, &work, &should_stop);
+    stream::iter(items)
+        .map(|item| async move {
+            // checked once the item is started, rather than when it's queued
+            if stopped.get() {
+                return None;
+            }
+            let result = work(item).await;
+            if should_stop(&result) {
+                stopped.set(true);
+            }
+            Some(result)
+        })
+        .buffered(max_concurrency.max(1))
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn it_keeps_results_in_order() {
+        let items: Vec<u64> = (0..10).collect();
+        let results = block_on(run_bounded(
+            &items,
+            4,
+            |item| async move {
+                // finish out of order
+                tokio::time::sleep(Duration::from_millis(10 * (10 - item))).await;
+                item * 2
+            },
+            |_| false,
+        ));
+        let expected: Vec<_> = (0..10).map(|item| Some(item * 2)).collect();
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn it_never_runs_more_than_the_max_at_once() {
+        let in_flight = Cell::new(0);
+        let most_in_flight = Cell::new(0);
+        let items: Vec<usize> = (0..12).collect();
+        block_on(run_bounded(
+            &items,
+            3,
+            |_| async {
+                in_flight.set(in_flight.get() + 1);
+                most_in_flight.set(most_in_flight.get().max(in_flight.get()));
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                in_flight.set(in_flight.get() - 1);
+            },
+            |_| false,
+        ));
+        assert_eq!(most_in_flight.get(), 3);
+    }
+
+    #[test]
+    fn it_stops_starting_items_after_a_failure() {
+        let items: Vec<usize> = (0..5).collect();
+        let results = block_on(run_bounded(
+            &items,
+            1,
+            |item| async move {
+                if *item == 1 {
+                    Err(*item)
+                } else {
+                    Ok(*item)
+                }
+            },
+            Result::is_err,
+        ));
+        assert_eq!(results, vec![Some(Ok(0)), Some(Err(1)), None, None, None]);
+    }
+}
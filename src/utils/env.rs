@@ -1,10 +1,14 @@
 use std::collections::HashMap;
-use std::{env, fmt, io};
+use std::{env, fmt, fs, io};
 
+use camino::Utf8Path;
 use heck::AsShoutySnekCase;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
+/// the file `--env-file` reads when it isn't given a path
+const DEFAULT_ENV_FILE: &str = ".env";
+
 /// RoverEnv allows us to mock environment variables while
 /// running tests. That way we can run our tests in parallel,
 /// and our local development environment will not have unintended
@@ -82,6 +86,78 @@ impl RoverEnv {
     pub fn remove(&mut self, key: RoverEnvKey) {
         self.env_store.remove(&key.to_string());
     }
+
+    /// sets the variables in the `.env` file at `path` that aren't already set,
+    /// so the real environment always takes precedence over the file.
+    /// a missing file is an error, since it was asked for by name.
+    pub fn load_env_file(&mut self, path: &Utf8Path) -> io::Result<()> {
+        self.read_env_file(path, false)
+    }
+
+    /// like [`RoverEnv::load_env_file`] for the `.env` file in the current
+    /// directory, which is skipped when it's missing, since not every directory has one
+    pub fn load_default_env_file(&mut self) -> io::Result<()> {
+        self.read_env_file(Utf8Path::new(DEFAULT_ENV_FILE), true)
+    }
+
+    fn read_env_file(&mut self, path: &Utf8Path, skip_missing: bool) -> io::Result<()> {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                tracing::debug!("reading environment variables from {}", path);
+                self.load_env_file_contents(&contents);
+                Ok(())
+            }
+            Err(e) if skip_missing && e.kind() == io::ErrorKind::NotFound => {
+                tracing::debug!("{} does not exist, so it was not read", path);
+                Ok(())
+            }
+            Err(e) => Err(io::Error::new(
+                e.kind(),
+                format!("Could not read the environment file \"{}\": {}", path, e),
+            )),
+        }
+    }
+
+    fn load_env_file_contents(&mut self, contents: &str) {
+        let vars = parse_env_file(contents);
+        for key in RoverEnvKey::iter() {
+            let key_str = key.to_string();
+            if self.env_store.contains_key(&key_str) {
+                continue;
+            }
+            if let Some(value) = vars.get(&key_str) {
+                tracing::debug!("{}", Self::get_debug_value(key, value));
+                self.env_store.insert(key_str, value.to_string());
+            }
+        }
+    }
+}
+
+/// parses the `NAME=value` lines of a `.env` file. blank lines, `#` comments
+/// and a leading `export` are skipped, and values may be wrapped in quotes.
+fn parse_env_file(contents: &str) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((name, value)) = line.split_once('=') else {
+            tracing::warn!("ignoring a line of the environment file without an `=`");
+            continue;
+        };
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) if value.len() > 1 && value.ends_with(quote) => {
+                &value[1..value.len() - 1]
+            }
+            // unquoted values can be followed by a comment
+            _ => value.split(" #").next().unwrap_or_default().trim_end(),
+        };
+        vars.insert(name.trim().to_string(), value.to_string());
+    }
+    vars
 }
 
 /// RoverEnvKey defines all of the environment variables
@@ -106,6 +182,7 @@ pub enum RoverEnvKey {
     VcsAuthor,
     NodeModulesBin,
     ChecksTimeoutSeconds,
+    RoutingUrlFetchAttempts,
 }
 
 impl fmt::Display for RoverEnvKey {
@@ -117,8 +194,19 @@ impl fmt::Display for RoverEnvKey {
 
 #[cfg(test)]
 mod tests {
+    use assert_fs::prelude::*;
+    use camino::Utf8PathBuf;
+
     use super::*;
 
+    fn env_file(contents: &str) -> (assert_fs::TempDir, Utf8PathBuf) {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let file = dir.child(".env");
+        file.write_str(contents).unwrap();
+        let path = Utf8PathBuf::try_from(file.path().to_path_buf()).unwrap();
+        (dir, path)
+    }
+
     #[test]
     fn it_doesnt_read_from_real_env_in_tests() {
         assert!(RoverEnv::new().unwrap().env_store.is_empty())
@@ -153,4 +241,61 @@ mod tests {
         let actual_value = env_store.get(key);
         assert_eq!(expected_value, actual_value);
     }
+
+    #[test]
+    fn it_loads_variables_from_an_env_file() {
+        let (_dir, path) = env_file(
+            "# rover settings\nAPOLLO_KEY=service:my-graph:abc123\nexport APOLLO_REGISTRY_URL=\"http://localhost:4000/graphql\"\n\nAPOLLO_VCS_BRANCH='main' \nAPOLLO_VCS_AUTHOR=Ada # the author\nNOT_AN_APOLLO_VAR=1\n",
+        );
+        let mut env_store = RoverEnv::new().unwrap();
+        env_store.load_env_file(&path).unwrap();
+
+        assert_eq!(
+            env_store.get(RoverEnvKey::Key).as_deref(),
+            Some("service:my-graph:abc123")
+        );
+        assert_eq!(
+            env_store.get(RoverEnvKey::RegistryUrl).as_deref(),
+            Some("http://localhost:4000/graphql")
+        );
+        assert_eq!(
+            env_store.get(RoverEnvKey::VcsBranch).as_deref(),
+            Some("main")
+        );
+        assert_eq!(
+            env_store.get(RoverEnvKey::VcsAuthor).as_deref(),
+            Some("Ada")
+        );
+        assert!(!env_store.env_store.contains_key("NOT_AN_APOLLO_VAR"));
+    }
+
+    #[test]
+    fn it_prefers_the_environment_over_an_env_file() {
+        let (_dir, path) = env_file("APOLLO_KEY=from-the-file\nAPOLLO_VCS_BRANCH=from-the-file\n");
+        let mut env_store = RoverEnv::new().unwrap();
+        env_store.insert(RoverEnvKey::Key, "from-the-environment");
+        env_store.load_env_file(&path).unwrap();
+
+        assert_eq!(
+            env_store.get(RoverEnvKey::Key).as_deref(),
+            Some("from-the-environment")
+        );
+        assert_eq!(
+            env_store.get(RoverEnvKey::VcsBranch).as_deref(),
+            Some("from-the-file")
+        );
+    }
+
+    #[test]
+    fn it_only_skips_a_missing_env_file_that_was_not_named() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = Utf8PathBuf::try_from(dir.path().join(".env")).unwrap();
+        let mut env_store = RoverEnv::new().unwrap();
+        env_store.read_env_file(&path, true).unwrap();
+        assert!(env_store.env_store.is_empty());
+
+        let error = env_store.load_env_file(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(error.to_string().contains(path.as_str()), "{}", error);
+    }
 }
@@ -1,7 +1,11 @@
 pub mod client;
+pub mod concurrency;
 pub mod env;
 pub mod parsers;
 pub mod pkg;
+pub mod proxy;
+pub mod retry;
+pub mod sdl;
 pub mod stringify;
 pub mod table;
 pub mod telemetry;
@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Context};
 use camino::{Utf8Path, Utf8PathBuf};
+use reqwest::header::{HeaderName, HeaderValue};
 use rover_std::Fs;
 
 use crate::{RoverError, RoverErrorSuggestion, RoverResult};
@@ -8,8 +9,12 @@ use std::{
     fmt,
     io::{self, Read},
     str::FromStr,
+    time::Duration,
 };
 
+/// how long to wait for another process to finish writing to a named pipe
+const PIPE_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum FileDescriptorType {
     Stdin,
@@ -32,7 +37,23 @@ impl FileDescriptorType {
             }
             Self::File(file_path) => {
                 if Utf8Path::exists(file_path) {
-                    let contents = Fs::read_file(file_path).with_context(|| {
+                    // named pipes block until something writes to them, so
+                    // they are read with a timeout instead. whether what's
+                    // written to them is compressed is decided when it arrives
+                    let is_pipe = Fs::metadata(file_path)
+                        .map(|metadata| !metadata.is_file() && !metadata.is_dir())
+                        .unwrap_or(false);
+                    let is_gzip = file_path
+                        .extension()
+                        .map_or(false, |extension| extension.eq_ignore_ascii_case("gz"));
+                    let contents = if is_pipe {
+                        Fs::read_pipe(file_path, PIPE_READ_TIMEOUT)
+                    } else if is_gzip {
+                        Fs::read_gzip_file(file_path)
+                    } else {
+                        Fs::read_file(file_path)
+                    }
+                    .with_context(|| {
                         format!("Could not read {} from {}", file_description, file_path)
                     })?;
                     Ok(contents)
@@ -109,12 +130,38 @@ pub fn parse_header(header: &str) -> std::result::Result<(String, String), io::E
     }
 }
 
+/// Parses a "Name: Value" header to send to Apollo Studio, erroring if it
+/// isn't a valid HTTP header. The value is marked as sensitive, so it's never
+/// logged.
+pub fn parse_studio_header(
+    header: &str,
+) -> std::result::Result<(HeaderName, HeaderValue), io::Error> {
+    let invalid = |reason: &str| {
+        let msg = format!(
+            "Could not parse \"{}\" as a header: {}. Headers must be provided as \"Name: Value\".",
+            header, reason
+        );
+        io::Error::new(io::ErrorKind::InvalidInput, msg)
+    };
+    let (name, value) = header
+        .split_once(':')
+        .ok_or_else(|| invalid("there is no \":\" between the name and the value"))?;
+    let name = HeaderName::from_bytes(name.trim().as_bytes())
+        .map_err(|_| invalid("the name is empty or has characters a header name can't"))?;
+    let mut value = HeaderValue::from_str(value.trim())
+        .map_err(|_| invalid("the value has characters a header value can't"))?;
+    value.set_sensitive(true);
+    Ok((name, value))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::FileDescriptorType;
+    use super::{parse_studio_header, FileDescriptorType};
     use assert_fs::prelude::*;
     use camino::Utf8PathBuf;
+    use flate2::{write::GzEncoder, Compression};
     use std::convert::TryFrom;
+    use std::io::Write;
     use std::str::FromStr;
 
     #[test]
@@ -162,6 +209,61 @@ mod tests {
         assert_eq!(schema, "type Query { hello: String! }".to_string());
     }
 
+    #[test]
+    fn load_schema_from_flag_decompresses_gzip_files() {
+        let fixture = assert_fs::TempDir::new().unwrap();
+
+        let test_file = fixture.child("schema.graphql.gz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"type Query { hello: String! }").unwrap();
+        test_file.write_binary(&encoder.finish().unwrap()).unwrap();
+
+        let test_path = Utf8PathBuf::try_from(test_file.path().to_path_buf()).unwrap();
+        let fd = FileDescriptorType::File(test_path);
+
+        let schema = fd
+            .read_file_descriptor("SDL", &mut "".to_string().as_bytes())
+            .unwrap();
+        assert_eq!(schema, "type Query { hello: String! }".to_string());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn load_schema_from_flag_reads_a_fifo_named_like_a_gzip_file() {
+        let fixture = assert_fs::TempDir::new().unwrap();
+        let fifo_path = Utf8PathBuf::try_from(fixture.path().join("schema.graphql.gz")).unwrap();
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo_path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let writer_path = fifo_path.clone();
+        std::thread::spawn(move || std::fs::write(writer_path, "type Query { hello: String! }"));
+
+        let schema = FileDescriptorType::File(fifo_path)
+            .read_file_descriptor("SDL", &mut "".to_string().as_bytes())
+            .unwrap();
+        assert_eq!(schema, "type Query { hello: String! }".to_string());
+    }
+
+    #[test]
+    fn load_schema_from_flag_errs_on_corrupted_gzip_files() {
+        let fixture = assert_fs::TempDir::new().unwrap();
+
+        let test_file = fixture.child("schema.graphql.gz");
+        test_file
+            .write_str("type Query { hello: String! }")
+            .unwrap();
+
+        let test_path = Utf8PathBuf::try_from(test_file.path().to_path_buf()).unwrap();
+        let fd = FileDescriptorType::File(test_path);
+
+        let err = fd
+            .read_file_descriptor("SDL", &mut "".to_string().as_bytes())
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("could not be decompressed as gzip"));
+    }
+
     #[test]
     fn load_schema_from_flag_errs_on_bad_path() {
         let empty_path = "./wow.graphql";
@@ -192,4 +294,34 @@ mod tests {
         let schema_result = fd.read_file_descriptor("SDL", &mut input.as_bytes());
         assert!(schema_result.is_err())
     }
+
+    #[test]
+    fn it_parses_studio_headers() {
+        let headers: Vec<_> = ["X-Gateway-Token: abc:123", "x-team:accounts"]
+            .into_iter()
+            .map(|header| parse_studio_header(header).unwrap())
+            .collect();
+
+        assert_eq!(headers[0].0, "x-gateway-token");
+        assert_eq!(headers[0].1, "abc:123");
+        assert_eq!(headers[1].0, "x-team");
+        assert_eq!(headers[1].1, "accounts");
+        assert!(headers.iter().all(|(_, value)| value.is_sensitive()));
+    }
+
+    #[test]
+    fn it_rejects_malformed_studio_headers() {
+        for header in [
+            "noколон",
+            ": value",
+            "bad name: value",
+            "x-name: bad\nvalue",
+        ] {
+            assert!(
+                parse_studio_header(header).is_err(),
+                "{:?} was accepted",
+                header
+            );
+        }
+    }
 }
@@ -0,0 +1,232 @@
+use std::env;
+
+use reqwest::Url;
+
+/// The proxies configured with the standard `HTTPS_PROXY`, `HTTP_PROXY`,
+/// `ALL_PROXY` and `NO_PROXY` environment variables, plus an optional proxy
+/// that overrides them for every scheme.
+///
+/// The lowercase variants take precedence over the uppercase ones, like they
+/// do for curl.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProxyConfig {
+    https_proxy: Option<Url>,
+    http_proxy: Option<Url>,
+    override_proxy: Option<Url>,
+    no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// reads the proxy configuration from the environment
+    pub(crate) fn from_env(override_proxy: Option<Url>) -> Self {
+        Self::from_lookup(|name| env::var(name).ok(), override_proxy)
+    }
+
+    /// reads the proxy configuration with `lookup`, which returns the value of
+    /// an environment variable
+    pub(crate) fn from_lookup<F>(lookup: F, override_proxy: Option<Url>) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |name: &str| {
+            lookup(&name.to_lowercase())
+                .or_else(|| lookup(name))
+                .filter(|value| !value.trim().is_empty())
+        };
+        let proxy_url = |name: &str| {
+            var(name).and_then(|value| match Url::parse(value.trim()) {
+                Ok(url) => Some(url),
+                Err(e) => {
+                    tracing::warn!("ignoring {} because it isn't a valid url: {}", name, e);
+                    None
+                }
+            })
+        };
+        let all_proxy = proxy_url("ALL_PROXY");
+        Self {
+            https_proxy: proxy_url("HTTPS_PROXY").or_else(|| all_proxy.clone()),
+            http_proxy: proxy_url("HTTP_PROXY").or(all_proxy),
+            override_proxy,
+            no_proxy: var("NO_PROXY")
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(|entry| entry.trim().trim_start_matches('.').to_lowercase())
+                        .filter(|entry| !entry.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// the proxy that requests to `url` should go through, if any
+    pub(crate) fn proxy_for(&self, url: &Url) -> Option<Url> {
+        if self.is_excluded(url) {
+            return None;
+        }
+        if let Some(override_proxy) = &self.override_proxy {
+            return Some(override_proxy.clone());
+        }
+        match url.scheme() {
+            "https" => self.https_proxy.clone(),
+            "http" => self.http_proxy.clone(),
+            _ => None,
+        }
+    }
+
+    /// whether `NO_PROXY` says requests to `url` should skip the proxy.
+    /// entries match a host and all of its subdomains, and `*` matches every host.
+    fn is_excluded(&self, url: &Url) -> bool {
+        let host = match url.host_str() {
+            Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+            None => return false,
+        }
+        .to_lowercase();
+        self.no_proxy.iter().any(|entry| {
+            entry == "*"
+                || host == *entry
+                || (host.ends_with(entry.as_str())
+                    && host[..host.len() - entry.len()].ends_with('.'))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn config(vars: &[(&str, &str)], override_proxy: Option<&str>) -> ProxyConfig {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        ProxyConfig::from_lookup(
+            |name| vars.get(name).cloned(),
+            override_proxy.map(|url| Url::parse(url).unwrap()),
+        )
+    }
+
+    fn proxy_for(config: &ProxyConfig, url: &str) -> Option<String> {
+        config
+            .proxy_for(&Url::parse(url).unwrap())
+            .map(|proxy| proxy.to_string())
+    }
+
+    #[test]
+    fn it_uses_the_proxy_for_the_scheme() {
+        let config = config(
+            &[
+                ("HTTPS_PROXY", "http://secure-proxy.corp:3128"),
+                ("http_proxy", "http://proxy.corp:3128"),
+            ],
+            None,
+        );
+
+        assert_eq!(
+            proxy_for(&config, "https://api.apollographql.com/graphql").as_deref(),
+            Some("http://secure-proxy.corp:3128/")
+        );
+        assert_eq!(
+            proxy_for(&config, "http://localhost:4001").as_deref(),
+            Some("http://proxy.corp:3128/")
+        );
+    }
+
+    #[test]
+    fn it_prefers_lowercase_variables() {
+        let config = config(
+            &[
+                ("HTTPS_PROXY", "http://upper.corp:3128"),
+                ("https_proxy", "http://lower.corp:3128"),
+            ],
+            None,
+        );
+
+        assert_eq!(
+            proxy_for(&config, "https://api.apollographql.com/graphql").as_deref(),
+            Some("http://lower.corp:3128/")
+        );
+    }
+
+    #[test]
+    fn it_falls_back_to_all_proxy() {
+        let config = config(&[("ALL_PROXY", "socks5://proxy.corp:1080")], None);
+
+        assert_eq!(
+            proxy_for(&config, "https://api.apollographql.com/graphql").as_deref(),
+            Some("socks5://proxy.corp:1080")
+        );
+    }
+
+    #[test]
+    fn it_skips_the_proxy_for_no_proxy_hosts() {
+        let config = config(
+            &[
+                ("HTTPS_PROXY", "http://proxy.corp:3128"),
+                ("NO_PROXY", "localhost, .internal.corp,127.0.0.1"),
+            ],
+            None,
+        );
+
+        for url in [
+            "https://localhost:4001",
+            "https://accounts.internal.corp/graphql",
+            "https://internal.corp",
+            "https://127.0.0.1:4000",
+        ] {
+            assert_eq!(proxy_for(&config, url), None, "{} was proxied", url);
+        }
+        for url in [
+            "https://api.apollographql.com/graphql",
+            "https://notinternal.corp",
+        ] {
+            assert!(proxy_for(&config, url).is_some(), "{} was not proxied", url);
+        }
+    }
+
+    #[test]
+    fn it_skips_the_proxy_for_every_host_with_a_wildcard() {
+        let config = config(
+            &[("HTTPS_PROXY", "http://proxy.corp:3128"), ("NO_PROXY", "*")],
+            Some("http://override.corp:8080"),
+        );
+
+        assert_eq!(
+            proxy_for(&config, "https://api.apollographql.com/graphql"),
+            None
+        );
+    }
+
+    #[test]
+    fn it_lets_an_explicit_proxy_override_the_environment() {
+        let config = config(
+            &[
+                ("HTTPS_PROXY", "http://proxy.corp:3128"),
+                ("NO_PROXY", "localhost"),
+            ],
+            Some("http://override.corp:8080"),
+        );
+
+        assert_eq!(
+            proxy_for(&config, "https://api.apollographql.com/graphql").as_deref(),
+            Some("http://override.corp:8080/")
+        );
+        assert_eq!(
+            proxy_for(&config, "http://api.apollographql.com/graphql").as_deref(),
+            Some("http://override.corp:8080/")
+        );
+        assert_eq!(proxy_for(&config, "http://localhost:4001"), None);
+    }
+
+    #[test]
+    fn it_does_not_proxy_without_configuration() {
+        let config = config(&[], None);
+
+        assert_eq!(
+            proxy_for(&config, "https://api.apollographql.com/graphql"),
+            None
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt::Display,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    thread,
+    time::Duration,
+};
+
+use rover_client::RoverClientError;
+
+/// the number of attempts made when no other value is configured
+pub const DEFAULT_ATTEMPTS: usize = 3;
+
+const DEFAULT_INITIAL_DELAY: Duration = Duration::from_millis(500);
+
+/// Retries an operation a bounded number of times,
+/// doubling the delay between each attempt.
+///
+/// Each wait is picked at random between zero and that delay, so that many
+/// CI jobs that failed at the same moment don't all retry at the same moment too.
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    max_attempts: usize,
+    initial_delay: Duration,
+    jitter_seed: Option<u64>,
+}
+
+impl Retry {
+    /// makes at most `max_attempts` attempts, always making at least one
+    pub fn new(max_attempts: usize) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_delay: DEFAULT_INITIAL_DELAY,
+            jitter_seed: None,
+        }
+    }
+
+    /// sets how long to wait before the first retry
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// picks the same random waits every time, which keeps tests deterministic
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_seed = Some(seed);
+        self
+    }
+
+    fn backoff(&self) -> Backoff {
+        Backoff {
+            window: self.initial_delay,
+            rng: SplitMix64(
+                self.jitter_seed
+                    .unwrap_or_else(|| RandomState::new().build_hasher().finish()),
+            ),
+        }
+    }
+
+    /// runs `operation` until it succeeds, it fails with an error that
+    /// `is_retryable` rejects, or every attempt has been used up
+    pub fn run<T, E, F, R>(&self, operation: F, is_retryable: R) -> Result<T, E>
+    where
+        E: Display,
+        F: FnMut() -> Result<T, E>,
+        R: Fn(&E) -> bool,
+    {
+        self.run_with_retry_after(operation, is_retryable, |_| None)
+    }
+
+    /// like [`Retry::run`], but waits for as long as `requested_delay` asks
+    /// instead of the usual delay whenever it returns `Some`, which is how
+    /// servers that send `Retry-After` are respected
+    pub fn run_with_retry_after<T, E, F, R, D>(
+        &self,
+        mut operation: F,
+        is_retryable: R,
+        requested_delay: D,
+    ) -> Result<T, E>
+    where
+        E: Display,
+        F: FnMut() -> Result<T, E>,
+        R: Fn(&E) -> bool,
+        D: Fn(&E) -> Option<Duration>,
+    {
+        let mut backoff = self.backoff();
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Err(e) if attempt < self.max_attempts && is_retryable(&e) => {
+                    thread::sleep(self.wait(&mut backoff, attempt, &e, &requested_delay));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// like [`Retry::run_with_retry_after`], for an operation that doesn't block
+    pub async fn run_with_retry_after_async<T, E, F, Fut, R, D>(
+        &self,
+        mut operation: F,
+        is_retryable: R,
+        requested_delay: D,
+    ) -> Result<T, E>
+    where
+        E: Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        R: Fn(&E) -> bool,
+        D: Fn(&E) -> Option<Duration>,
+    {
+        let mut backoff = self.backoff();
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if attempt < self.max_attempts && is_retryable(&e) => {
+                    tokio::time::sleep(self.wait(&mut backoff, attempt, &e, &requested_delay))
+                        .await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// how long to wait after `attempt` failed with `e`
+    fn wait<E: Display>(
+        &self,
+        backoff: &mut Backoff,
+        attempt: usize,
+        e: &E,
+        requested_delay: impl Fn(&E) -> Option<Duration>,
+    ) -> Duration {
+        let wait = requested_delay(e).unwrap_or_else(|| backoff.next_delay());
+        tracing::info!(
+            "attempt {} of {} failed, retrying in {:?}: {}",
+            attempt,
+            self.max_attempts,
+            wait,
+            e
+        );
+        wait
+    }
+}
+
+/// the waits between attempts, each one picked at random from a window
+/// that doubles after every retry
+#[derive(Debug)]
+struct Backoff {
+    window: Duration,
+    rng: SplitMix64,
+}
+
+impl Backoff {
+    fn next_delay(&mut self) -> Duration {
+        let window = u64::try_from(self.window.as_nanos()).unwrap_or(u64::MAX);
+        self.window = self.window.saturating_mul(2);
+        match window.checked_add(1) {
+            Some(bound) => Duration::from_nanos(self.rng.next_u64() % bound),
+            None => Duration::from_nanos(self.rng.next_u64()),
+        }
+    }
+}
+
+/// a tiny, seedable random number generator, which is plenty for spreading out retries
+#[derive(Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// whether a request failed for a reason that might go away on its own,
+/// like a dropped connection, a server error or being rate limited. client
+/// errors such as an invalid API key are never transient.
+pub fn is_transient(error: &RoverClientError) -> bool {
+    match error {
+        RoverClientError::RateLimited { .. } => true,
+        RoverClientError::SendRequest { source, .. } => {
+            source.is_connect()
+                || source.is_timeout()
+                || source
+                    .status()
+                    .map_or(false, |status| status.is_server_error())
+        }
+        _ => false,
+    }
+}
+
+/// how long the server asked to wait before retrying, if it's rate limiting
+pub fn retry_after(error: &RoverClientError) -> Option<Duration> {
+    match error {
+        RoverClientError::RateLimited { retry_after, .. } => *retry_after,
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use httpmock::{Method::GET, MockServer};
+    use rover_client::EndpointKind;
+
+    use super::*;
+
+    /// fetches `url`, failing like a Studio request would on any error status
+    fn fetch(url: &str) -> Result<String, RoverClientError> {
+        reqwest::blocking::get(url)
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .map_err(|source| RoverClientError::SendRequest {
+                source,
+                endpoint_kind: EndpointKind::ApolloStudio,
+            })
+    }
+
+    #[test]
+    fn it_uses_the_result_after_transient_failures() {
+        let server = MockServer::start();
+        let mut failing = server.mock(|when, then| {
+            when.method(GET).path("/routing-url");
+            then.status(503);
+        });
+
+        let result = Retry::new(3).with_initial_delay(Duration::ZERO).run(
+            || {
+                // the server recovers after failing twice
+                if failing.hits() == 2 {
+                    failing.delete();
+                    server.mock(|when, then| {
+                        when.method(GET).path("/routing-url");
+                        then.status(200)
+                            .body("https://accounts.example.com/graphql");
+                    });
+                }
+                fetch(&server.url("/routing-url"))
+            },
+            is_transient,
+        );
+
+        assert_eq!(result.unwrap(), "https://accounts.example.com/graphql");
+    }
+
+    #[test]
+    fn it_gives_up_after_the_last_attempt() {
+        let server = MockServer::start();
+        let failing = server.mock(|when, then| {
+            when.method(GET).path("/routing-url");
+            then.status(500);
+        });
+
+        let result = Retry::new(2)
+            .with_initial_delay(Duration::ZERO)
+            .run(|| fetch(&server.url("/routing-url")), is_transient);
+
+        assert!(result.is_err());
+        failing.assert_hits(2);
+    }
+
+    #[test]
+    fn it_does_not_retry_client_errors() {
+        let server = MockServer::start();
+        let unauthorized = server.mock(|when, then| {
+            when.method(GET).path("/routing-url");
+            then.status(401);
+        });
+
+        let result = Retry::new(3)
+            .with_initial_delay(Duration::ZERO)
+            .run(|| fetch(&server.url("/routing-url")), is_transient);
+
+        assert!(result.is_err());
+        unauthorized.assert_hits(1);
+    }
+
+    #[test]
+    fn it_does_not_retry_auth_errors() {
+        assert!(!is_transient(&RoverClientError::InvalidKey));
+    }
+
+    #[test]
+    fn it_waits_as_long_as_the_server_asks() {
+        let attempts = Cell::new(0);
+        let started = std::time::Instant::now();
+        let result = Retry::new(2)
+            .with_initial_delay(Duration::ZERO)
+            .run_with_retry_after(
+                || {
+                    attempts.set(attempts.get() + 1);
+                    if attempts.get() < 2 {
+                        Err(RoverClientError::RateLimited {
+                            retry_after: Some(Duration::from_millis(200)),
+                            endpoint_kind: EndpointKind::ApolloStudio,
+                        })
+                    } else {
+                        Ok(())
+                    }
+                },
+                is_transient,
+                retry_after,
+            );
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.get(), 2);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn it_jitters_each_delay_within_its_doubling_window() {
+        let retry = Retry::new(6)
+            .with_initial_delay(Duration::from_millis(100))
+            .with_jitter_seed(42);
+        let delays = |retry: Retry| {
+            let mut backoff = retry.backoff();
+            (0..5).map(|_| backoff.next_delay()).collect::<Vec<_>>()
+        };
+        let jittered = delays(retry);
+
+        let windows: Vec<Duration> = (0..5).map(|i| Duration::from_millis(100 << i)).collect();
+        for (delay, window) in jittered.iter().zip(&windows) {
+            assert!(delay <= window, "{:?} is longer than {:?}", delay, window);
+        }
+        assert_ne!(jittered, windows);
+        assert_eq!(jittered, delays(retry));
+        assert_ne!(jittered, delays(retry.with_jitter_seed(7)));
+    }
+
+    #[test]
+    fn it_never_waits_without_an_initial_delay() {
+        let mut backoff = Retry::new(3).with_initial_delay(Duration::ZERO).backoff();
+        assert_eq!(backoff.next_delay(), Duration::ZERO);
+        assert_eq!(backoff.next_delay(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,624 @@
+use std::fmt;
+
+use apollo_parser::{ast, Lexer, Parser, TokenKind};
+
+/// Normalizes SDL so that it can be compared ignoring the things that can't
+/// change what it means: comments, trailing whitespace, blank lines, and line
+/// endings. Whitespace inside strings, like descriptions, is left alone.
+///
+/// Returns `None` if the SDL couldn't be lexed, since it isn't safe to tell
+/// what's a comment then.
+pub fn normalize(sdl: &str) -> Option<String> {
+    let mut normalized = String::with_capacity(sdl.len());
+    // whitespace is held back until the next token, so that whitespace on
+    // either side of a dropped comment is handled as one run
+    let mut whitespace = String::new();
+    for token in Lexer::new(sdl) {
+        let token = token.ok()?;
+        match token.kind() {
+            TokenKind::Comment => {}
+            TokenKind::Whitespace => whitespace.push_str(token.data()),
+            TokenKind::Eof => break,
+            _ => {
+                match whitespace.rfind('\n') {
+                    // any number of lines ending in whitespace become a
+                    // single line break, keeping the next line's indentation
+                    Some(last_newline) => {
+                        normalized.push('\n');
+                        normalized.push_str(&whitespace[last_newline + 1..]);
+                    }
+                    None => normalized.push_str(&whitespace),
+                }
+                whitespace.clear();
+                normalized.push_str(token.data());
+            }
+        }
+    }
+    Some(normalized.trim_start().to_string())
+}
+
+/// whether two SDLs are the same once [`normalize`]d, comparing them as-is
+/// if either can't be normalized
+pub fn is_equivalent(left: &str, right: &str) -> bool {
+    match (normalize(left), normalize(right)) {
+        (Some(left), Some(right)) => left == right,
+        _ => left == right,
+    }
+}
+
+/// Reformats SDL into a canonical layout so that diffs between versions of
+/// a schema only show real changes: every definition is separated by a blank
+/// line, fields, arguments and enum values are laid out consistently with
+/// two-space indentation, and commas between items are made consistent.
+///
+/// Only the layout changes. Definitions stay in the order they were written,
+/// and descriptions, directives, default values and comments are kept exactly
+/// as they are, so formatting the result again doesn't change it.
+///
+/// Returns the SDL's syntax errors if it can't be parsed.
+pub fn format(sdl: &str) -> Result<String, Vec<SyntaxError>> {
+    let errors = syntax_errors(sdl);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let mut formatter = Formatter::default();
+    for token in Lexer::new(sdl) {
+        // anything the lexer rejects is a syntax error, which was checked above
+        let Ok(token) = token else {
+            return Err(syntax_errors(sdl));
+        };
+        formatter.push(token.kind(), token.data());
+    }
+    Ok(formatter.finish())
+}
+
+/// the keywords that start a definition in SDL
+const DEFINITION_KEYWORDS: [&str; 9] = [
+    "schema",
+    "scalar",
+    "type",
+    "interface",
+    "union",
+    "enum",
+    "input",
+    "directive",
+    "extend",
+];
+
+/// what [`format`] is inside of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// the fields, enum values, or operation types of a definition, one per line
+    Block,
+    /// arguments, kept on one line
+    Arguments,
+    /// a list value, kept on one line
+    List,
+    /// an input object value, kept on one line
+    Object,
+}
+
+/// lays tokens out one at a time for [`format`]
+#[derive(Debug, Default)]
+struct Formatter {
+    out: String,
+    scopes: Vec<Scope>,
+    /// the last token written, leaving out comments
+    previous: Option<(TokenKind, String)>,
+    /// whether the next token goes on a new line
+    newline: bool,
+    /// whether a description has already started the next definition or
+    /// item, so the token after it doesn't start another
+    started: bool,
+    /// comments between definitions, which are held back until it's clear
+    /// whether they're above the next definition
+    comments: Vec<String>,
+}
+
+impl Formatter {
+    fn push(&mut self, kind: TokenKind, data: &str) {
+        match kind {
+            TokenKind::Whitespace | TokenKind::Comma | TokenKind::Eof => {}
+            TokenKind::Comment => self.push_comment(data),
+            TokenKind::LCurly if self.opens_block() => {
+                self.flush_comments();
+                self.write_token(kind, data, true);
+                self.scopes.push(Scope::Block);
+                self.newline = true;
+            }
+            TokenKind::RCurly if self.scopes.last() == Some(&Scope::Block) => {
+                self.flush_comments();
+                self.scopes.pop();
+                let empty = matches!(self.previous, Some((TokenKind::LCurly, _)));
+                self.newline = !empty;
+                self.write_token(kind, data, false);
+                self.newline = self.scopes.is_empty();
+            }
+            _ => {
+                let starts_item = self.starts_item(kind, data);
+                let description = kind == TokenKind::StringValue
+                    && starts_item
+                    && matches!(
+                        self.scopes.last(),
+                        None | Some(Scope::Block | Scope::Arguments)
+                    );
+                match self.scopes.last() {
+                    None if starts_item && !self.started => self.start_definition(),
+                    Some(Scope::Block) if starts_item && !self.started => self.newline = true,
+                    Some(Scope::Arguments | Scope::List | Scope::Object)
+                        if starts_item && !self.started =>
+                    {
+                        self.out.push(',')
+                    }
+                    _ => {}
+                }
+                self.flush_comments();
+                self.write_token(kind, data, false);
+                match kind {
+                    TokenKind::LParen => self.scopes.push(Scope::Arguments),
+                    TokenKind::LBracket => self.scopes.push(Scope::List),
+                    TokenKind::LCurly => self.scopes.push(Scope::Object),
+                    TokenKind::RParen | TokenKind::RBracket | TokenKind::RCurly => {
+                        self.scopes.pop();
+                    }
+                    _ => {}
+                }
+                // a description goes on its own line above what it describes
+                self.started = description;
+                if description && matches!(self.scopes.last(), None | Some(Scope::Block)) {
+                    self.newline = true;
+                }
+            }
+        }
+    }
+
+    /// whether a `{` starts the fields of a definition rather than an input object value
+    fn opens_block(&self) -> bool {
+        matches!(self.scopes.last(), None | Some(Scope::Block))
+            && !matches!(self.previous, Some((TokenKind::Eq, _)))
+    }
+
+    /// whether a token starts a new definition, or a new item in the current scope
+    fn starts_item(&self, kind: TokenKind, data: &str) -> bool {
+        let Some((previous, previous_data)) = &self.previous else {
+            return true;
+        };
+        if self.scopes.is_empty() {
+            return kind == TokenKind::StringValue
+                || (kind == TokenKind::Name
+                    && DEFINITION_KEYWORDS.contains(&data)
+                    && !(*previous == TokenKind::Name && previous_data == "extend"));
+        }
+        let ends_item = matches!(
+            previous,
+            TokenKind::Name
+                | TokenKind::StringValue
+                | TokenKind::Int
+                | TokenKind::Float
+                | TokenKind::Bang
+                | TokenKind::RParen
+                | TokenKind::RBracket
+                | TokenKind::RCurly
+        );
+        let begins_item = matches!(
+            kind,
+            TokenKind::Name
+                | TokenKind::StringValue
+                | TokenKind::Int
+                | TokenKind::Float
+                | TokenKind::Dollar
+                | TokenKind::Spread
+                | TokenKind::LBracket
+                | TokenKind::LCurly
+        );
+        ends_item && begins_item
+    }
+
+    /// separates a definition from the one before it with a blank line
+    fn start_definition(&mut self) {
+        if !self.out.is_empty() {
+            self.out.push_str("\n\n");
+        }
+        self.newline = false;
+    }
+
+    /// comments are kept on a line of their own
+    fn push_comment(&mut self, data: &str) {
+        let comment = data.trim_end().to_string();
+        if self.scopes.is_empty() {
+            self.comments.push(comment);
+        } else {
+            self.newline = true;
+            self.write_indented(&comment);
+            self.newline = true;
+        }
+    }
+
+    fn flush_comments(&mut self) {
+        for comment in std::mem::take(&mut self.comments) {
+            if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+                self.newline = true;
+            }
+            self.write_indented(&comment);
+            self.newline = true;
+        }
+    }
+
+    fn write_token(&mut self, kind: TokenKind, data: &str, opens_block: bool) {
+        if !self.newline
+            && !self.out.is_empty()
+            && !self.out.ends_with('\n')
+            && self.needs_space(kind, data, opens_block)
+        {
+            self.out.push(' ');
+        }
+        self.write_indented(data);
+        self.previous = Some((kind, data.to_string()));
+    }
+
+    fn write_indented(&mut self, data: &str) {
+        if self.newline {
+            if !self.out.is_empty() {
+                self.out.push('\n');
+            }
+            for _ in 0..self.scopes.len() {
+                self.out.push_str("  ");
+            }
+            self.newline = false;
+        }
+        self.out.push_str(data);
+    }
+
+    fn needs_space(&self, kind: TokenKind, data: &str, opens_block: bool) -> bool {
+        if opens_block {
+            return true;
+        }
+        let Some((previous, _)) = &self.previous else {
+            return false;
+        };
+        if matches!(
+            kind,
+            TokenKind::Colon
+                | TokenKind::Bang
+                | TokenKind::LParen
+                | TokenKind::RParen
+                | TokenKind::RBracket
+        ) || (kind == TokenKind::RCurly && self.scopes.last() == Some(&Scope::Object))
+        {
+            return false;
+        }
+        match previous {
+            TokenKind::At | TokenKind::Dollar | TokenKind::LParen | TokenKind::LBracket => false,
+            TokenKind::LCurly => self.scopes.last() != Some(&Scope::Object),
+            TokenKind::Spread => data == "on",
+            _ => true,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush_comments();
+        let trimmed_len = self.out.trim_end().len();
+        self.out.truncate(trimmed_len);
+        self.out.push('\n');
+        self.out
+    }
+}
+
+/// A syntax error in SDL and where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// the line the error is on, starting from 1
+    pub line: usize,
+    /// the character in the line the error starts at, starting from 1
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Parses SDL, returning every syntax error in it. This only checks that the
+/// SDL is well-formed, not that it's a valid schema, which is left to Studio.
+pub fn syntax_errors(sdl: &str) -> Vec<SyntaxError> {
+    let tree = Parser::new(sdl).parse();
+    tree.errors()
+        .map(|error| {
+            let (line, column) = line_and_column(sdl, error.index());
+            SyntaxError {
+                line,
+                column,
+                message: error.message().to_string(),
+            }
+        })
+        .collect()
+}
+
+/// The names of the types and directives that SDL defines, with directives
+/// written like `@key`. Extensions aren't counted, since extending a type
+/// that's defined elsewhere is allowed.
+pub fn defined_names(sdl: &str) -> Vec<String> {
+    let document = Parser::new(sdl).parse().document();
+    document
+        .definitions()
+        .filter_map(|definition| {
+            match definition {
+                ast::Definition::ObjectTypeDefinition(def) => def.name(),
+                ast::Definition::InterfaceTypeDefinition(def) => def.name(),
+                ast::Definition::UnionTypeDefinition(def) => def.name(),
+                ast::Definition::EnumTypeDefinition(def) => def.name(),
+                ast::Definition::InputObjectTypeDefinition(def) => def.name(),
+                ast::Definition::ScalarTypeDefinition(def) => def.name(),
+                ast::Definition::DirectiveDefinition(def) => {
+                    return def.name().map(|name| format!("@{}", name.text()))
+                }
+                _ => None,
+            }
+            .map(|name| name.text().to_string())
+        })
+        .collect()
+}
+
+/// The object types that look like entities because they have an `id: ID`
+/// field, if the SDL has no `@key` directives at all. Nothing is returned
+/// when any type has a `@key`, since the schema is clearly declaring its
+/// entities then, and the root operation types are never counted.
+pub fn unkeyed_entities(sdl: &str) -> Vec<String> {
+    let document = Parser::new(sdl).parse().document();
+    let mut entities = Vec::new();
+    for definition in document.definitions() {
+        let directives = match &definition {
+            ast::Definition::ObjectTypeDefinition(def) => def.directives(),
+            ast::Definition::ObjectTypeExtension(ext) => ext.directives(),
+            ast::Definition::InterfaceTypeDefinition(def) => def.directives(),
+            ast::Definition::InterfaceTypeExtension(ext) => ext.directives(),
+            _ => None,
+        };
+        let has_key = directives.map_or(false, |directives| {
+            directives
+                .directives()
+                .any(|directive| directive.name().map_or(false, |name| name.text() == "key"))
+        });
+        if has_key {
+            return Vec::new();
+        }
+        if let ast::Definition::ObjectTypeDefinition(def) = definition {
+            let Some(name) = def.name().map(|name| name.text().to_string()) else {
+                continue;
+            };
+            let has_id = def.fields_definition().map_or(false, |fields| {
+                fields.field_definitions().any(|field| {
+                    field.name().map_or(false, |name| name.text() == "id")
+                        && field.ty().map_or(false, is_id_type)
+                })
+            });
+            if has_id && !matches!(name.as_str(), "Query" | "Mutation" | "Subscription") {
+                entities.push(name);
+            }
+        }
+    }
+    entities
+}
+
+/// whether a field's type is `ID` or `ID!`
+fn is_id_type(ty: ast::Type) -> bool {
+    let named_type = match ty {
+        ast::Type::NamedType(named_type) => Some(named_type),
+        ast::Type::NonNullType(non_null) => non_null.named_type(),
+        ast::Type::ListType(_) => None,
+    };
+    named_type
+        .and_then(|named_type| named_type.name())
+        .map_or(false, |name| name.text() == "ID")
+}
+
+/// the 1-based line and column of the byte at `index`
+fn line_and_column(sdl: &str, index: usize) -> (usize, usize) {
+    let mut index = index.min(sdl.len());
+    while !sdl.is_char_boundary(index) {
+        index -= 1;
+    }
+    let before = &sdl[..index];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SDL: &str = r#"type Query {
+  "the current user"
+  me: User
+}
+
+type User @key(fields: "id") {
+  id: ID!
+}
+"#;
+
+    #[test]
+    fn it_treats_identical_sdl_as_equivalent() {
+        assert!(is_equivalent(SDL, SDL));
+    }
+
+    #[test]
+    fn it_ignores_trailing_whitespace_comments_and_blank_lines() {
+        let reformatted = "# the schema for the accounts subgraph\r\ntype Query {   \r\n  \"the current user\"\r\n  me: User # who's asking\r\n}\r\n\r\n\r\n# users\r\ntype User @key(fields: \"id\") {\t\r\n  id: ID!\r\n}";
+        assert_eq!(normalize(reformatted), normalize(SDL));
+        assert!(is_equivalent(SDL, reformatted));
+    }
+
+    #[test]
+    fn it_does_not_ignore_real_changes() {
+        let changed = SDL.replace("me: User", "me: User!");
+        assert!(!is_equivalent(SDL, &changed));
+    }
+
+    #[test]
+    fn it_does_not_ignore_changes_to_indentation() {
+        let changed = SDL.replace("  id: ID!", "    id: ID!");
+        assert!(!is_equivalent(SDL, &changed));
+    }
+
+    #[test]
+    fn it_does_not_touch_whitespace_or_hashes_in_strings() {
+        let described =
+            "\"\"\"\nthe current user   \n\n# not a comment\n\"\"\"\ntype User { id: ID! }";
+        let trimmed = "\"\"\"\nthe current user\n# not a comment\n\"\"\"\ntype User { id: ID! }";
+        assert!(!is_equivalent(described, trimmed));
+        assert!(normalize(described).unwrap().contains("# not a comment"));
+    }
+
+    const MESSY_SDL: &str = r#"extend schema @link(url: "https://specs.apollo.dev/federation/v2.3", import: ["@key" "@shareable"])
+# the accounts subgraph
+"""
+A user
+  of the app
+"""
+type User @key(fields: "id") { "the id" id: ID!,
+  name(   upper: Boolean = false  "how long" length: Int): String @shareable @deprecated(reason: "use fullName")
+    friends(first: Int = 10 filter: UserFilter = {active: true, tags: ["a", "b"]}): [User!]! }
+type Query{me:User # who's asking
+}
+enum Color { RED GREEN @deprecated BLUE }
+union Thing = User | Query
+directive @custom(arg: String) repeatable on FIELD_DEFINITION | OBJECT
+scalar Empty
+input UserFilter { active: Boolean tags: [String!] }
+"#;
+
+    #[test]
+    fn it_formats_sdl_consistently() {
+        assert_eq!(
+            format(MESSY_SDL).unwrap(),
+            r#"extend schema @link(url: "https://specs.apollo.dev/federation/v2.3", import: ["@key", "@shareable"])
+
+# the accounts subgraph
+"""
+A user
+  of the app
+"""
+type User @key(fields: "id") {
+  "the id"
+  id: ID!
+  name(upper: Boolean = false, "how long" length: Int): String @shareable @deprecated(reason: "use fullName")
+  friends(first: Int = 10, filter: UserFilter = {active: true, tags: ["a", "b"]}): [User!]!
+}
+
+type Query {
+  me: User
+  # who's asking
+}
+
+enum Color {
+  RED
+  GREEN @deprecated
+  BLUE
+}
+
+union Thing = User | Query
+
+directive @custom(arg: String) repeatable on FIELD_DEFINITION | OBJECT
+
+scalar Empty
+
+input UserFilter {
+  active: Boolean
+  tags: [String!]
+}
+"#
+        );
+    }
+
+    #[test]
+    fn it_formats_formatted_sdl_the_same_way() {
+        let formatted = format(MESSY_SDL).unwrap();
+        assert_eq!(format(&formatted).unwrap(), formatted);
+        assert_eq!(format(SDL).unwrap(), SDL);
+    }
+
+    #[test]
+    fn it_keeps_directives_and_descriptions_when_formatting() {
+        let formatted = format(MESSY_SDL).unwrap();
+        for kept in [
+            r#"@link(url: "https://specs.apollo.dev/federation/v2.3", import: ["@key", "@shareable"])"#,
+            r#"@key(fields: "id")"#,
+            r#"@deprecated(reason: "use fullName")"#,
+            "@shareable",
+            "GREEN @deprecated",
+            "\"\"\"\nA user\n  of the app\n\"\"\"",
+            "\"the id\"",
+            "\"how long\"",
+        ] {
+            assert!(
+                formatted.contains(kept),
+                "{kept} is missing from\n{formatted}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_does_not_format_sdl_with_syntax_errors() {
+        let errors = format("type Query {\n  me: User\n").unwrap_err();
+        assert!(!errors.is_empty());
+    }
+
+    #[test]
+    fn it_lists_the_types_and_directives_sdl_defines() {
+        let sdl = r#"
+            directive @audit(reason: String) on FIELD_DEFINITION
+            scalar Date
+            type Query { me: User }
+            extend type Query { users: [User] }
+            interface Node { id: ID! }
+            union Thing = User | Post
+            enum Role { ADMIN }
+            input UserFilter { role: Role }
+            extend schema @link(url: "https://specs.apollo.dev/federation/v2.3")
+        "#;
+        assert_eq!(
+            defined_names(sdl),
+            vec![
+                "@audit",
+                "Date",
+                "Query",
+                "Node",
+                "Thing",
+                "Role",
+                "UserFilter"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_finds_no_syntax_errors_in_valid_sdl() {
+        assert_eq!(syntax_errors(SDL), Vec::new());
+    }
+
+    #[test]
+    fn it_reports_where_syntax_errors_are() {
+        let errors = syntax_errors("type Query {\n  me: User\n  name String\n}\n");
+        assert!(!errors.is_empty());
+        assert_eq!((errors[0].line, errors[0].column), (3, 8));
+        assert_eq!(errors[0].to_string(), format!("3:8: {}", errors[0].message));
+    }
+
+    #[test]
+    fn it_finds_no_unkeyed_entities_when_there_are_keys() {
+        assert_eq!(unkeyed_entities(SDL), Vec::<String>::new());
+        let extended = "type Query { me: User }\ntype User { id: ID! }\nextend type Product @key(fields: \"upc\") { upc: String! }";
+        assert_eq!(unkeyed_entities(extended), Vec::<String>::new());
+    }
+
+    #[test]
+    fn it_finds_entity_looking_types_without_keys() {
+        let sdl = "type Query {\n  id: ID!\n  me: User\n}\n\ntype User {\n  id: ID!\n  name: String\n}\n\ntype Review {\n  id: ID\n}\n\ntype Tag {\n  id: String\n}\n";
+        assert_eq!(unkeyed_entities(sdl), vec!["User", "Review"]);
+    }
+}
@@ -1,7 +1,9 @@
 use assert_cmd::Command;
-use assert_fs::TempDir;
+use assert_fs::{prelude::*, TempDir};
 use camino::Utf8PathBuf;
+use httpmock::MockServer;
 use predicates::prelude::*;
+use serde_json::json;
 
 use houston::{Config, Profile};
 use rover::utils::env::RoverEnvKey;
@@ -38,6 +40,100 @@ fn it_can_list_one_profile() {
     result.stdout(predicate::str::contains(CUSTOM_PROFILE));
 }
 
+#[test]
+fn it_authenticates_with_a_profile_from_the_config_dir() {
+    let config_dir = TempDir::new().unwrap();
+    let config_dir_path = Utf8PathBuf::try_from(config_dir.path().to_path_buf()).unwrap();
+    let config = Config::new(Some(&config_dir_path), None).unwrap();
+    Profile::set_api_key(CUSTOM_PROFILE, &config, CUSTOM_API_KEY).unwrap();
+    let schema = config_dir.child("schema.graphql");
+    schema.write_str("type Query { hello: String }").unwrap();
+    let studio = MockServer::start();
+    let routing_url_request = studio.mock(|when, then| {
+        when.body_contains("SubgraphRoutingUrlQuery")
+            .header("x-api-key", CUSTOM_API_KEY);
+        then.status(200).json_body(json!({
+            "data": {
+                "variant": {
+                    "__typename": "GraphVariant",
+                    "subgraph": { "url": "https://accounts.example.com/graphql" }
+                }
+            }
+        }));
+    });
+
+    let mut cmd = Command::cargo_bin("rover").unwrap();
+    cmd.env_remove(RoverEnvKey::Key.to_string())
+        .env(RoverEnvKey::ConfigHome.to_string(), get_temp_dir())
+        .env(RoverEnvKey::RegistryUrl.to_string(), studio.url("/graphql"))
+        .env(RoverEnvKey::TelemetryDisabled.to_string(), "1")
+        .args([
+            "subgraph",
+            "publish",
+            "my-graph@current",
+            "--name",
+            "accounts",
+            "--dry-run",
+            "--profile",
+            CUSTOM_PROFILE,
+            "--config-dir",
+            config_dir_path.as_str(),
+            "--skip-update-check",
+            "--schema",
+        ])
+        .arg(schema.path())
+        .assert()
+        .success();
+
+    routing_url_request.assert();
+}
+
+#[test]
+fn it_errors_when_the_config_dir_does_not_exist() {
+    let missing = get_temp_dir().join("missing");
+
+    let mut cmd = Command::cargo_bin("rover").unwrap();
+    cmd.env_remove(RoverEnvKey::Key.to_string())
+        .env(RoverEnvKey::ConfigHome.to_string(), get_temp_dir())
+        .env(RoverEnvKey::TelemetryDisabled.to_string(), "1")
+        .args([
+            "config",
+            "whoami",
+            "--config-dir",
+            missing.as_str(),
+            "--skip-update-check",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Could not read configuration profiles from {}",
+            missing
+        )));
+}
+
 fn get_temp_dir() -> Utf8PathBuf {
     Utf8PathBuf::try_from(TempDir::new().unwrap().path().to_path_buf()).unwrap()
 }
+
+#[test]
+fn it_only_skips_a_missing_env_file_that_was_not_named() {
+    // the directory has to outlive the commands that run in it
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("rover").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .env(RoverEnvKey::ConfigHome.to_string(), temp_dir.path())
+        .args(["config", "list", "--env-file"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("rover").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .env(RoverEnvKey::ConfigHome.to_string(), temp_dir.path())
+        .args(["config", "list", "--env-file=ci.env"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Could not read the environment file \"ci.env\"",
+        ));
+}
@@ -0,0 +1,1719 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use httpmock::{Method::HEAD, Mock, MockServer};
+use predicates::prelude::*;
+use serde_json::{json, Value};
+
+#[test]
+fn it_does_not_publish_on_a_dry_run() {
+    let studio = MockServer::start();
+    let any_request = studio.mock(|when, then| {
+        when.any_request();
+        then.status(500);
+    });
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(
+        &studio,
+        &config_home,
+        &["--routing-url", ROUTING_URL, "--dry-run"],
+    )
+    .success()
+    .stderr(predicate::str::contains("This is a dry run."))
+    .stderr(predicate::str::contains(ROUTING_URL))
+    .stderr(predicate::str::contains("schema: 28 bytes"));
+
+    any_request.assert_hits(0);
+}
+
+/// dry runs a publish of `--schema <schema>` from `dir`, with `stdin` piped in
+fn dry_run_schema(
+    schema: &str,
+    dir: &assert_fs::TempDir,
+    stdin: &str,
+) -> assert_cmd::assert::Assert {
+    let studio = MockServer::start();
+    mock_routing_url(&studio, ROUTING_URL);
+
+    subgraph_publish_to(&studio, dir)
+        .current_dir(dir.path())
+        .args([
+            "--name",
+            "accounts",
+            "--routing-url",
+            ROUTING_URL,
+            "--dry-run",
+            "--schema",
+            schema,
+        ])
+        .write_stdin(stdin)
+        .assert()
+}
+
+#[test]
+fn it_reads_the_schema_from_stdin_with_a_dash() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    // a file named `-` is only read when it's passed as a path
+    dir.child("-")
+        .write_str("type Query { fromFile: String }")
+        .unwrap();
+
+    dry_run_schema("-", &dir, "type Query { hello: String }")
+        .success()
+        .stderr(predicate::str::contains("schema: 28 bytes"));
+}
+
+#[test]
+fn it_reads_a_file_named_dash_with_a_path() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("-")
+        .write_str("type Query { fromFile: String }")
+        .unwrap();
+
+    dry_run_schema("./-", &dir, "type Query { hello: String }")
+        .success()
+        .stderr(predicate::str::contains("schema: 31 bytes"));
+}
+
+/// the routing url that [`mock_successful_publish`] says the accounts subgraph has
+const ROUTING_URL: &str = "https://accounts.example.com/graphql";
+
+/// `rover subgraph publish my-graph@current`, keeping its config in `config_home`
+fn subgraph_publish(config_home: &assert_fs::TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("rover").unwrap();
+    cmd.env("APOLLO_KEY", "user:fake:key")
+        .env("APOLLO_CONFIG_HOME", config_home.path())
+        .env("APOLLO_TELEMETRY_DISABLED", "1")
+        .args([
+            "subgraph",
+            "publish",
+            "my-graph@current",
+            "--skip-update-check",
+        ]);
+    cmd
+}
+
+/// [`subgraph_publish`] to the Studio at `studio`
+fn subgraph_publish_to(studio: &MockServer, config_home: &assert_fs::TempDir) -> Command {
+    let mut cmd = subgraph_publish(config_home);
+    cmd.env("APOLLO_REGISTRY_URL", studio.url("/graphql"));
+    cmd
+}
+
+/// mocks a Studio that knows the current routing url and accepts any publish,
+/// returning the publish mock
+fn mock_successful_publish(studio: &MockServer) -> Mock<'_> {
+    mock_successful_publish_containing(studio, "")
+}
+
+/// like [`mock_successful_publish`], only accepting a publish whose request
+/// body contains `fragment`, like [`mock_publish_containing`]
+fn mock_successful_publish_containing<'a>(studio: &'a MockServer, fragment: &str) -> Mock<'a> {
+    mock_routing_url(studio, ROUTING_URL);
+    mock_publish_containing(studio, fragment)
+}
+
+/// like [`mock_successful_publish`], with `routing_url` as the current routing url
+fn mock_successful_publish_of<'a>(studio: &'a MockServer, routing_url: &str) -> Mock<'a> {
+    mock_routing_url(studio, routing_url);
+    mock_publish_containing(studio, "")
+}
+
+/// mocks Studio having `routing_url` as the accounts subgraph's routing url
+fn mock_routing_url(studio: &MockServer, routing_url: &str) {
+    studio.mock(|when, then| {
+        when.body_contains("SubgraphRoutingUrlQuery");
+        then.status(200).json_body(routing_url_body(routing_url));
+    });
+}
+
+/// mocks Studio accepting a publish whose request body contains `fragment`,
+/// returning the publish mock. the schema is sent as a json string, so the
+/// newlines of a schema in `fragment` have to be escaped
+fn mock_publish_containing<'a>(studio: &'a MockServer, fragment: &str) -> Mock<'a> {
+    studio.mock(|when, then| {
+        when.body_contains("SubgraphPublishMutation")
+            .body_contains(fragment);
+        then.status(200).json_body(published_body());
+    })
+}
+
+/// the body of Studio's answer to a routing url query
+fn routing_url_body(routing_url: &str) -> Value {
+    json!({
+        "data": {
+            "variant": {
+                "__typename": "GraphVariant",
+                "subgraph": { "url": routing_url }
+            }
+        }
+    })
+}
+
+/// the body of Studio's answer to a successful publish
+fn published_body() -> Value {
+    json!({
+        "data": {
+            "graph": {
+                "publishSubgraph": {
+                    "compositionConfig": { "schemaHash": "5gf564" },
+                    "errors": [],
+                    "didUpdateGateway": true,
+                    "serviceWasCreated": false,
+                    "launchCliCopy": null,
+                    "launchUrl": null
+                }
+            }
+        }
+    })
+}
+
+#[test]
+fn it_does_not_ask_to_confirm_outside_of_a_tty() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(&studio, &config_home, &["--confirm"])
+        .success()
+        .stderr(predicate::str::contains("bytes of SDL").not());
+    publish_request.assert();
+}
+
+/// publishes with `args` added, returning the command's assertion
+fn publish_with_args(
+    studio: &MockServer,
+    config_home: &assert_fs::TempDir,
+    args: &[&str],
+) -> assert_cmd::assert::Assert {
+    publish_sdl_with_args(studio, config_home, "type Query { hello: String }", args)
+}
+
+/// publishes `sdl` with `args` added, returning the command's assertion
+fn publish_sdl_with_args(
+    studio: &MockServer,
+    config_home: &assert_fs::TempDir,
+    sdl: &str,
+    args: &[&str],
+) -> assert_cmd::assert::Assert {
+    let schema = config_home.child("schema.graphql");
+    schema.write_str(sdl).unwrap();
+
+    subgraph_publish_to(studio, config_home)
+        .args(["--name", "accounts", "--convert"])
+        .args(args)
+        .arg("--schema")
+        .arg(schema.path())
+        .assert()
+}
+
+/// publishes the schema piped to stdin as `stdin` with `args` added,
+/// returning the command's assertion
+fn publish_stdin_with_args(
+    studio: &MockServer,
+    config_home: &assert_fs::TempDir,
+    stdin: &str,
+    args: &[&str],
+) -> assert_cmd::assert::Assert {
+    subgraph_publish_to(studio, config_home)
+        .args(["--name", "accounts", "--convert"])
+        .args(args)
+        .args(["--schema", "-"])
+        .write_stdin(stdin)
+        .assert()
+}
+
+#[test]
+fn it_does_not_publish_an_empty_schema_file() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_sdl_with_args(&studio, &config_home, "", &[])
+        .failure()
+        .stderr(predicate::str::contains("is an empty file"));
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_does_not_publish_a_schema_of_only_whitespace() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_sdl_with_args(&studio, &config_home, "  \n\t\n\n", &[])
+        .failure()
+        .stderr(predicate::str::contains(
+            "The schema for the accounts subgraph is empty, so it was not published.",
+        ))
+        .stderr(predicate::str::contains("schema.graphql"))
+        .stderr(predicate::str::contains("and that it's been saved."));
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_does_not_publish_an_empty_schema_from_stdin() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_stdin_with_args(&studio, &config_home, "\n\n   \n", &[])
+        .failure()
+        .stderr(predicate::str::contains(
+            "The schema for the accounts subgraph is empty",
+        ))
+        .stderr(predicate::str::contains(
+            "Make sure the command you are piping to Rover outputs the schema.",
+        ));
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_publishes_a_schema_without_syntax_errors() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_sdl_with_args(
+        &studio,
+        &config_home,
+        "type Query {\n  me: User\n}\n\ntype User @key(fields: \"id\") {\n  id: ID!\n}\n",
+        &[],
+    )
+    .success();
+    publish_request.assert();
+}
+
+#[test]
+fn it_does_not_publish_a_schema_with_syntax_errors() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_sdl_with_args(
+        &studio,
+        &config_home,
+        "type Query {\n  me: User\n  name String\n}\n",
+        &[],
+    )
+    .failure()
+    .stderr(predicate::str::contains(
+        "The schema for the accounts subgraph has syntax errors:",
+    ))
+    .stderr(predicate::str::contains("3:8:"))
+    .stderr(predicate::str::contains(
+        "    3 |   name String\n      |        ^",
+    ))
+    .stderr(predicate::str::contains("--skip-validation"));
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_publishes_a_schema_with_syntax_errors_with_skip_validation() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_sdl_with_args(
+        &studio,
+        &config_home,
+        "type Query {\n  me: User\n  name String\n}\n",
+        &["--skip-validation"],
+    )
+    .success();
+    publish_request.assert();
+}
+
+#[test]
+fn it_publishes_the_normalized_schema_with_normalize() {
+    let studio = MockServer::start();
+    let normalized_publish = mock_successful_publish_containing(
+        &studio,
+        r#"type Query {\n  me: User @deprecated(reason: \"use viewer\")\n}\n"#,
+    );
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_sdl_with_args(
+        &studio,
+        &config_home,
+        "type Query { me: User   @deprecated(reason: \"use viewer\") }",
+        &["--normalize"],
+    )
+    .success();
+    normalized_publish.assert();
+}
+
+/// publishes the `.graphql` files in `dir` with `--schema-from-build`
+fn publish_schema_dir(
+    studio: &MockServer,
+    config_home: &assert_fs::TempDir,
+    dir: &assert_fs::fixture::ChildPath,
+) -> assert_cmd::assert::Assert {
+    subgraph_publish_to(studio, config_home)
+        .args(["--name", "accounts", "--convert", "--schema-from-build"])
+        .arg(dir.path())
+        .assert()
+}
+
+#[test]
+fn it_publishes_the_schema_files_in_a_directory_in_order() {
+    let studio = MockServer::start();
+    let combined_publish = mock_successful_publish_containing(
+        &studio,
+        r"type Query { me: User }\n\ntype User { id: ID! }\n\nextend type Query { users: [User] }\n",
+    );
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let dir = config_home.child("schema");
+    dir.child("c_users.graphql")
+        .write_str("extend type Query { users: [User] }\n")
+        .unwrap();
+    dir.child("a_query.graphql")
+        .write_str("type Query { me: User }\n")
+        .unwrap();
+    dir.child("b_user.graphql")
+        .write_str("type User { id: ID! }\n")
+        .unwrap();
+    dir.child("notes.md").write_str("# not a schema").unwrap();
+
+    publish_schema_dir(&studio, &config_home, &dir).success();
+    combined_publish.assert();
+}
+
+#[test]
+fn it_does_not_publish_an_empty_schema_directory() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let dir = config_home.child("schema");
+    dir.create_dir_all().unwrap();
+    dir.child("README.md").write_str("# schema").unwrap();
+
+    publish_schema_dir(&studio, &config_home, &dir)
+        .failure()
+        .stderr(predicate::str::contains("There are no `.graphql` files in"));
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_does_not_publish_schema_files_that_define_the_same_type() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let dir = config_home.child("schema");
+    dir.child("a.graphql")
+        .write_str("type Query { me: User }\ntype User { id: ID! }\n")
+        .unwrap();
+    dir.child("b.graphql")
+        .write_str("type User { name: String }\n")
+        .unwrap();
+
+    publish_schema_dir(&studio, &config_home, &dir)
+        .failure()
+        .stderr(predicate::str::contains("`User` is defined in both"))
+        .stderr(predicate::str::contains("a.graphql"))
+        .stderr(predicate::str::contains("b.graphql"));
+    publish_request.assert_hits(0);
+}
+
+const UNKEYED_SDL: &str = "type Query {\n  me: User\n}\n\ntype User {\n  id: ID!\n}\n";
+
+#[test]
+fn it_warns_about_entities_without_keys() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_sdl_with_args(&studio, &config_home, UNKEYED_SDL, &[])
+        .success()
+        .stderr(predicate::str::contains(
+            "The schema for the `accounts` subgraph has no `@key` directives",
+        ))
+        .stderr(predicate::str::contains("`User` has an `id` field"));
+    publish_request.assert();
+}
+
+#[test]
+fn it_warns_loudly_when_tls_verification_is_skipped() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_sdl_with_args(
+        &studio,
+        &config_home,
+        "type Query { hello: String }",
+        &["--insecure-skip-tls-verify", "--quiet"],
+    )
+    .success()
+    .stderr(predicate::str::contains(
+        "`--insecure-skip-tls-verify` is set, so TLS certificates and hostnames are NOT being verified.",
+    ));
+    publish_request.assert();
+
+    publish_sdl_with_args(&studio, &config_home, "type Query { hello: String }", &[])
+        .success()
+        .stderr(predicate::str::contains("--insecure-skip-tls-verify").not());
+}
+
+#[test]
+fn it_does_not_warn_about_keys_when_the_schema_has_them() {
+    let studio = MockServer::start();
+    mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_sdl_with_args(
+        &studio,
+        &config_home,
+        "type Query {\n  me: User\n}\n\ntype User @key(fields: \"id\") {\n  id: ID!\n}\n",
+        &[],
+    )
+    .success()
+    .stderr(predicate::str::contains("@key").not());
+}
+
+#[test]
+fn it_does_not_warn_about_keys_with_yes_or_quiet() {
+    for flag in ["--yes", "--quiet"] {
+        let studio = MockServer::start();
+        let publish_request = mock_successful_publish(&studio);
+        let config_home = assert_fs::TempDir::new().unwrap();
+
+        publish_sdl_with_args(&studio, &config_home, UNKEYED_SDL, &[flag])
+            .success()
+            .stderr(predicate::str::contains("@key").not());
+        publish_request.assert();
+    }
+}
+
+#[test]
+fn it_only_watches_schemas_read_from_a_file() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_stdin_with_args(
+        &studio,
+        &config_home,
+        "type Query { hello: String }",
+        &["--watch"],
+    )
+    .failure()
+    .stderr(predicate::str::contains(
+        "`--watch` can only watch a schema that is read from a file.",
+    ));
+    publish_request.assert_hits(0);
+}
+
+/// mocks a publish whose subgraph composes with the given errors, which are
+/// made up of a message and a code
+fn mock_publish_with_composition_errors<'a>(
+    studio: &'a MockServer,
+    errors: &[(&str, &str)],
+) -> Mock<'a> {
+    mock_routing_url(studio, ROUTING_URL);
+    let errors: Vec<Value> = errors
+        .iter()
+        .map(|(message, code)| json!({ "message": message, "code": code }))
+        .collect();
+    let mut body = published_body();
+    let publish = &mut body["data"]["graph"]["publishSubgraph"];
+    publish["compositionConfig"] = Value::Null;
+    publish["errors"] = json!(errors);
+    publish["didUpdateGateway"] = json!(false);
+    studio.mock(|when, then| {
+        when.body_contains("SubgraphPublishMutation");
+        then.status(200).json_body(body);
+    })
+}
+
+#[test]
+fn it_exits_with_a_distinct_code_when_composition_fails() {
+    let studio = MockServer::start();
+    let publish_request = mock_publish_with_composition_errors(
+        &studio,
+        &[(
+            "[accounts] User -> A @key selects id, but User.id could not be found",
+            "KEY_FIELDS_SELECT_INVALID_TYPE",
+        )],
+    );
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(&studio, &config_home, &[])
+        .code(3)
+        .stderr(predicate::str::contains("KEY_FIELDS_SELECT_INVALID_TYPE"));
+    publish_request.assert();
+}
+
+#[test]
+fn it_checks_routing_urls_as_strictly_as_the_config_says() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let local = ["--routing-url", "http://localhost:4000"];
+
+    config_home
+        .child("rover.toml")
+        .write_str("routing_url_validation = \"strict\"\n")
+        .unwrap();
+    publish_with_args(&studio, &config_home, &local)
+        .failure()
+        .stderr(predicate::str::contains(
+            "doesn't allow publishing local-only routing URLs",
+        ));
+    publish_request.assert_hits(0);
+
+    config_home
+        .child("rover.toml")
+        .write_str("routing_url_validation = \"warn\"\n")
+        .unwrap();
+    publish_with_args(&studio, &config_home, &local)
+        .success()
+        .stderr(predicate::str::contains(
+            "is not routable via the public internet",
+        ));
+    publish_request.assert_hits(1);
+
+    config_home
+        .child("rover.toml")
+        .write_str("routing_url_validation = \"off\"\n")
+        .unwrap();
+    publish_with_args(&studio, &config_home, &local)
+        .success()
+        .stderr(predicate::str::contains("is not routable").not());
+    publish_request.assert_hits(2);
+}
+
+#[test]
+fn it_does_not_check_fetched_routing_urls_when_the_config_turns_checking_off() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish_of(&studio, "http://localhost:4000");
+    let config_home = assert_fs::TempDir::new().unwrap();
+    config_home
+        .child("rover.toml")
+        .write_str("routing_url_validation = \"off\"\n")
+        .unwrap();
+
+    publish_with_args(&studio, &config_home, &[])
+        .success()
+        .stderr(predicate::str::contains("is not routable").not())
+        .stderr(predicate::str::contains("[y/N]").not());
+    publish_request.assert();
+}
+
+#[test]
+fn it_lets_routing_url_flags_override_the_config() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+    config_home
+        .child("rover.toml")
+        .write_str("routing_url_validation = \"strict\"\n")
+        .unwrap();
+
+    publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--routing-url",
+            "http://localhost:4000",
+            "--allow-invalid-routing-url",
+        ],
+    )
+    .success();
+    publish_request.assert_hits(1);
+
+    config_home
+        .child("rover.toml")
+        .write_str("routing_url_validation = \"off\"\n")
+        .unwrap();
+    publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--routing-url",
+            "http://localhost:4000",
+            "--reject-local-routing-url",
+        ],
+    )
+    .failure();
+    publish_request.assert_hits(1);
+}
+
+#[test]
+fn it_prints_a_single_line_with_summary_only() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(&studio, &config_home, &["--summary-only"])
+        .success()
+        .stdout("success | accounts | my-graph@current\n")
+        .stderr(predicate::str::contains("Publishing SDL").not())
+        .stderr(predicate::str::contains("Published subgraph").not());
+    publish_request.assert();
+}
+
+#[test]
+fn it_counts_composition_errors_with_summary_only() {
+    let studio = MockServer::start();
+    let publish_request = mock_publish_with_composition_errors(
+        &studio,
+        &[
+            (
+                "[accounts] User -> A @key selects id, but User.id could not be found",
+                "KEY_FIELDS_SELECT_INVALID_TYPE",
+            ),
+            (
+                "[accounts] Query.me is defined in more than one subgraph",
+                "INVALID_FIELD_SHARING",
+            ),
+        ],
+    );
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(&studio, &config_home, &["--summary-only"])
+        .code(3)
+        .stdout("composition failed | accounts | my-graph@current | 2 composition errors\n")
+        .stderr(predicate::str::contains("KEY_FIELDS_SELECT_INVALID_TYPE").not());
+    publish_request.assert();
+}
+
+#[test]
+fn it_prints_a_single_line_when_a_publish_fails_with_summary_only() {
+    let studio = MockServer::start();
+    mock_routing_url(&studio, ROUTING_URL);
+    let publish_request = studio.mock(|when, then| {
+        when.body_contains("SubgraphPublishMutation");
+        then.status(200).json_body(json!({
+            "errors": [{ "message": "accounts could not be published" }]
+        }));
+    });
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(&studio, &config_home, &["--summary-only"])
+        .failure()
+        .stdout("failed | accounts | my-graph@current\n")
+        .stderr(predicate::str::contains("accounts could not be published"));
+    publish_request.assert();
+}
+
+#[test]
+fn it_exits_successfully_when_composition_succeeds() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(&studio, &config_home, &[]).code(0);
+    publish_request.assert();
+}
+
+#[test]
+fn it_saves_the_publish_response_to_a_file() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let response_file = config_home.child("audit/publishes/accounts.json");
+
+    publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--response-file",
+            response_file.path().to_str().unwrap(),
+            "--create-parent-dirs",
+        ],
+    )
+    .success()
+    .stderr(predicate::str::contains("Publishing SDL to"));
+
+    let response: Value =
+        serde_json::from_str(&std::fs::read_to_string(response_file.path()).unwrap()).unwrap();
+    assert_eq!(response["error"], Value::Null);
+    let data = &response["data"];
+    assert_eq!(data["success"], true);
+    assert_eq!(data["graph_ref"], "my-graph@current");
+    assert_eq!(data["subgraph"], "accounts");
+    assert_eq!(data["api_schema_hash"], "5gf564");
+    assert_eq!(data["supergraph_was_updated"], true);
+    assert_eq!(data["routing_url"], ROUTING_URL);
+    publish_request.assert();
+}
+
+#[test]
+fn it_says_a_dry_run_finished_when_the_response_file_cannot_be_written() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let response_file = config_home.child("missing/accounts.json");
+
+    publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--dry-run",
+            "--response-file",
+            response_file.path().to_str().unwrap(),
+        ],
+    )
+    .code(4)
+    .stderr(predicate::str::contains(
+        "The dry run of the publish to my-graph@current finished",
+    ))
+    .stderr(predicate::str::contains("succeeded").not())
+    .stderr(predicate::str::contains("publish again").not());
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_says_the_publish_succeeded_when_the_response_file_cannot_be_written() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let response_file = config_home.child("missing/accounts.json");
+
+    let output = publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--response-file",
+            response_file.path().to_str().unwrap(),
+            "--format",
+            "json",
+        ],
+    )
+    .code(4)
+    .stderr(predicate::str::contains(
+        "The publish to my-graph@current succeeded",
+    ))
+    .stderr(predicate::str::contains("publish again."))
+    .stderr(predicate::str::contains("--create-parent-dirs"))
+    .get_output()
+    .stdout
+    .clone();
+
+    // the result of the publish is still printed
+    let output: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(output["data"]["success"], true);
+    assert_eq!(output["data"]["subgraph"], "accounts");
+    response_file.assert(predicate::path::missing());
+    publish_request.assert();
+}
+
+#[test]
+fn it_prints_the_publish_result_as_json() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    let output = publish_with_args(
+        &studio,
+        &config_home,
+        &["--routing-url", ROUTING_URL, "--format", "json"],
+    )
+    .success()
+    .stderr(predicate::str::contains("Publishing SDL to"))
+    .get_output()
+    .stdout
+    .clone();
+
+    let output: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(output["json_version"], "1");
+    assert_eq!(output["error"], Value::Null);
+    let data = &output["data"];
+    assert_eq!(data["success"], true);
+    assert_eq!(data["graph_ref"], "my-graph@current");
+    assert_eq!(data["subgraph"], "accounts");
+    assert_eq!(data["api_schema_hash"], "5gf564");
+    assert_eq!(data["supergraph_was_updated"], true);
+    assert_eq!(data["subgraph_was_created"], false);
+    assert_eq!(data["routing_url"], ROUTING_URL);
+    assert_eq!(data["warnings"], Value::Array(Vec::new()));
+    publish_request.assert();
+}
+
+#[test]
+fn it_lists_warnings_in_the_json_output() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    let output = publish_sdl_with_args(
+        &studio,
+        &config_home,
+        UNKEYED_SDL,
+        &[
+            "--routing-url",
+            "http://localhost:4000",
+            "--yes",
+            "--format",
+            "json",
+        ],
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+
+    let output: Value = serde_json::from_slice(&output).unwrap();
+    let warnings = output["data"]["warnings"].as_array().unwrap();
+    let codes: Vec<&str> = warnings
+        .iter()
+        .map(|warning| warning["code"].as_str().unwrap())
+        .collect();
+    assert_eq!(codes, ["LOCAL_ROUTING_URL", "MISSING_KEY_DIRECTIVES"]);
+    assert!(warnings[0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("The host `localhost` is not routable"));
+    publish_request.assert();
+}
+
+#[test]
+fn it_warns_when_the_routing_url_is_unreachable_after_publishing() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    // `.invalid` never resolves, so nothing can be connected to
+    let output = publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--routing-url",
+            "http://accounts.invalid/graphql",
+            "--verify-reachable",
+            "--format",
+            "json",
+        ],
+    )
+    .success()
+    .stderr(predicate::str::contains("could not be reached"))
+    .get_output()
+    .stdout
+    .clone();
+
+    let output: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        output["data"]["warnings"][0]["code"],
+        "UNREACHABLE_ROUTING_URL"
+    );
+    publish_request.assert();
+}
+
+#[test]
+fn it_does_not_publish_unsupported_routing_urls_outside_a_tty_with_yes() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(
+        &studio,
+        &config_home,
+        &["--routing-url", "ftp://accounts.example.com", "--yes"],
+    )
+    .failure()
+    .stderr(predicate::str::contains(
+        "The `ftp` protocol is not supported by the router.",
+    ));
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_fails_on_every_kind_of_warning_with_strict() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let keyed_sdl = "type Query { hello: String }";
+    // unsupported schemes can't be published outside of a TTY unless
+    // checking routing urls is turned off, which `--strict` ignores
+    let cases = [
+        (
+            "http://localhost:4000",
+            keyed_sdl,
+            "is not routable",
+            "warn",
+        ),
+        ("ftp://accounts.example.com", keyed_sdl, "ftp", "off"),
+        (
+            "https://accounts.example.com:80",
+            keyed_sdl,
+            "port 80",
+            "warn",
+        ),
+        ("file:///tmp/accounts.graphql", keyed_sdl, "file", "warn"),
+        (
+            "https://accounts.example.com",
+            UNKEYED_SDL,
+            "has no `@key` directives",
+            "warn",
+        ),
+    ];
+
+    let mut hits = 0;
+    for (routing_url, sdl, message, validation) in cases {
+        config_home
+            .child("rover.toml")
+            .write_str(&format!("routing_url_validation = \"{}\"\n", validation))
+            .unwrap();
+        let args = ["--routing-url", routing_url, "--yes"];
+        publish_sdl_with_args(&studio, &config_home, sdl, &args).success();
+        hits += 1;
+        publish_request.assert_hits(hits);
+
+        publish_sdl_with_args(
+            &studio,
+            &config_home,
+            sdl,
+            &[&args[..], &["--strict"]].concat(),
+        )
+        .failure()
+        .stderr(predicate::str::contains(message))
+        .stderr(predicate::str::contains("without `--strict`"));
+        publish_request.assert_hits(hits);
+    }
+}
+
+#[test]
+fn it_fails_with_strict_when_the_routing_url_is_unreachable() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let args = [
+        "--routing-url",
+        "http://accounts.invalid/graphql",
+        "--verify-reachable",
+    ];
+
+    publish_with_args(&studio, &config_home, &args).success();
+    publish_with_args(&studio, &config_home, &[&args[..], &["--strict"]].concat())
+        .failure()
+        .stderr(predicate::str::contains("could not be reached"));
+    // with `--strict`, it's checked before anything is published
+    publish_request.assert_hits(1);
+}
+
+#[test]
+fn it_says_nothing_was_published_when_strict_stops_an_unreachable_routing_url() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--routing-url",
+            "http://accounts.invalid/graphql",
+            "--verify-reachable",
+            "--strict",
+        ],
+    )
+    .failure()
+    .stderr(predicate::str::contains(
+        "The routing URL `http://accounts.invalid/graphql` could not be reached, so the subgraph was not published.",
+    ))
+    .stderr(predicate::str::contains("was published, but").not());
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_reports_every_warning_that_failed_a_strict_publish_as_json() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    let output = publish_sdl_with_args(
+        &studio,
+        &config_home,
+        UNKEYED_SDL,
+        &[
+            "--routing-url",
+            "http://localhost:4000",
+            "--yes",
+            "--strict",
+            "--format",
+            "json",
+        ],
+    )
+    .failure()
+    .get_output()
+    .stdout
+    .clone();
+
+    let output: Value = serde_json::from_slice(&output).unwrap();
+    let error = &output["error"];
+    let details = error["details"].as_array().unwrap();
+    let codes: Vec<&str> = details
+        .iter()
+        .map(|warning| warning["code"].as_str().unwrap())
+        .collect();
+    assert_eq!(codes, ["LOCAL_ROUTING_URL", "MISSING_KEY_DIRECTIVES"]);
+    let message = error["message"].as_str().unwrap();
+    for warning in details {
+        assert!(message.contains(warning["message"].as_str().unwrap()));
+    }
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_does_not_verify_local_routing_urls() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let subgraph = MockServer::start();
+    let head = subgraph.mock(|when, then| {
+        when.method(HEAD);
+        then.status(200);
+    });
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--routing-url",
+            &subgraph.url("/graphql"),
+            "--verify-reachable",
+            "--yes",
+        ],
+    )
+    .success()
+    .stderr(predicate::str::contains("could not be reached").not());
+    head.assert_hits(0);
+    publish_request.assert();
+}
+
+#[test]
+fn it_prints_plain_text_with_plain() {
+    let studio = MockServer::start();
+    mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let schema = config_home.child("schema.graphql");
+    schema.write_str("type Query { hello: String }").unwrap();
+
+    let publish = |plain: bool| {
+        let mut cmd = subgraph_publish_to(&studio, &config_home);
+        cmd.env("ROVER_EMOJI", "1")
+            .args([
+                "--name",
+                "accounts",
+                "--routing-url",
+                "http://localhost:4000",
+                "--yes",
+            ])
+            .arg("--schema")
+            .arg(schema.path());
+        if plain {
+            cmd.arg("--plain");
+        }
+        let output = cmd.assert().success().get_output().clone();
+        format!(
+            "{}{}",
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap()
+        )
+    };
+
+    // color is already turned off when stdout isn't a terminal, but emoji aren't
+    let decorated = publish(false);
+    assert!(!decorated.is_ascii(), "{decorated}");
+    let plain = publish(true);
+    assert!(plain.contains("Publishing SDL to my-graph@current"));
+    assert!(plain.is_ascii(), "{plain}");
+}
+
+#[test]
+fn it_streams_status_events_as_json() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    let output = publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--routing-url",
+            ROUTING_URL,
+            "--status-format",
+            "json",
+            "--format",
+            "json",
+        ],
+    )
+    .success()
+    .get_output()
+    .clone();
+
+    // every line on stderr is an event, and the result stays on stdout
+    let events: Vec<Value> = String::from_utf8(output.stderr)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let phases: Vec<(&str, &str)> = events
+        .iter()
+        .map(|event| {
+            (
+                event["phase"].as_str().unwrap(),
+                event["status"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        phases,
+        [
+            ("authenticate", "started"),
+            ("authenticate", "completed"),
+            // the routing url is given and no change is shown, so it isn't fetched
+            ("read_schema", "started"),
+            ("read_schema", "completed"),
+            ("validate_schema", "started"),
+            ("validate_schema", "completed"),
+            ("publish_mutation", "started"),
+            ("publish_mutation", "completed"),
+            ("result", "success"),
+        ]
+    );
+    for event in &events {
+        assert_eq!(event["subgraph"], "accounts");
+        assert!(event["timestamp"].is_string());
+    }
+    let result: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["data"]["subgraph"], "accounts");
+    publish_request.assert();
+}
+
+#[test]
+fn it_does_not_print_informational_messages_when_quiet() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    let output = publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--routing-url",
+            "http://localhost:4001",
+            "--quiet",
+            "--format",
+            "json",
+        ],
+    )
+    .success()
+    .stderr(predicate::str::contains("Publishing SDL to").not())
+    .stderr(predicate::str::contains("WARN").not())
+    .get_output()
+    .stdout
+    .clone();
+
+    // the result is still printed
+    let output: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(output["data"]["success"], true);
+    publish_request.assert();
+}
+
+/// mocks Studio accepting a publish of the named subgraph, returning the publish mock
+fn mock_subgraph_publish<'a>(studio: &'a MockServer, subgraph: &str) -> Mock<'a> {
+    mock_publish_containing(studio, &format!("\"subgraph\":\"{}\"", subgraph))
+}
+
+/// writes a manifest listing the accounts and products subgraphs
+fn write_manifest(dir: &assert_fs::TempDir) -> assert_fs::fixture::ChildPath {
+    for subgraph in ["accounts", "products"] {
+        dir.child(format!("{}.graphql", subgraph))
+            .write_str("type Query { hello: String }")
+            .unwrap();
+    }
+    let manifest = dir.child("subgraphs.yaml");
+    manifest
+        .write_str(
+            r#"accounts:
+  routing_url: https://accounts.example.com/graphql
+  schema_path: accounts.graphql
+products:
+  routing_url: https://products.example.com/graphql
+  schema_path: products.graphql
+"#,
+        )
+        .unwrap();
+    manifest
+}
+
+fn publish_manifest(
+    studio: &MockServer,
+    config_home: &assert_fs::TempDir,
+    manifest: &assert_fs::fixture::ChildPath,
+    args: &[&str],
+) -> assert_cmd::assert::Assert {
+    subgraph_publish_to(studio, config_home)
+        .args(["--convert", "--manifest"])
+        .arg(manifest.path())
+        .args(args)
+        .assert()
+}
+
+#[test]
+fn it_publishes_every_subgraph_in_a_manifest() {
+    let studio = MockServer::start();
+    studio.mock(|when, then| {
+        when.body_contains("SubgraphRoutingUrlQuery");
+        then.status(200).json_body(json!({
+            "data": { "variant": { "__typename": "GraphVariant", "subgraph": null } }
+        }));
+    });
+    let accounts = mock_subgraph_publish(&studio, "accounts");
+    let products = mock_subgraph_publish(&studio, "products");
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let manifest = write_manifest(&config_home);
+
+    let output = publish_manifest(&studio, &config_home, &manifest, &["--format", "json"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let output: Value = serde_json::from_slice(&output).unwrap();
+    let subgraphs = output["data"]["subgraphs"].as_array().unwrap();
+    assert_eq!(subgraphs.len(), 2);
+    assert_eq!(subgraphs[0]["subgraph"], "accounts");
+    assert_eq!(subgraphs[1]["subgraph"], "products");
+    assert!(subgraphs
+        .iter()
+        .all(|subgraph| subgraph["elapsed_ms"].is_u64()));
+    assert_eq!(
+        subgraphs[1]["routing_url"],
+        "https://products.example.com/graphql"
+    );
+    accounts.assert();
+    products.assert();
+}
+
+#[test]
+fn it_reports_which_subgraphs_were_published_when_a_manifest_publish_fails() {
+    let studio = MockServer::start();
+    studio.mock(|when, then| {
+        when.body_contains("SubgraphRoutingUrlQuery");
+        then.status(200).json_body(json!({
+            "data": { "variant": { "__typename": "GraphVariant", "subgraph": null } }
+        }));
+    });
+    let accounts = mock_subgraph_publish(&studio, "accounts");
+    studio.mock(|when, then| {
+        when.body_contains("SubgraphPublishMutation")
+            .body_contains("\"subgraph\":\"products\"");
+        then.status(200).json_body(json!({
+            "errors": [{ "message": "products could not be published" }]
+        }));
+    });
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let manifest = write_manifest(&config_home);
+
+    let stderr = publish_manifest(&studio, &config_home, &manifest, &[])
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+
+    let stderr = String::from_utf8(stderr).unwrap();
+    assert!(
+        stderr.contains("products could not be published"),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains(
+            "These subgraphs were published before the `products` subgraph failed: `accounts`."
+        ),
+        "{}",
+        stderr
+    );
+    accounts.assert();
+}
+
+/// mocks Studio having `sdl` published for the accounts subgraph
+fn mock_published_schema(studio: &MockServer, sdl: &str) {
+    studio.mock(|when, then| {
+        when.body_contains("IsFederatedGraph");
+        then.status(200).json_body(json!({
+            "data": { "graph": { "variant": { "subgraphs": [{ "name": "accounts" }] } } }
+        }));
+    });
+    studio.mock(|when, then| {
+        when.body_contains("SubgraphFetchQuery");
+        then.status(200).json_body(json!({
+            "data": {
+                "variant": {
+                    "__typename": "GraphVariant",
+                    "subgraph": {
+                        "url": "https://accounts.example.com/graphql",
+                        "activePartialSchema": { "sdl": sdl }
+                    },
+                    "subgraphs": [{ "name": "accounts" }]
+                }
+            }
+        }));
+    });
+}
+
+/// publishes `sdl` to the accounts subgraph with `--skip-if-unchanged`,
+/// returning the JSON output
+fn publish_if_changed(studio: &MockServer, sdl: &str) -> Value {
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    let output = publish_sdl_with_args(
+        studio,
+        &config_home,
+        sdl,
+        &[
+            "--routing-url",
+            ROUTING_URL,
+            "--skip-if-unchanged",
+            "--format",
+            "json",
+        ],
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    serde_json::from_slice(&output).unwrap()
+}
+
+#[test]
+fn it_skips_publishing_an_identical_schema() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    mock_published_schema(&studio, "type Query {\n  hello: String\n}\n");
+
+    let output = publish_if_changed(&studio, "type Query {\n  hello: String\n}\n");
+
+    assert_eq!(output["data"]["skipped"], true);
+    assert_eq!(output["data"]["subgraph"], "accounts");
+    assert_eq!(output["data"]["graph_ref"], "my-graph@current");
+    assert_eq!(
+        output["data"]["reason"],
+        "The schema and routing url of the accounts subgraph in my-graph@current are unchanged, so it was not published."
+    );
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_skips_publishing_a_schema_that_only_differs_by_whitespace_and_comments() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    mock_published_schema(&studio, "type Query {\n  hello: String\n}\n");
+
+    let output = publish_if_changed(
+        &studio,
+        "# accounts\r\ntype Query {  \r\n  hello: String # greets you\r\n}\r\n\r\n",
+    );
+
+    assert_eq!(output["data"]["skipped"], true);
+    assert_eq!(
+        output["data"]["reason"],
+        "The schema and routing url of the accounts subgraph in my-graph@current are unchanged, so it was not published."
+    );
+    publish_request.assert_hits(0);
+}
+
+#[test]
+fn it_publishes_a_changed_schema_with_skip_if_unchanged() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    mock_published_schema(&studio, "type Query {\n  hello: String\n}\n");
+
+    let output = publish_if_changed(&studio, "type Query {\n  hello: String!\n}\n");
+
+    assert_eq!(output["data"]["skipped"], Value::Null);
+    assert_eq!(output["data"]["success"], true);
+    publish_request.assert();
+}
+
+/// publishes the accounts subgraph with `routing_url_args`, asserting that
+/// the publish mutation was sent `expected_url`
+fn assert_publishes_url(routing_url_args: &[&str], expected_url: &str) {
+    let studio = MockServer::start();
+    let publish_request =
+        mock_successful_publish_containing(&studio, &format!("\"url\":{}", expected_url));
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(&studio, &config_home, routing_url_args).success();
+    publish_request.assert();
+}
+
+#[test]
+fn it_keeps_the_routing_url_when_it_is_omitted() {
+    assert_publishes_url(&[], "null");
+}
+
+#[test]
+fn it_clears_the_routing_url_when_it_is_empty() {
+    assert_publishes_url(&["--routing-url", ""], "\"\"");
+}
+
+#[test]
+fn it_sets_the_routing_url_when_one_is_passed() {
+    assert_publishes_url(
+        &["--routing-url", "https://accounts.example.net/graphql"],
+        "\"https://accounts.example.net/graphql\"",
+    );
+}
+
+#[test]
+fn it_gives_up_when_the_publish_takes_longer_than_the_timeout() {
+    let studio = MockServer::start();
+    let routing_url_request = studio.mock(|when, then| {
+        when.body_contains("SubgraphRoutingUrlQuery");
+        then.status(200)
+            .delay(std::time::Duration::from_secs(3))
+            .json_body(json!({ "data": { "variant": null } }));
+    });
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    let started = std::time::Instant::now();
+    publish_with_args(&studio, &config_home, &["--timeout", "1"])
+        .failure()
+        .stderr(predicate::str::contains(
+            "The operation did not finish within its 1s timeout.",
+        ))
+        .stderr(predicate::str::contains(
+            "allow more time by passing a higher value to the --timeout option",
+        ));
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(3));
+    routing_url_request.assert_hits(1);
+}
+
+fn publish_with_proxy_env(
+    studio_url: &str,
+    proxy_env: &[(&str, &str)],
+) -> assert_cmd::assert::Assert {
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let schema = config_home.child("schema.graphql");
+    schema.write_str("type Query { hello: String }").unwrap();
+
+    let mut cmd = subgraph_publish(&config_home);
+    for name in [
+        "http_proxy",
+        "HTTP_PROXY",
+        "https_proxy",
+        "HTTPS_PROXY",
+        "all_proxy",
+        "ALL_PROXY",
+        "no_proxy",
+        "NO_PROXY",
+    ] {
+        cmd.env_remove(name);
+    }
+    cmd.envs(proxy_env.iter().copied())
+        .env("APOLLO_REGISTRY_URL", studio_url)
+        .args(["--name", "accounts", "--convert", "--schema"])
+        .arg(schema.path())
+        .assert()
+}
+
+#[test]
+fn it_publishes_through_the_proxy_in_http_proxy() {
+    let proxy = MockServer::start();
+    let publish_request = mock_successful_publish(&proxy);
+
+    // the registry's host doesn't resolve, so only the proxy can reach it
+    publish_with_proxy_env(
+        "http://studio.example.invalid/graphql",
+        &[("HTTP_PROXY", &proxy.base_url())],
+    )
+    .success();
+
+    publish_request.assert();
+}
+
+#[test]
+fn it_skips_the_proxy_for_hosts_in_no_proxy() {
+    let studio = MockServer::start();
+    let publish_request = mock_successful_publish(&studio);
+    let proxy = MockServer::start();
+    let proxied_request = proxy.mock(|when, then| {
+        when.any_request();
+        then.status(502);
+    });
+
+    publish_with_proxy_env(
+        &studio.url("/graphql"),
+        &[("HTTP_PROXY", &proxy.base_url()), ("NO_PROXY", "127.0.0.1")],
+    )
+    .success();
+
+    publish_request.assert();
+    proxied_request.assert_hits(0);
+}
+
+#[test]
+fn it_sends_custom_headers_with_every_studio_request() {
+    let studio = MockServer::start();
+    let routing_url_request = studio.mock(|when, then| {
+        when.body_contains("SubgraphRoutingUrlQuery")
+            .header("x-gateway-token", "abc:123")
+            .header("x-team", "accounts");
+        then.status(200).json_body(routing_url_body(ROUTING_URL));
+    });
+    let publish_request = studio.mock(|when, then| {
+        when.body_contains("SubgraphPublishMutation")
+            .header("x-gateway-token", "abc:123")
+            .header("x-team", "accounts");
+        then.status(200).json_body(published_body());
+    });
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(
+        &studio,
+        &config_home,
+        &[
+            "--header",
+            "X-Gateway-Token: abc:123",
+            "-H",
+            "x-team:accounts",
+            "--log",
+            "trace",
+        ],
+    )
+    .success()
+    // the headers are logged, but not their values
+    .stderr(predicate::str::contains("\"x-gateway-token\": Sensitive"))
+    .stderr(predicate::str::contains("abc:123").not());
+
+    routing_url_request.assert();
+    publish_request.assert();
+}
+
+#[test]
+fn it_rejects_malformed_headers() {
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    subgraph_publish(&config_home)
+        .args([
+            "--name",
+            "accounts",
+            "--header",
+            "noколон",
+            "--schema",
+            "schema.graphql",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Could not parse \"noколон\" as a header",
+        ));
+}
+
+#[test]
+fn it_does_not_accept_both_a_schema_and_a_schema_ref() {
+    let studio = MockServer::start();
+    let requests = studio.mock(|when, then| {
+        when.any_request();
+        then.status(500);
+    });
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(
+        &studio,
+        &config_home,
+        &["--schema-ref", "my-graph@staging:accounts"],
+    )
+    .failure()
+    .stderr(predicate::str::contains("cannot be used with"));
+    requests.assert_hits(0);
+}
+
+#[test]
+fn it_does_not_accept_a_response_file_while_watching() {
+    let studio = MockServer::start();
+    let requests = studio.mock(|when, then| {
+        when.any_request();
+        then.status(500);
+    });
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    publish_with_args(
+        &studio,
+        &config_home,
+        &["--watch", "--response-file", "publish.json"],
+    )
+    .failure()
+    .stderr(predicate::str::contains("cannot be used with"));
+    requests.assert_hits(0);
+}
+
+#[test]
+fn it_publishes_the_schema_of_a_schema_ref() {
+    let studio = MockServer::start();
+    let sdl = "type Query { staged: String }";
+    let fetch = studio.mock(|when, then| {
+        when.body_contains("SubgraphFetchQuery")
+            .body_contains("my-graph@staging");
+        then.status(200).json_body(json!({
+            "data": {
+                "variant": {
+                    "__typename": "GraphVariant",
+                    "subgraph": {
+                        "url": "https://accounts.example.com/graphql",
+                        "activePartialSchema": { "sdl": sdl }
+                    },
+                    "subgraphs": [{ "name": "accounts" }]
+                }
+            }
+        }));
+    });
+    studio.mock(|when, then| {
+        when.body_contains("IsFederatedGraph");
+        then.status(200).json_body(json!({
+            "data": { "graph": { "variant": { "subgraphs": [{ "name": "accounts" }] } } }
+        }));
+    });
+    let publish = mock_successful_publish_containing(&studio, sdl);
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    subgraph_publish_to(&studio, &config_home)
+        .args([
+            "--name",
+            "accounts",
+            "--schema-ref",
+            "my-graph@staging:accounts",
+            "--convert",
+        ])
+        .assert()
+        .success();
+    fetch.assert();
+    publish.assert();
+}
+
+#[test]
+fn it_rejects_malformed_schema_refs() {
+    let config_home = assert_fs::TempDir::new().unwrap();
+
+    subgraph_publish(&config_home)
+        .args(["--name", "accounts", "--schema-ref", "my-graph@staging"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("graph@variant:subgraph"));
+}
+
+#[test]
+fn it_names_the_env_var_when_the_routing_url_fetch_attempts_are_invalid() {
+    let config_home = assert_fs::TempDir::new().unwrap();
+    let schema = config_home.child("schema.graphql");
+    schema.write_str("type Query { hello: String }").unwrap();
+
+    subgraph_publish(&config_home)
+        .env("APOLLO_ROUTING_URL_FETCH_ATTEMPTS", "lots")
+        .args([
+            "--name",
+            "accounts",
+            "--schema",
+            schema.path().to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "APOLLO_ROUTING_URL_FETCH_ATTEMPTS must be a number of attempts, like `3`, but it is `lots`",
+        ));
+}